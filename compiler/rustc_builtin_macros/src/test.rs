@@ -9,6 +9,7 @@
 use rustc_ast_pretty::pprust;
 use rustc_errors::{Applicability, Diag, Level};
 use rustc_expand::base::*;
+use rustc_session::parse::feature_err;
 use rustc_span::symbol::{sym, Ident, Symbol};
 use rustc_span::{ErrorGuaranteed, FileNameDisplayPreference, Span};
 use thin_vec::{thin_vec, ThinVec};
@@ -234,26 +235,41 @@ pub(crate) fn expand_test_or_bench(
             ],
         )
     } else {
+        let is_async =
+            matches!(fn_.sig.header.coroutine_kind, Some(ast::CoroutineKind::Async { .. }));
+        let test_fn_call =
+            cx.expr_call(ret_ty_sp, cx.expr_path(cx.path(sp, vec![item.ident])), ThinVec::new());
+        // A `#[test_generator]` function returns the cases to run instead of
+        // being run itself.
+        let (test_fn_kind, collect_result) =
+            if attr::contains_name(&item.attrs, sym::test_generator) {
+                ("StaticTestGenFn", "collect_test_cases")
+            } else {
+                ("StaticTestFn", "assert_test_result")
+            };
         cx.expr_call(
             sp,
-            cx.expr_path(test_path("StaticTestFn")),
+            cx.expr_path(test_path(test_fn_kind)),
             thin_vec![
                 // #[coverage(off)]
                 // || {
                 coverage_off(cx.lambda0(
                     sp,
-                    // test::assert_test_result(
+                    // test::assert_test_result( or test::collect_test_cases(
                     cx.expr_call(
                         sp,
-                        cx.expr_path(test_path("assert_test_result")),
-                        thin_vec![
-                            // $test_fn()
+                        cx.expr_path(test_path(collect_result)),
+                        thin_vec![if is_async {
+                            // test::block_on($test_fn())
                             cx.expr_call(
-                                ret_ty_sp,
-                                cx.expr_path(cx.path(sp, vec![item.ident])),
-                                ThinVec::new(),
-                            ), // )
-                        ],
+                                sp,
+                                cx.expr_path(test_path("block_on")),
+                                thin_vec![test_fn_call],
+                            )
+                        } else {
+                            // $test_fn()
+                            test_fn_call
+                        }], // )
                     ), // }
                 )), // )
             ],
@@ -351,6 +367,22 @@ pub(crate) fn expand_test_or_bench(
                                             ),
                                         },
                                     ),
+                                    // timeout: Some(test::parse_test_timeout("...")) | None
+                                    field(
+                                        "timeout",
+                                        if let Some(value) = test_timeout(&item) {
+                                            cx.expr_some(
+                                                sp,
+                                                cx.expr_call(
+                                                    sp,
+                                                    cx.expr_path(test_path("parse_test_timeout")),
+                                                    thin_vec![cx.expr_str(sp, value)],
+                                                ),
+                                            )
+                                        } else {
+                                            cx.expr_none(sp)
+                                        },
+                                    ),
                                     // test_type: ...
                                     field(
                                         "test_type",
@@ -524,6 +556,15 @@ fn should_panic(cx: &ExtCtxt<'_>, i: &ast::Item) -> ShouldPanic {
     }
 }
 
+/// Returns the time limit set through `#[test_timeout = "..."]`.
+///
+/// The value is parsed by libtest while evaluating the test's constant, so that it
+/// accepts exactly the same format as `--test-timeout`.
+fn test_timeout(i: &ast::Item) -> Option<Symbol> {
+    // A missing value is reported as a malformed attribute already.
+    attr::find_by_name(&i.attrs, sym::test_timeout)?.value_str()
+}
+
 enum TestType {
     UnitTest,
     IntegrationTest,
@@ -566,13 +607,17 @@ fn check_test_signature(
 
     if let Some(coroutine_kind) = f.sig.header.coroutine_kind {
         match coroutine_kind {
-            ast::CoroutineKind::Async { span, .. } => {
-                return Err(dcx.emit_err(errors::TestBadFn {
-                    span: i.span,
-                    cause: span,
-                    kind: "async",
-                }));
+            // Async tests are run on libtest's built-in executor.
+            ast::CoroutineKind::Async { span, .. } if !cx.ecfg.features.async_test => {
+                return Err(feature_err(
+                    cx.sess,
+                    sym::async_test,
+                    span,
+                    "async functions as tests are experimental",
+                )
+                .emit());
             }
+            ast::CoroutineKind::Async { .. } => {}
             ast::CoroutineKind::Gen { span, .. } => {
                 return Err(dcx.emit_err(errors::TestBadFn {
                     span: i.span,
@@ -602,7 +647,9 @@ fn check_test_signature(
         return Err(dcx.span_err(i.span, "functions used as tests can not have any arguments"));
     }
 
-    if has_should_panic_attr && has_output {
+    // The cases generated by a `#[test_generator]` function are the ones expected to panic.
+    let is_generator = attr::contains_name(&i.attrs, sym::test_generator);
+    if has_should_panic_attr && has_output && !is_generator {
         return Err(dcx.span_err(i.span, "functions using `#[should_panic]` must return `()`"));
     }
 
@@ -626,5 +673,10 @@ fn check_bench_signature(
     if f.sig.decl.inputs.len() != 1 {
         return Err(cx.dcx().emit_err(errors::BenchSig { span: i.span }));
     }
+    if let Some(attr) = attr::find_by_name(&i.attrs, sym::test_generator) {
+        return Err(cx
+            .dcx()
+            .span_err(attr.span, "`#[test_generator]` can only be used on `#[test]` functions"));
+    }
     Ok(())
 }
@@ -1,7 +1,8 @@
 use std::ffi::OsStr;
 use std::fs;
+use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 use crate::path::{Dirs, RelPath};
 use crate::prepare::{apply_patches, GitRepo};
@@ -77,6 +78,40 @@ const fn jit_bin(config: &'static str, source: &'static str, args: &'static str)
     TestCase::build_bin_and_run("aot.alloc_example", "example/alloc_example.rs", &[]),
     TestCase::jit_bin("jit.std_example", "example/std_example.rs", "arg"),
     TestCase::build_bin_and_run("aot.std_example", "example/std_example.rs", &["arg"]),
+    TestCase::custom("jit.hot_reload", &|runner| {
+        // The source is modified while the program runs, so compile a copy of it.
+        let source = BUILD_EXAMPLE_OUT_DIR.to_path(&runner.dirs).join("hot_reload.rs");
+        fs::copy("example/hot_reload.rs", &source).unwrap();
+
+        let mut jit_cmd = runner.rustc_command([
+            "-Zunstable-options",
+            "-Cllvm-args=mode=jit-hot-reload",
+            "-Cprefer-dynamic",
+        ]);
+        jit_cmd.arg(&source).args(["--cfg", "jit"]).stdout(Stdio::piped());
+        let mut child = jit_cmd.spawn().unwrap();
+        let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+        let mut wait_for_line = |expected: &str| {
+            for line in &mut lines {
+                if line.unwrap() == expected {
+                    return;
+                }
+            }
+            panic!("hot reload test exited before printing `{expected}`");
+        };
+
+        wait_for_line("value: 1");
+        let code = fs::read_to_string(&source).unwrap();
+        fs::write(
+            &source,
+            code.replace("fn value() -> u32 {\n    1\n}", "fn value() -> u32 {\n    2\n}"),
+        )
+        .unwrap();
+        wait_for_line("value: 2");
+
+        let status = child.wait().unwrap();
+        assert!(status.success(), "hot reload test failed with {status}");
+    }),
     TestCase::build_bin_and_run("aot.dst_field_align", "example/dst-field-align.rs", &[]),
     TestCase::build_bin_and_run(
         "aot.subslice-patterns-const-eval",
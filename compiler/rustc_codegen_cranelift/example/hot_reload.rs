@@ -0,0 +1,19 @@
+// The build system replaces the value returned by `value` while this program runs.
+
+use std::time::Duration;
+
+fn value() -> u32 {
+    1
+}
+
+fn main() {
+    for _ in 0..600 {
+        let value = value();
+        println!("value: {value}");
+        if value == 2 {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    panic!("the change to `value` wasn't hot reloaded");
+}
@@ -6,7 +6,8 @@
     extern_types,
     naked_functions,
     thread_local,
-    repr_simd
+    repr_simd,
+    asm_goto
 )]
 #![no_core]
 #![allow(dead_code, non_camel_case_types, internal_features)]
@@ -338,6 +339,15 @@ struct ExternTypeWrapper {
         naked_test();
     }
 
+    #[cfg(all(
+        not(jit),
+        any(
+            all(target_arch = "x86_64", any(target_os = "linux", target_os = "macos")),
+            all(target_arch = "aarch64", target_os = "linux")
+        )
+    ))]
+    asm_sym_and_label_test();
+
     // Both statics have a reference that points to the same anonymous allocation.
     static REF1: &u8 = &42;
     static REF2: &u8 = REF1;
@@ -394,6 +404,86 @@ extern "C" fn naked_test() {
     }
 }
 
+#[cfg(all(not(jit), target_arch = "x86_64", any(target_os = "linux", target_os = "macos")))]
+fn asm_sym_and_label_test() {
+    static FORTY_TWO: u64 = 42;
+
+    extern "C" fn forty_three() -> u64 {
+        43
+    }
+
+    let value: u64;
+    let function: extern "C" fn() -> u64;
+    unsafe {
+        asm!(
+            "mov {value}, qword ptr [rip + {forty_two}]",
+            "lea {function}, [rip + {forty_three}]",
+            value = out(reg) value,
+            function = out(reg) function,
+            forty_two = sym FORTY_TWO,
+            forty_three = sym forty_three,
+        );
+    }
+    assert_eq!(value, 42);
+    assert_eq!(function(), 43);
+
+    let mut taken = 0;
+    unsafe {
+        asm!("cmp {value}, 42", "je {equal}", value = in(reg) value, equal = label { taken = 1; });
+    }
+    assert_eq!(taken, 1);
+    unsafe {
+        asm!("cmp {value}, 0", "je {equal}", value = in(reg) value, equal = label { taken = 2; });
+    }
+    assert_eq!(taken, 1);
+}
+
+#[cfg(all(not(jit), target_arch = "aarch64", target_os = "linux"))]
+fn asm_sym_and_label_test() {
+    static FORTY_TWO: u64 = 42;
+
+    extern "C" fn forty_three() -> u64 {
+        43
+    }
+
+    let value: u64;
+    let function: extern "C" fn() -> u64;
+    unsafe {
+        asm!(
+            "adrp {value}, {forty_two}",
+            "ldr {value}, [{value}, :lo12:{forty_two}]",
+            "adrp {function}, {forty_three}",
+            "add {function}, {function}, :lo12:{forty_three}",
+            value = out(reg) value,
+            function = out(reg) function,
+            forty_two = sym FORTY_TWO,
+            forty_three = sym forty_three,
+        );
+    }
+    assert_eq!(value, 42);
+    assert_eq!(function(), 43);
+
+    let mut taken = 0;
+    unsafe {
+        asm!(
+            "cmp {value}, #42",
+            "b.eq {equal}",
+            value = in(reg) value,
+            equal = label { taken = 1; },
+        );
+    }
+    assert_eq!(taken, 1);
+    unsafe {
+        asm!(
+            "cmp {value}, #0",
+            "b.eq {equal}",
+            value = in(reg) value,
+            equal = label { taken = 2; },
+        );
+    }
+    assert_eq!(taken, 1);
+}
+
 #[repr(C)]
 enum c_void {
     _1,
@@ -66,6 +66,18 @@ fn main() {
                 ])
                 .collect()
         }
+        Some("hot-reload-jit") => {
+            rustflags.push("-Cprefer-dynamic".to_owned());
+            args.remove(0);
+            IntoIterator::into_iter(["rustc".to_string()])
+                .chain(args)
+                .chain([
+                    "--".to_string(),
+                    "-Zunstable-options".to_string(),
+                    "-Cllvm-args=mode=jit-hot-reload".to_string(),
+                ])
+                .collect()
+        }
         _ => args,
     };
 
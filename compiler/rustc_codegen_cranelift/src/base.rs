@@ -512,25 +512,13 @@ fn codegen_fn_body(fx: &mut FunctionCx<'_, '_, '_>, start_block: Block) {
                     );
                 }
 
-                let have_labels = if options.contains(InlineAsmOptions::NORETURN) {
-                    !targets.is_empty()
-                } else {
-                    targets.len() > 1
-                };
-                if have_labels {
-                    fx.tcx.dcx().span_fatal(
-                        source_info.span,
-                        "cranelift doesn't support labels in inline assembly.",
-                    );
-                }
-
                 crate::inline_asm::codegen_inline_asm_terminator(
                     fx,
                     source_info.span,
                     template,
                     operands,
                     *options,
-                    targets.get(0).copied(),
+                    targets,
                 );
             }
             TerminatorKind::UnwindTerminate(reason) => {
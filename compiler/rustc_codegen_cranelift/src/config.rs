@@ -14,6 +14,9 @@ pub enum CodegenMode {
     Jit,
     /// JIT compile and execute the crate, but only compile functions the first time they are used.
     JitLazy,
+    /// Like `JitLazy`, but also watch the source files of the crate and swap in the functions whose
+    /// MIR changed while the program keeps running.
+    JitHotReload,
 }
 
 impl FromStr for CodegenMode {
@@ -24,6 +27,7 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
             "aot" => Ok(CodegenMode::Aot),
             "jit" => Ok(CodegenMode::Jit),
             "jit-lazy" => Ok(CodegenMode::JitLazy),
+            "jit-hot-reload" => Ok(CodegenMode::JitHotReload),
             _ => Err(format!("Unknown codegen mode `{}`", s)),
         }
     }
@@ -1,10 +1,17 @@
 // Adapted from https://github.com/rust-lang/rust/blob/10a7aa14fed9b528b74b0f098c4899c37c09a9c7/compiler/rustc_codegen_llvm/src/debuginfo/metadata.rs
 
+use std::iter;
+
 use gimli::write::{AttributeValue, UnitEntryId};
-use rustc_codegen_ssa::debuginfo::type_names;
+use rustc_codegen_ssa::debuginfo::{tag_base_type, type_names, wants_c_like_enum_debuginfo};
 use rustc_data_structures::fx::FxHashMap;
-use rustc_middle::ty::layout::LayoutOf;
-use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_hir::def::CtorKind;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::layout::{LayoutOf, TyAndLayout};
+use rustc_middle::ty::{self, AdtDef, AdtKind, Ty, TyCtxt, VariantDef};
+use rustc_target::abi::{
+    Align, Size, TagEncoding, VariantIdx, Variants, FAT_PTR_ADDR, FAT_PTR_EXTRA,
+};
 
 use crate::{has_ptr_meta, DebugContext, RevealAllLayoutCx};
 
@@ -46,17 +53,32 @@ pub(crate) fn debug_type<'tcx>(
                 *elem_ty,
                 len.eval_target_usize(tcx, ty::ParamEnv::reveal_all()),
             ),
-            // ty::Slice(_) | ty::Str
-            // ty::Dynamic
-            // ty::Foreign
+            // Slices and str are described by their element type, the length is part of the
+            // fat pointer pointing to them.
+            ty::Slice(elem_ty) => self.debug_type(tcx, type_dbg, *elem_ty),
+            ty::Str => self.debug_type(tcx, type_dbg, tcx.types.u8),
+            ty::Dynamic(..) | ty::Foreign(..) => self.opaque_type(tcx, ty),
             ty::RawPtr(pointee_type, _) | ty::Ref(_, pointee_type, _) => {
                 self.pointer_type(tcx, type_dbg, ty, *pointee_type)
             }
-            // ty::Adt(def, args) if def.is_box() && args.get(1).map_or(true, |arg| cx.layout_of(arg.expect_ty()).is_1zst())
-            // ty::FnDef(..) | ty::FnPtr(..)
-            // ty::Closure(..)
-            // ty::Adt(def, ..)
+            ty::Adt(def, args)
+                if def.is_box()
+                    && args.get(1).map_or(true, |arg| {
+                        RevealAllLayoutCx(tcx).layout_of(arg.expect_ty()).is_1zst()
+                    }) =>
+            {
+                self.pointer_type(tcx, type_dbg, ty, ty.boxed_ty())
+            }
+            // ty::FnDef(..)
+            ty::FnPtr(..) => self.fn_ptr_type(tcx, type_dbg, ty),
+            ty::Closure(def_id, _) => self.closure_type(tcx, type_dbg, ty, *def_id),
+            ty::Adt(def, _) => match def.adt_kind() {
+                AdtKind::Struct => self.struct_type(tcx, type_dbg, ty, *def),
+                AdtKind::Union => self.union_type(tcx, type_dbg, ty, *def),
+                AdtKind::Enum => self.enum_type(tcx, type_dbg, ty, *def),
+            },
             ty::Tuple(components) => self.tuple_type(tcx, type_dbg, ty, *components),
+            // ty::CoroutineClosure(..) | ty::Coroutine(..)
             // ty::Param(_)
             // FIXME implement remaining types and add unreachable!() to the fallback branch
             _ => self.placeholder_for_type(tcx, type_dbg, ty),
@@ -129,7 +151,7 @@ fn pointer_type<'tcx>(
 
         let name = type_names::compute_debuginfo_type_name(tcx, ptr_type, true);
 
-        if !has_ptr_meta(tcx, ptr_type) {
+        if !has_ptr_meta(tcx, pointee_type) {
             let pointer_type_id =
                 self.dwarf.unit.add(self.dwarf.unit.root(), gimli::DW_TAG_pointer_type);
             let pointer_entry = self.dwarf.unit.get_mut(pointer_type_id);
@@ -139,11 +161,104 @@ fn pointer_type<'tcx>(
 
             pointer_type_id
         } else {
-            // FIXME implement debuginfo for fat pointers
-            self.placeholder_for_type(tcx, type_dbg, ptr_type)
+            let cx = RevealAllLayoutCx(tcx);
+            // The layout of a `Box<T>` doesn't have the data pointer and the metadata as fields.
+            let layout_type =
+                if ptr_type.is_box() { Ty::new_mut_ptr(tcx, pointee_type) } else { ptr_type };
+            let layout = cx.layout_of(layout_type);
+            let addr_field = layout.field(&cx, FAT_PTR_ADDR);
+            let extra_field = layout.field(&cx, FAT_PTR_EXTRA);
+            let extra_dw_ty = self.debug_type(tcx, type_dbg, extra_field.ty);
+
+            let (addr_field_name, extra_field_name) = match tcx
+                .struct_tail_for_codegen(pointee_type, ty::ParamEnv::reveal_all())
+                .kind()
+            {
+                ty::Dynamic(..) => ("pointer", "vtable"),
+                _ => ("data_ptr", "length"),
+            };
+
+            let fat_pointer_type_id = self.composite_type(
+                self.dwarf.unit.root(),
+                gimli::DW_TAG_structure_type,
+                &name,
+                layout,
+            );
+
+            let addr_dw_ty =
+                self.dwarf.unit.add(self.dwarf.unit.root(), gimli::DW_TAG_pointer_type);
+            self.dwarf
+                .unit
+                .get_mut(addr_dw_ty)
+                .set(gimli::DW_AT_type, AttributeValue::UnitRef(pointee_dw_ty));
+
+            self.add_member(
+                fat_pointer_type_id,
+                addr_field_name,
+                addr_dw_ty,
+                addr_field.align.pref,
+                layout.fields.offset(FAT_PTR_ADDR),
+            );
+            self.add_member(
+                fat_pointer_type_id,
+                extra_field_name,
+                extra_dw_ty,
+                extra_field.align.pref,
+                layout.fields.offset(FAT_PTR_EXTRA),
+            );
+
+            fat_pointer_type_id
         }
     }
 
+    fn fn_ptr_type<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_dbg: &mut TypeDebugContext<'tcx>,
+        fn_ptr_type: Ty<'tcx>,
+    ) -> UnitEntryId {
+        let sig = tcx.normalize_erasing_late_bound_regions(
+            ty::ParamEnv::reveal_all(),
+            fn_ptr_type.fn_sig(tcx),
+        );
+        let output_dw_ty = if sig.output().is_unit() {
+            None
+        } else {
+            Some(self.debug_type(tcx, type_dbg, sig.output()))
+        };
+        let input_dw_tys =
+            sig.inputs().iter().map(|&ty| self.debug_type(tcx, type_dbg, ty)).collect::<Vec<_>>();
+
+        return_if_type_created_in_meantime!(type_dbg, fn_ptr_type);
+
+        let subroutine_type_id =
+            self.dwarf.unit.add(self.dwarf.unit.root(), gimli::DW_TAG_subroutine_type);
+        if let Some(output_dw_ty) = output_dw_ty {
+            self.dwarf
+                .unit
+                .get_mut(subroutine_type_id)
+                .set(gimli::DW_AT_type, AttributeValue::UnitRef(output_dw_ty));
+        }
+        for input_dw_ty in input_dw_tys {
+            let param_id = self.dwarf.unit.add(subroutine_type_id, gimli::DW_TAG_formal_parameter);
+            self.dwarf
+                .unit
+                .get_mut(param_id)
+                .set(gimli::DW_AT_type, AttributeValue::UnitRef(input_dw_ty));
+        }
+
+        // This is actually a function pointer, so wrap it in a pointer type.
+        let name = type_names::compute_debuginfo_type_name(tcx, fn_ptr_type, false);
+        let pointer_type_id =
+            self.dwarf.unit.add(self.dwarf.unit.root(), gimli::DW_TAG_pointer_type);
+        let pointer_entry = self.dwarf.unit.get_mut(pointer_type_id);
+        pointer_entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(subroutine_type_id));
+        pointer_entry
+            .set(gimli::DW_AT_name, AttributeValue::StringRef(self.dwarf.strings.add(name)));
+
+        pointer_type_id
+    }
+
     fn tuple_type<'tcx>(
         &mut self,
         tcx: TyCtxt<'tcx>,
@@ -161,32 +276,374 @@ fn tuple_type<'tcx>(
         let name = type_names::compute_debuginfo_type_name(tcx, tuple_type, false);
         let layout = RevealAllLayoutCx(tcx).layout_of(tuple_type);
 
-        let tuple_type_id =
-            self.dwarf.unit.add(self.dwarf.unit.root(), gimli::DW_TAG_structure_type);
-        let tuple_entry = self.dwarf.unit.get_mut(tuple_type_id);
-        tuple_entry.set(gimli::DW_AT_name, AttributeValue::StringRef(self.dwarf.strings.add(name)));
-        tuple_entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(layout.size.bytes()));
-        tuple_entry.set(gimli::DW_AT_alignment, AttributeValue::Udata(layout.align.pref.bytes()));
+        let tuple_type_id = self.composite_type(
+            self.dwarf.unit.root(),
+            gimli::DW_TAG_structure_type,
+            &name,
+            layout,
+        );
 
         for (i, (ty, dw_ty)) in components.into_iter().enumerate() {
-            let member_id = self.dwarf.unit.add(tuple_type_id, gimli::DW_TAG_member);
-            let member_entry = self.dwarf.unit.get_mut(member_id);
-            member_entry.set(
+            self.add_member(
+                tuple_type_id,
+                &format!("__{i}"),
+                dw_ty,
+                RevealAllLayoutCx(tcx).layout_of(ty).align.pref,
+                layout.fields.offset(i),
+            );
+        }
+
+        tuple_type_id
+    }
+
+    fn closure_type<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_dbg: &mut TypeDebugContext<'tcx>,
+        closure_type: Ty<'tcx>,
+        def_id: DefId,
+    ) -> UnitEntryId {
+        let cx = RevealAllLayoutCx(tcx);
+        let layout = cx.layout_of(closure_type);
+        let scope = self.item_namespace(tcx, tcx.parent(def_id));
+        let name = type_names::compute_debuginfo_type_name(tcx, closure_type, false);
+
+        let closure_type_id =
+            self.composite_type(scope, gimli::DW_TAG_structure_type, &name, layout);
+        type_dbg.type_map.insert(closure_type, closure_type_id);
+
+        let upvar_names = tcx.closure_saved_names_of_captured_variables(def_id);
+        for (i, upvar_name) in upvar_names.iter().enumerate() {
+            let upvar_layout = layout.field(&cx, i);
+            let upvar_dw_ty = self.debug_type(tcx, type_dbg, upvar_layout.ty);
+            self.add_member(
+                closure_type_id,
+                upvar_name.as_str(),
+                upvar_dw_ty,
+                upvar_layout.align.pref,
+                layout.fields.offset(i),
+            );
+        }
+
+        closure_type_id
+    }
+
+    fn struct_type<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_dbg: &mut TypeDebugContext<'tcx>,
+        struct_type: Ty<'tcx>,
+        adt_def: AdtDef<'tcx>,
+    ) -> UnitEntryId {
+        self.struct_or_union_type(tcx, type_dbg, struct_type, adt_def, gimli::DW_TAG_structure_type)
+    }
+
+    fn union_type<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_dbg: &mut TypeDebugContext<'tcx>,
+        union_type: Ty<'tcx>,
+        adt_def: AdtDef<'tcx>,
+    ) -> UnitEntryId {
+        self.struct_or_union_type(tcx, type_dbg, union_type, adt_def, gimli::DW_TAG_union_type)
+    }
+
+    fn struct_or_union_type<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_dbg: &mut TypeDebugContext<'tcx>,
+        adt_type: Ty<'tcx>,
+        adt_def: AdtDef<'tcx>,
+        tag: gimli::DwTag,
+    ) -> UnitEntryId {
+        let layout = RevealAllLayoutCx(tcx).layout_of(adt_type);
+        let scope = self.item_namespace(tcx, tcx.parent(adt_def.did()));
+        let name = type_names::compute_debuginfo_type_name(tcx, adt_type, false);
+
+        // Insert the type before its fields to allow recursive types like linked lists.
+        let adt_type_id = self.composite_type(scope, tag, &name, layout);
+        self.dwarf.unit.get_mut(adt_type_id).set(
+            gimli::DW_AT_accessibility,
+            AttributeValue::Accessibility(accessibility(tcx, adt_def.did(), adt_def.did())),
+        );
+        type_dbg.type_map.insert(adt_type, adt_type_id);
+
+        self.variant_fields(
+            tcx,
+            type_dbg,
+            adt_type_id,
+            adt_def.did(),
+            adt_def.non_enum_variant(),
+            layout,
+        );
+        self.generic_type_params(tcx, type_dbg, adt_type_id, adt_type);
+
+        adt_type_id
+    }
+
+    /// Describes an enum either as a C-like `DW_TAG_enumeration_type` if none of its variants
+    /// have fields, or as a `DW_TAG_structure_type` with a `DW_TAG_variant_part` following the
+    /// actual layout of the enum. In the latter case the `DW_TAG_variant_part` refers to an
+    /// artificial member holding the tag, and each `DW_TAG_variant` has the tag value which
+    /// selects it, if any. For niche-filling layouts the untagged variant is the one without a
+    /// tag value. Layouts with a single variant only describe that variant, as the others are
+    /// uninhabited.
+    fn enum_type<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_dbg: &mut TypeDebugContext<'tcx>,
+        enum_type: Ty<'tcx>,
+        adt_def: AdtDef<'tcx>,
+    ) -> UnitEntryId {
+        let cx = RevealAllLayoutCx(tcx);
+        let layout = cx.layout_of(enum_type);
+
+        if wants_c_like_enum_debuginfo(tcx, layout) {
+            return self.c_like_enum_type(tcx, type_dbg, enum_type, adt_def, layout);
+        }
+
+        let scope = self.item_namespace(tcx, tcx.parent(adt_def.did()));
+        let name = type_names::compute_debuginfo_type_name(tcx, enum_type, false);
+        let enum_accessibility = accessibility(tcx, adt_def.did(), adt_def.did());
+
+        let enum_type_id = self.composite_type(scope, gimli::DW_TAG_structure_type, &name, layout);
+        self.dwarf
+            .unit
+            .get_mut(enum_type_id)
+            .set(gimli::DW_AT_accessibility, AttributeValue::Accessibility(enum_accessibility));
+        type_dbg.type_map.insert(enum_type, enum_type_id);
+
+        let variant_part_id = self.dwarf.unit.add(enum_type_id, gimli::DW_TAG_variant_part);
+        if let Variants::Multiple { tag_field, .. } = layout.variants {
+            let tag_ty = tag_base_type(tcx, layout);
+            let tag_dw_ty = self.debug_type(tcx, type_dbg, tag_ty);
+            let tag_member_id = self.add_member(
+                variant_part_id,
+                "",
+                tag_dw_ty,
+                cx.layout_of(tag_ty).align.pref,
+                layout.fields.offset(tag_field),
+            );
+            self.dwarf
+                .unit
+                .get_mut(tag_member_id)
+                .set(gimli::DW_AT_artificial, AttributeValue::FlagPresent);
+            self.dwarf
+                .unit
+                .get_mut(variant_part_id)
+                .set(gimli::DW_AT_discr, AttributeValue::UnitRef(tag_member_id));
+        }
+
+        let live_variant = match layout.variants {
+            Variants::Single { index } => Some(index),
+            Variants::Multiple { .. } => None,
+        };
+        for variant_index in adt_def.variant_range() {
+            if live_variant.is_some_and(|index| index != variant_index) {
+                continue;
+            }
+
+            let variant_def = adt_def.variant(variant_index);
+            let variant_name = variant_def.name.as_str();
+
+            // The struct for each variant has the size of the whole enum, as the fields of a
+            // variant are at offsets relative to the start of the enum.
+            let variant_struct_id = self.composite_type(
+                enum_type_id,
+                gimli::DW_TAG_structure_type,
+                variant_name,
+                layout,
+            );
+            self.dwarf
+                .unit
+                .get_mut(variant_struct_id)
+                .set(gimli::DW_AT_accessibility, AttributeValue::Accessibility(enum_accessibility));
+            self.variant_fields(
+                tcx,
+                type_dbg,
+                variant_struct_id,
+                adt_def.did(),
+                variant_def,
+                layout.for_variant(&cx, variant_index),
+            );
+            self.generic_type_params(tcx, type_dbg, variant_struct_id, enum_type);
+
+            let variant_id = self.dwarf.unit.add(variant_part_id, gimli::DW_TAG_variant);
+            if let Some(discr_value) = discriminant_value(tcx, layout, variant_index) {
+                self.dwarf.unit.get_mut(variant_id).set(gimli::DW_AT_discr_value, discr_value);
+            }
+            self.add_member(
+                variant_id,
+                variant_name,
+                variant_struct_id,
+                layout.align.pref,
+                Size::ZERO,
+            );
+        }
+
+        enum_type_id
+    }
+
+    fn c_like_enum_type<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_dbg: &mut TypeDebugContext<'tcx>,
+        enum_type: Ty<'tcx>,
+        adt_def: AdtDef<'tcx>,
+        layout: TyAndLayout<'tcx>,
+    ) -> UnitEntryId {
+        let tag_ty = tag_base_type(tcx, layout);
+        let tag_dw_ty = self.debug_type(tcx, type_dbg, tag_ty);
+        let tag_size = RevealAllLayoutCx(tcx).layout_of(tag_ty).size;
+        let is_unsigned = match tag_ty.kind() {
+            ty::Int(_) => false,
+            ty::Uint(_) => true,
+            _ => unreachable!(),
+        };
+
+        return_if_type_created_in_meantime!(type_dbg, enum_type);
+
+        let scope = self.item_namespace(tcx, tcx.parent(adt_def.did()));
+        let name = type_names::compute_debuginfo_type_name(tcx, enum_type, false);
+
+        let enum_type_id =
+            self.composite_type(scope, gimli::DW_TAG_enumeration_type, &name, layout);
+        let enum_type_entry = self.dwarf.unit.get_mut(enum_type_id);
+        enum_type_entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(tag_dw_ty));
+        enum_type_entry.set(
+            gimli::DW_AT_accessibility,
+            AttributeValue::Accessibility(accessibility(tcx, adt_def.did(), adt_def.did())),
+        );
+
+        for (variant_index, discr) in adt_def.discriminants(tcx) {
+            let enumerator_id = self.dwarf.unit.add(enum_type_id, gimli::DW_TAG_enumerator);
+            let enumerator_entry = self.dwarf.unit.get_mut(enumerator_id);
+            enumerator_entry.set(
                 gimli::DW_AT_name,
-                AttributeValue::StringRef(self.dwarf.strings.add(format!("__{i}"))),
+                AttributeValue::StringRef(
+                    self.dwarf.strings.add(adt_def.variant(variant_index).name.as_str()),
+                ),
+            );
+            let value = if is_unsigned {
+                AttributeValue::Udata(tag_size.truncate(discr.val) as u64)
+            } else {
+                AttributeValue::Sdata(tag_size.sign_extend(discr.val) as i64)
+            };
+            enumerator_entry.set(gimli::DW_AT_const_value, value);
+        }
+
+        enum_type_id
+    }
+
+    fn opaque_type<'tcx>(&mut self, tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> UnitEntryId {
+        let layout = RevealAllLayoutCx(tcx).layout_of(ty);
+        let (scope, name) = match ty.kind() {
+            ty::Foreign(def_id) => (
+                self.item_namespace(tcx, tcx.parent(*def_id)),
+                type_names::compute_debuginfo_type_name(tcx, ty, false),
+            ),
+            _ => (self.dwarf.unit.root(), type_names::compute_debuginfo_type_name(tcx, ty, true)),
+        };
+
+        self.composite_type(scope, gimli::DW_TAG_structure_type, &name, layout)
+    }
+
+    /// Adds a member for each field of `variant_def` to `parent`, which is either a struct, a
+    /// union or the struct describing a single enum variant.
+    fn variant_fields<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_dbg: &mut TypeDebugContext<'tcx>,
+        parent: UnitEntryId,
+        adt_did: DefId,
+        variant_def: &VariantDef,
+        variant_layout: TyAndLayout<'tcx>,
+    ) {
+        let cx = RevealAllLayoutCx(tcx);
+        let is_tuple_like = variant_def.ctor_kind() == Some(CtorKind::Fn);
+        for (i, field_def) in variant_def.fields.iter().enumerate() {
+            let field_layout = variant_layout.field(&cx, i);
+            let field_dw_ty = self.debug_type(tcx, type_dbg, field_layout.ty);
+            let field_name =
+                if is_tuple_like { format!("__{i}") } else { field_def.name.to_string() };
+            let member_id = self.add_member(
+                parent,
+                &field_name,
+                field_dw_ty,
+                field_layout.align.pref,
+                variant_layout.fields.offset(i),
             );
-            member_entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(dw_ty));
-            member_entry.set(
-                gimli::DW_AT_alignment,
-                AttributeValue::Udata(RevealAllLayoutCx(tcx).layout_of(ty).align.pref.bytes()),
+            self.dwarf.unit.get_mut(member_id).set(
+                gimli::DW_AT_accessibility,
+                AttributeValue::Accessibility(accessibility(tcx, field_def.did, adt_did)),
             );
-            member_entry.set(
-                gimli::DW_AT_data_member_location,
-                AttributeValue::Udata(layout.fields.offset(i).bytes()),
+        }
+    }
+
+    fn generic_type_params<'tcx>(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        type_dbg: &mut TypeDebugContext<'tcx>,
+        parent: UnitEntryId,
+        ty: Ty<'tcx>,
+    ) {
+        let ty::Adt(def, args) = *ty.kind() else {
+            return;
+        };
+
+        let generics = tcx.generics_of(def.did());
+        for (arg, param) in iter::zip(args, &generics.own_params) {
+            let Some(arg_ty) = arg.as_type() else {
+                continue;
+            };
+            let arg_ty = tcx.normalize_erasing_regions(ty::ParamEnv::reveal_all(), arg_ty);
+            let arg_dw_ty = self.debug_type(tcx, type_dbg, arg_ty);
+
+            let param_id = self.dwarf.unit.add(parent, gimli::DW_TAG_template_type_parameter);
+            let param_entry = self.dwarf.unit.get_mut(param_id);
+            param_entry.set(
+                gimli::DW_AT_name,
+                AttributeValue::StringRef(self.dwarf.strings.add(param.name.as_str())),
             );
+            param_entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(arg_dw_ty));
         }
+    }
 
-        tuple_type_id
+    fn composite_type(
+        &mut self,
+        scope: UnitEntryId,
+        tag: gimli::DwTag,
+        name: &str,
+        layout: TyAndLayout<'_>,
+    ) -> UnitEntryId {
+        let type_id = self.dwarf.unit.add(scope, tag);
+        let type_entry = self.dwarf.unit.get_mut(type_id);
+        type_entry.set(gimli::DW_AT_name, AttributeValue::StringRef(self.dwarf.strings.add(name)));
+        type_entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(layout.size.bytes()));
+        type_entry.set(gimli::DW_AT_alignment, AttributeValue::Udata(layout.align.pref.bytes()));
+
+        type_id
+    }
+
+    fn add_member(
+        &mut self,
+        parent: UnitEntryId,
+        name: &str,
+        dw_ty: UnitEntryId,
+        align: Align,
+        offset: Size,
+    ) -> UnitEntryId {
+        let member_id = self.dwarf.unit.add(parent, gimli::DW_TAG_member);
+        let member_entry = self.dwarf.unit.get_mut(member_id);
+        if !name.is_empty() {
+            member_entry
+                .set(gimli::DW_AT_name, AttributeValue::StringRef(self.dwarf.strings.add(name)));
+        }
+        member_entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(dw_ty));
+        member_entry.set(gimli::DW_AT_alignment, AttributeValue::Udata(align.bytes()));
+        member_entry.set(gimli::DW_AT_data_member_location, AttributeValue::Udata(offset.bytes()));
+
+        member_id
     }
 
     fn placeholder_for_type<'tcx>(
@@ -202,3 +659,52 @@ fn placeholder_for_type<'tcx>(
         )
     }
 }
+
+fn accessibility(tcx: TyCtxt<'_>, did: DefId, type_did: DefId) -> gimli::DwAccess {
+    let parent_did = tcx.parent(type_did);
+    match tcx.visibility(did) {
+        ty::Visibility::Public => gimli::DW_ACCESS_public,
+        // Private fields have a restricted visibility of the module containing the type.
+        ty::Visibility::Restricted(did) if did == parent_did => gimli::DW_ACCESS_private,
+        // `pub(crate)`/`pub(super)` visibilities are any other restricted visibility.
+        ty::Visibility::Restricted(..) => gimli::DW_ACCESS_protected,
+    }
+}
+
+/// Returns the value of the tag selecting the given variant, in a form matching the size of the
+/// tag, or `None` if the variant isn't selected by a single tag value.
+fn discriminant_value<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    enum_layout: TyAndLayout<'tcx>,
+    variant_index: VariantIdx,
+) -> Option<AttributeValue> {
+    let (value, tag) = match enum_layout.variants {
+        Variants::Single { .. } => return None,
+        Variants::Multiple { tag_encoding: TagEncoding::Direct, tag, .. } => {
+            (enum_layout.ty.discriminant_for_variant(tcx, variant_index).unwrap().val, tag)
+        }
+        Variants::Multiple {
+            tag_encoding: TagEncoding::Niche { ref niche_variants, niche_start, untagged_variant },
+            tag,
+            ..
+        } => {
+            if variant_index == untagged_variant {
+                return None;
+            }
+            let value = (variant_index.as_u32() as u128)
+                .wrapping_sub(niche_variants.start().as_u32() as u128)
+                .wrapping_add(niche_start);
+            (value, tag)
+        }
+    };
+
+    let tag_size = tag.size(&RevealAllLayoutCx(tcx));
+    let value = tag_size.truncate(value);
+    Some(match tag_size.bytes() {
+        1 => AttributeValue::Data1(value as u8),
+        2 => AttributeValue::Data2(value as u16),
+        4 => AttributeValue::Data4(value as u32),
+        // FIXME 128-bit tags can't be represented by any of the forms supported by gimli
+        _ => AttributeValue::Data8(value as u64),
+    })
+}
@@ -528,7 +528,8 @@ fn module_codegen(
                     MonoItem::GlobalAsm(item_id) => {
                         crate::global_asm::codegen_global_asm_item(
                             tcx,
-                            &mut cx.global_asm,
+                            &mut cx,
+                            &mut module,
                             item_id,
                         );
                     }
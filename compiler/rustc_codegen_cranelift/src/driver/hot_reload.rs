@@ -0,0 +1,356 @@
+//! Hot reloading for the JIT driver.
+//!
+//! While the jitted program runs, the rustc thread polls the source files of the local crate. Once
+//! one of them changes, the crate is analyzed again in a nested compiler session and every
+//! function whose optimized MIR changed is recompiled. As all functions are called through the GOT
+//! in hotswap mode, the new code is used by every call made after the GOT entries are updated.
+//!
+//! Not every change can be applied to a running program:
+//!
+//! * Changing the signature of an existing function requires a restart.
+//! * Functions using a type whose layout changed are recompiled, but values created before the
+//!   reload keep the old layout, so changing the layout of a type with live values requires a
+//!   restart too.
+//! * Existing statics keep their current value, even if their initializer changed.
+//! * Spans are ignored when comparing MIR, so functions that only moved keep reporting their old
+//!   location in panic messages.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use rustc_data_structures::fingerprint::Fingerprint;
+use rustc_data_structures::fx::{FxHashSet, FxIndexSet};
+use rustc_data_structures::stable_hasher::{HashStable, StableHasher};
+use rustc_driver::{Callbacks, Compilation, RunCompiler};
+use rustc_interface::{interface, Queries};
+use rustc_middle::mir::mono::{MonoItem, MonoItemData};
+use rustc_middle::mir::visit::{PlaceContext, Visitor};
+use rustc_span::{FileName, Symbol};
+
+use super::jit::{codegen_and_compile_fn, JitState};
+use crate::prelude::*;
+
+/// How often the source files are checked for changes while the jitted program runs.
+pub(super) const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+pub(super) struct HotReload {
+    /// The arguments rustc was invoked with. Used to start the nested compiler sessions.
+    args: Vec<String>,
+    source_files: Vec<(PathBuf, Option<SystemTime>)>,
+    /// The fingerprint of everything every function was last compiled from, keyed by symbol name.
+    /// See [`fn_fingerprint`].
+    fn_fingerprints: FxHashMap<String, Fingerprint>,
+    /// The symbol names of all statics that have been defined.
+    statics: FxHashSet<String>,
+}
+
+impl HotReload {
+    pub(super) fn new<'tcx>(
+        tcx: TyCtxt<'tcx>,
+        mono_items: &[(MonoItem<'tcx>, MonoItemData)],
+    ) -> Self {
+        HotReload {
+            args: std::env::args().collect(),
+            source_files: source_files(tcx),
+            fn_fingerprints: fn_fingerprints(tcx, mono_items),
+            statics: statics(tcx, mono_items),
+        }
+    }
+
+    /// Recompiles the crate and swaps in all changed functions if any source file was modified
+    /// since the last check.
+    pub(super) fn reload_if_changed(&mut self, tcx: TyCtxt<'_>, jit_state: &mut JitState) {
+        let mut changed = false;
+        for (path, mtime) in &mut self.source_files {
+            let new_mtime = modification_time(path);
+            if new_mtime != *mtime {
+                *mtime = new_mtime;
+                changed = true;
+            }
+        }
+        if !changed {
+            return;
+        }
+
+        let start = Instant::now();
+        let mut callbacks = ReloadCallbacks {
+            jit_state,
+            fn_fingerprints: &mut self.fn_fingerprints,
+            statics: &mut self.statics,
+            module_modified: false,
+            reloaded: None,
+        };
+        let res =
+            rustc_driver::catch_fatal_errors(|| RunCompiler::new(&self.args, &mut callbacks).run());
+
+        match (res, callbacks.reloaded) {
+            (Ok(Ok(())), Some(Reloaded { changed_fns, new_fns, source_files })) => {
+                self.source_files = source_files;
+                tcx.dcx().note(format!(
+                    "hot reloaded {changed_fns} changed and {new_fns} new functions in {:.2?}",
+                    start.elapsed(),
+                ));
+            }
+            _ if callbacks.module_modified => {
+                // Part of the new code may already be in use, so there is no consistent state to
+                // go back to.
+                tcx.dcx().fatal("hot reload failed after modifying the running program");
+            }
+            _ => {
+                tcx.dcx()
+                    .warn("hot reload failed, the previous version of the program keeps running");
+            }
+        }
+    }
+}
+
+struct Reloaded {
+    changed_fns: usize,
+    new_fns: usize,
+    source_files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+struct ReloadCallbacks<'a> {
+    jit_state: &'a mut JitState,
+    fn_fingerprints: &'a mut FxHashMap<String, Fingerprint>,
+    statics: &'a mut FxHashSet<String>,
+    /// Whether any function or static of the running program has been redefined.
+    module_modified: bool,
+    reloaded: Option<Reloaded>,
+}
+
+impl Callbacks for ReloadCallbacks<'_> {
+    fn after_analysis<'tcx>(
+        &mut self,
+        _compiler: &interface::Compiler,
+        queries: &'tcx Queries<'tcx>,
+    ) -> Compilation {
+        queries.global_ctxt().unwrap().enter(|tcx| self.reload(tcx));
+        Compilation::Stop
+    }
+}
+
+impl ReloadCallbacks<'_> {
+    fn reload(&mut self, tcx: TyCtxt<'_>) {
+        let mono_items = super::jit::mono_items(tcx);
+        let fingerprints = fn_fingerprints(tcx, &mono_items);
+
+        let mut changed_fns = vec![];
+        let mut new_items = vec![];
+        for &(mono_item, data) in &mono_items {
+            match mono_item {
+                MonoItem::Fn(instance) => {
+                    let name = tcx.symbol_name(instance).name;
+                    match self.fn_fingerprints.get(name) {
+                        Some(fingerprint) if *fingerprint == fingerprints[name] => {}
+                        Some(_) => changed_fns.push(instance),
+                        None => new_items.push((mono_item, data)),
+                    }
+                }
+                MonoItem::Static(def_id) => {
+                    if !self.statics.contains(tcx.symbol_name(Instance::mono(tcx, def_id)).name) {
+                        new_items.push((mono_item, data));
+                    }
+                }
+                MonoItem::GlobalAsm(item_id) => {
+                    let item = tcx.hir().item(item_id);
+                    tcx.dcx().span_err(item.span, "Global asm is not supported in JIT mode");
+                }
+            }
+        }
+
+        let jit_module = &mut self.jit_state.jit_module;
+
+        // Check that all changed functions can be redefined before touching the running program.
+        let mut changed_fn_ids = Vec::with_capacity(changed_fns.len());
+        for &instance in &changed_fns {
+            let name = tcx.symbol_name(instance).name;
+            let sig = crate::abi::get_function_sig(
+                tcx,
+                jit_module.target_config().default_call_conv,
+                instance,
+            );
+            match jit_module.declare_function(name, Linkage::Export, &sig) {
+                Ok(func_id) => changed_fn_ids.push(func_id),
+                Err(err) => {
+                    let mut diag =
+                        tcx.dcx().struct_span_err(tcx.def_span(instance.def_id()), err.to_string());
+                    diag.note("changing the signature of a function requires a restart");
+                    diag.emit();
+                }
+            }
+        }
+        if tcx.dcx().has_errors().is_some() {
+            return;
+        }
+
+        self.module_modified = true;
+
+        let mut cx =
+            crate::CodegenCx::new(tcx, jit_module.isa(), false, Symbol::intern("dummy_cgu_name"));
+        let mut cached_context = Context::new();
+
+        for (&instance, func_id) in changed_fns.iter().zip(changed_fn_ids) {
+            jit_module.module.prepare_for_function_redefine(func_id).unwrap();
+            codegen_and_compile_fn(tcx, &mut cx, &mut cached_context, jit_module, instance);
+        }
+
+        // New functions are compiled eagerly rather than through a lazy-jit shim, as shims refer
+        // to their instance in the `TyCtxt` of this compiler session, which ends once the reload
+        // is done.
+        super::predefine_mono_items(tcx, jit_module, &new_items);
+        let mut new_fns = 0;
+        for &(mono_item, _) in &new_items {
+            match mono_item {
+                MonoItem::Fn(instance) => {
+                    codegen_and_compile_fn(tcx, &mut cx, &mut cached_context, jit_module, instance);
+                    new_fns += 1;
+                }
+                MonoItem::Static(def_id) => {
+                    crate::constant::codegen_static(tcx, jit_module, def_id);
+                }
+                MonoItem::GlobalAsm(_) => unreachable!(),
+            }
+        }
+
+        if !cx.global_asm.is_empty() {
+            tcx.dcx().fatal("Inline asm is not supported in JIT mode");
+        }
+
+        tcx.dcx().abort_if_errors();
+
+        jit_module.finalize_definitions();
+
+        self.fn_fingerprints.extend(fingerprints);
+        self.statics.extend(statics(tcx, &mono_items));
+        self.reloaded = Some(Reloaded {
+            changed_fns: changed_fns.len(),
+            new_fns,
+            source_files: source_files(tcx),
+        });
+    }
+}
+
+/// Returns all source files of the local crate that exist on disk.
+fn source_files(tcx: TyCtxt<'_>) -> Vec<(PathBuf, Option<SystemTime>)> {
+    tcx.sess
+        .source_map()
+        .files()
+        .iter()
+        .filter(|file| file.cnum == LOCAL_CRATE)
+        .filter_map(|file| match &file.name {
+            FileName::Real(name) => name.local_path().map(Path::to_owned),
+            _ => None,
+        })
+        .map(|path| {
+            let mtime = modification_time(&path);
+            (path, mtime)
+        })
+        .collect()
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn fn_fingerprints<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mono_items: &[(MonoItem<'tcx>, MonoItemData)],
+) -> FxHashMap<String, Fingerprint> {
+    mono_items
+        .iter()
+        .filter_map(|&(mono_item, _)| match mono_item {
+            MonoItem::Fn(instance) => {
+                Some((tcx.symbol_name(instance).name.to_owned(), fn_fingerprint(tcx, instance)))
+            }
+            MonoItem::Static(_) | MonoItem::GlobalAsm(_) => None,
+        })
+        .collect()
+}
+
+/// Fingerprints everything the code of a function is compiled from: its MIR, the MIR of all
+/// functions inlined into it, the layouts of the types of its places and the values of its
+/// constants. Other functions are called through the GOT, so a function only depends on the
+/// signature of its callees, which can't change without a restart.
+fn fn_fingerprint<'tcx>(tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) -> Fingerprint {
+    let mir = tcx.instance_mir(instance.def);
+
+    let mut deps = CodegenDeps {
+        tcx,
+        body: mir,
+        place_types: mir.local_decls.iter().map(|local_decl| local_decl.ty).collect(),
+        constants: vec![],
+    };
+    deps.visit_body(mir);
+
+    tcx.with_stable_hashing_context(|mut hcx| {
+        let mut hasher = StableHasher::new();
+        hcx.while_hashing_spans(false, |hcx| {
+            mir.hash_stable(hcx, &mut hasher);
+            for scope in mir.source_scopes.iter() {
+                if let Some((callee, _)) = scope.inlined {
+                    tcx.instance_mir(callee.def).hash_stable(hcx, &mut hasher);
+                }
+            }
+            for &ty in &deps.place_types {
+                let ty = monomorphize(tcx, instance, ty);
+                let layout = tcx.layout_of(ty::ParamEnv::reveal_all().and(ty));
+                layout.ok().hash_stable(hcx, &mut hasher);
+            }
+            for constant in &deps.constants {
+                let const_ = monomorphize(tcx, instance, constant.const_);
+                let value = const_.eval(tcx, ty::ParamEnv::reveal_all(), constant.span);
+                value.ok().hash_stable(hcx, &mut hasher);
+            }
+        });
+        hasher.finish()
+    })
+}
+
+fn monomorphize<'tcx, T>(tcx: TyCtxt<'tcx>, instance: Instance<'tcx>, value: T) -> T
+where
+    T: ty::TypeFoldable<TyCtxt<'tcx>>,
+{
+    instance.instantiate_mir_and_normalize_erasing_regions(
+        tcx,
+        ty::ParamEnv::reveal_all(),
+        ty::EarlyBinder::bind(value),
+    )
+}
+
+/// Collects the types of all places and all constants of a body, whose layouts and values the
+/// compiled code depends on.
+struct CodegenDeps<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a Body<'tcx>,
+    place_types: FxIndexSet<Ty<'tcx>>,
+    constants: Vec<ConstOperand<'tcx>>,
+}
+
+impl<'tcx> Visitor<'tcx> for CodegenDeps<'_, 'tcx> {
+    fn visit_place(&mut self, place: &Place<'tcx>, _context: PlaceContext, _location: Location) {
+        for (base, elem) in place.iter_projections() {
+            let ty = base.ty(self.body, self.tcx).projection_ty(self.tcx, elem).ty;
+            self.place_types.insert(ty);
+        }
+    }
+
+    fn visit_const_operand(&mut self, constant: &ConstOperand<'tcx>, _location: Location) {
+        self.constants.push(*constant);
+    }
+}
+
+fn statics<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    mono_items: &[(MonoItem<'tcx>, MonoItemData)],
+) -> FxHashSet<String> {
+    mono_items
+        .iter()
+        .filter_map(|&(mono_item, _)| match mono_item {
+            MonoItem::Static(def_id) => {
+                Some(tcx.symbol_name(Instance::mono(tcx, def_id)).name.to_owned())
+            }
+            MonoItem::Fn(_) | MonoItem::GlobalAsm(_) => None,
+        })
+        .collect()
+}
@@ -9,19 +9,24 @@
 use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
 use cranelift_jit::{JITBuilder, JITModule};
 use rustc_codegen_ssa::CrateInfo;
-use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::mir::mono::{MonoItem, MonoItemData};
 use rustc_session::Session;
 use rustc_span::Symbol;
 
+use super::hot_reload::HotReload;
 use crate::debuginfo::TypeDebugContext;
 use crate::prelude::*;
 use crate::unwind_module::UnwindModule;
 use crate::{BackendConfig, CodegenCx, CodegenMode};
 
-struct JitState {
-    jit_module: UnwindModule<JITModule>,
+pub(super) struct JitState {
+    pub(super) jit_module: UnwindModule<JITModule>,
 }
 
+// SAFETY: The JIT module is only used by the rustc thread and, while the rustc thread waits for a
+// hot reload to finish, by the thread of the compiler session doing the hot reload.
+unsafe impl Send for JitState {}
+
 thread_local! {
     static LAZY_JIT_STATE: RefCell<Option<JitState>> = const { RefCell::new(None) };
 }
@@ -93,18 +98,16 @@ pub(crate) fn run_jit(tcx: TyCtxt<'_>, backend_config: BackendConfig) -> ! {
     let (mut jit_module, mut cx) = create_jit_module(
         tcx,
         &backend_config,
-        matches!(backend_config.codegen_mode, CodegenMode::JitLazy),
+        matches!(backend_config.codegen_mode, CodegenMode::JitLazy | CodegenMode::JitHotReload),
     );
     let mut cached_context = Context::new();
 
-    let (_, cgus) = tcx.collect_and_partition_mono_items(());
-    let mono_items = cgus
-        .iter()
-        .map(|cgu| cgu.items_in_deterministic_order(tcx).into_iter())
-        .flatten()
-        .collect::<FxHashMap<_, _>>()
-        .into_iter()
-        .collect::<Vec<(_, _)>>();
+    let mono_items = mono_items(tcx);
+
+    let mut hot_reload = match backend_config.codegen_mode {
+        CodegenMode::JitHotReload => Some(HotReload::new(tcx, &mono_items)),
+        _ => None,
+    };
 
     tcx.sess.time("codegen mono items", || {
         super::predefine_mono_items(tcx, &mut jit_module, &mono_items);
@@ -121,7 +124,7 @@ pub(crate) fn run_jit(tcx: TyCtxt<'_>, backend_config: BackendConfig) -> ! {
                             inst,
                         );
                     }
-                    CodegenMode::JitLazy => {
+                    CodegenMode::JitLazy | CodegenMode::JitHotReload => {
                         codegen_shim(tcx, &mut cached_context, &mut jit_module, inst)
                     }
                 },
@@ -193,7 +196,21 @@ pub(crate) fn run_jit(tcx: TyCtxt<'_>, backend_config: BackendConfig) -> ! {
 
     // Handle messages
     loop {
-        match rx.recv().unwrap() {
+        let message = match &mut hot_reload {
+            Some(hot_reload) => match rx.recv_timeout(super::hot_reload::POLL_INTERVAL) {
+                Ok(message) => message,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    LAZY_JIT_STATE.with(|lazy_jit_state| {
+                        hot_reload
+                            .reload_if_changed(tcx, lazy_jit_state.borrow_mut().as_mut().unwrap())
+                    });
+                    continue;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => unreachable!(),
+            },
+            None => rx.recv().unwrap(),
+        };
+        match message {
             // lazy JIT compilation request - compile requested instance and return pointer to result
             UnsafeMessage::JitFn { instance_ptr, trampoline_ptr, tx } => {
                 tx.send(jit_fn(instance_ptr, trampoline_ptr))
@@ -203,6 +220,17 @@ pub(crate) fn run_jit(tcx: TyCtxt<'_>, backend_config: BackendConfig) -> ! {
     }
 }
 
+/// Returns the mono items of all codegen units, which are all compiled into the same module.
+pub(super) fn mono_items(tcx: TyCtxt<'_>) -> Vec<(MonoItem<'_>, MonoItemData)> {
+    let (_, cgus) = tcx.collect_and_partition_mono_items(());
+    cgus.iter()
+        .map(|cgu| cgu.items_in_deterministic_order(tcx).into_iter())
+        .flatten()
+        .collect::<FxHashMap<_, _>>()
+        .into_iter()
+        .collect::<Vec<(_, _)>>()
+}
+
 pub(crate) fn codegen_and_compile_fn<'tcx>(
     tcx: TyCtxt<'tcx>,
     cx: &mut crate::CodegenCx,
@@ -11,10 +11,16 @@
 use rustc_middle::mir::interpret::ErrorHandled;
 use rustc_session::config::{OutputFilenames, OutputType};
 use rustc_target::asm::InlineAsmArch;
+use target_lexicon::BinaryFormat;
 
 use crate::prelude::*;
 
-pub(crate) fn codegen_global_asm_item(tcx: TyCtxt<'_>, global_asm: &mut String, item_id: ItemId) {
+pub(crate) fn codegen_global_asm_item(
+    tcx: TyCtxt<'_>,
+    cx: &mut crate::CodegenCx,
+    module: &mut dyn Module,
+    item_id: ItemId,
+) {
     let item = tcx.hir().item(item_id);
     if let rustc_hir::ItemKind::GlobalAsm(asm) = item.kind {
         let is_x86 =
@@ -22,14 +28,14 @@ pub(crate) fn codegen_global_asm_item(tcx: TyCtxt<'_>, global_asm: &mut String,
 
         if is_x86 {
             if !asm.options.contains(InlineAsmOptions::ATT_SYNTAX) {
-                global_asm.push_str("\n.intel_syntax noprefix\n");
+                cx.global_asm.push_str("\n.intel_syntax noprefix\n");
             } else {
-                global_asm.push_str("\n.att_syntax\n");
+                cx.global_asm.push_str("\n.att_syntax\n");
             }
         }
         for piece in asm.template {
             match *piece {
-                InlineAsmTemplatePiece::String(ref s) => global_asm.push_str(s),
+                InlineAsmTemplatePiece::String(ref s) => cx.global_asm.push_str(s),
                 InlineAsmTemplatePiece::Placeholder { operand_idx, modifier: _, span: op_sp } => {
                     match asm.operands[operand_idx].0 {
                         InlineAsmOperand::Const { ref anon_const } => {
@@ -44,7 +50,7 @@ pub(crate) fn codegen_global_asm_item(tcx: TyCtxt<'_>, global_asm: &mut String,
                                         const_value,
                                         RevealAllLayoutCx(tcx).layout_of(ty),
                                     );
-                                    global_asm.push_str(&string);
+                                    cx.global_asm.push_str(&string);
                                 }
                                 Err(ErrorHandled::Reported { .. }) => {
                                     // An error has already been reported and compilation is
@@ -56,34 +62,25 @@ pub(crate) fn codegen_global_asm_item(tcx: TyCtxt<'_>, global_asm: &mut String,
                             }
                         }
                         InlineAsmOperand::SymFn { anon_const } => {
-                            if cfg!(not(feature = "inline_asm_sym")) {
-                                tcx.dcx().span_err(
-                                    item.span,
-                                    "asm! and global_asm! sym operands are not yet supported",
-                                );
-                            }
-
                             let ty = tcx.typeck_body(anon_const.body).node_type(anon_const.hir_id);
                             let instance = match ty.kind() {
-                                &ty::FnDef(def_id, args) => Instance::new(def_id, args),
+                                &ty::FnDef(def_id, args) => Instance::resolve_for_fn_ptr(
+                                    tcx,
+                                    ty::ParamEnv::reveal_all(),
+                                    def_id,
+                                    args,
+                                )
+                                .unwrap(),
                                 _ => span_bug!(op_sp, "asm sym is not a function"),
                             };
-                            let symbol = tcx.symbol_name(instance);
-                            // FIXME handle the case where the function was made private to the
-                            // current codegen unit
-                            global_asm.push_str(symbol.name);
+                            let symbol =
+                                crate::inline_asm::sym_fn_wrapper(tcx, cx, module, instance);
+                            push_symbol(tcx, &mut cx.global_asm, &symbol);
                         }
                         InlineAsmOperand::SymStatic { path: _, def_id } => {
-                            if cfg!(not(feature = "inline_asm_sym")) {
-                                tcx.dcx().span_err(
-                                    item.span,
-                                    "asm! and global_asm! sym operands are not yet supported",
-                                );
-                            }
-
                             let instance = Instance::mono(tcx, def_id);
                             let symbol = tcx.symbol_name(instance);
-                            global_asm.push_str(symbol.name);
+                            push_symbol(tcx, &mut cx.global_asm, symbol.name);
                         }
                         InlineAsmOperand::In { .. }
                         | InlineAsmOperand::Out { .. }
@@ -97,15 +94,23 @@ pub(crate) fn codegen_global_asm_item(tcx: TyCtxt<'_>, global_asm: &mut String,
             }
         }
 
-        global_asm.push('\n');
+        cx.global_asm.push('\n');
         if is_x86 {
-            global_asm.push_str(".att_syntax\n\n");
+            cx.global_asm.push_str(".att_syntax\n\n");
         }
     } else {
         bug!("Expected GlobalAsm found {:?}", item);
     }
 }
 
+/// Pushes `symbol` the way the assembler expects it, which on Mach-O is with a leading `_`.
+fn push_symbol(tcx: TyCtxt<'_>, global_asm: &mut String, symbol: &str) {
+    if crate::target_triple(tcx.sess).binary_format == BinaryFormat::Macho {
+        global_asm.push('_');
+    }
+    global_asm.push_str(symbol);
+}
+
 #[derive(Debug)]
 pub(crate) struct GlobalAsmConfig {
     assembler: PathBuf,
@@ -33,6 +33,9 @@ pub(crate) enum CInlineAsmOperand<'tcx> {
     Symbol {
         symbol: String,
     },
+    Label {
+        block: Block,
+    },
 }
 
 pub(crate) fn codegen_inline_asm_terminator<'tcx>(
@@ -41,7 +44,7 @@ pub(crate) fn codegen_inline_asm_terminator<'tcx>(
     template: &[InlineAsmTemplatePiece],
     operands: &[InlineAsmOperand<'tcx>],
     options: InlineAsmOptions,
-    destination: Option<mir::BasicBlock>,
+    targets: &[mir::BasicBlock],
 ) {
     // Used by panic_abort on Windows, but uses a syntax which only happens to work with
     // asm!() by accident and breaks with the GNU assembler as well as global_asm!() for
@@ -82,12 +85,6 @@ pub(crate) fn codegen_inline_asm_terminator<'tcx>(
                 CInlineAsmOperand::Const { value }
             }
             InlineAsmOperand::SymFn { ref value } => {
-                if cfg!(not(feature = "inline_asm_sym")) {
-                    fx.tcx
-                        .dcx()
-                        .span_err(span, "asm! and global_asm! sym operands are not yet supported");
-                }
-
                 let const_ = fx.monomorphize(value.const_);
                 if let ty::FnDef(def_id, args) = *const_.ty().kind() {
                     let instance = ty::Instance::resolve_for_fn_ptr(
@@ -97,23 +94,8 @@ pub(crate) fn codegen_inline_asm_terminator<'tcx>(
                         args,
                     )
                     .unwrap();
-                    let symbol = fx.tcx.symbol_name(instance);
-
-                    // Pass a wrapper rather than the function itself as the function itself may not
-                    // be exported from the main codegen unit and may thus be unreachable from the
-                    // object file created by an external assembler.
-                    let inline_asm_index = fx.cx.inline_asm_index.get();
-                    fx.cx.inline_asm_index.set(inline_asm_index + 1);
-                    let wrapper_name = format!(
-                        "__inline_asm_{}_wrapper_n{}",
-                        fx.cx.cgu_name.as_str().replace('.', "__").replace('-', "_"),
-                        inline_asm_index
-                    );
-                    let sig =
-                        get_function_sig(fx.tcx, fx.target_config.default_call_conv, instance);
-                    create_wrapper_function(fx.module, sig, &wrapper_name, symbol.name);
-
-                    CInlineAsmOperand::Symbol { symbol: wrapper_name }
+                    let symbol = sym_fn_wrapper(fx.tcx, fx.cx, fx.module, instance);
+                    CInlineAsmOperand::Symbol { symbol }
                 } else {
                     span_bug!(span, "invalid type for asm sym (fn)");
                 }
@@ -123,13 +105,47 @@ pub(crate) fn codegen_inline_asm_terminator<'tcx>(
                 let instance = Instance::mono(fx.tcx, def_id);
                 CInlineAsmOperand::Symbol { symbol: fx.tcx.symbol_name(instance).name.to_owned() }
             }
-            InlineAsmOperand::Label { .. } => {
-                span_bug!(span, "asm! label operands are not yet supported");
+            InlineAsmOperand::Label { target_index } => {
+                // Jumping to a label operand is only implemented for these architectures.
+                if !matches!(
+                    fx.tcx.sess.asm_arch,
+                    Some(InlineAsmArch::X86_64 | InlineAsmArch::AArch64)
+                ) {
+                    fx.tcx.dcx().span_fatal(
+                        span,
+                        "cranelift doesn't support labels in inline assembly for this target.",
+                    );
+                }
+                CInlineAsmOperand::Label { block: fx.get_block(targets[target_index]) }
             }
         })
         .collect::<Vec<_>>();
 
-    codegen_inline_asm_inner(fx, template, &operands, options);
+    let label_index = codegen_inline_asm_inner(fx, template, &operands, options);
+
+    let destination =
+        if options.contains(InlineAsmOptions::NORETURN) { None } else { targets.get(0).copied() };
+
+    if let Some(label_index) = label_index {
+        // Jump to wherever the asm block would have jumped to: 0 is the fallthrough destination
+        // and n is the block of the n-th label operand.
+        let mut switch = ::cranelift_frontend::Switch::new();
+        if let Some(destination) = destination {
+            switch.set_entry(0, fx.get_block(destination));
+        }
+        let label_blocks = operands.iter().filter_map(|operand| match *operand {
+            CInlineAsmOperand::Label { block } => Some(block),
+            _ => None,
+        });
+        for (i, block) in label_blocks.enumerate() {
+            switch.set_entry(i as u128 + 1, block);
+        }
+        let unreachable_block = fx.bcx.create_block();
+        switch.emit(&mut fx.bcx, label_index, unreachable_block);
+        fx.bcx.switch_to_block(unreachable_block);
+        fx.bcx.ins().trap(TrapCode::UnreachableCodeReached);
+        return;
+    }
 
     match destination {
         Some(destination) => {
@@ -142,12 +158,14 @@ pub(crate) fn codegen_inline_asm_terminator<'tcx>(
     }
 }
 
+/// Returns the exit taken by the asm block if it has label operands: 0 if it fell through and n if
+/// it jumped to the n-th label operand.
 pub(crate) fn codegen_inline_asm_inner<'tcx>(
     fx: &mut FunctionCx<'_, '_, 'tcx>,
     template: &[InlineAsmTemplatePiece],
     operands: &[CInlineAsmOperand<'tcx>],
     options: InlineAsmOptions,
-) {
+) -> Option<Value> {
     // FIXME add .eh_frame unwind info directives
 
     let mut asm_gen = InlineAssemblyGenerator {
@@ -161,6 +179,7 @@ pub(crate) fn codegen_inline_asm_inner<'tcx>(
         stack_slots_clobber: Vec::new(),
         stack_slots_input: Vec::new(),
         stack_slots_output: Vec::new(),
+        stack_slot_label: None,
         stack_slot_size: Size::from_bytes(0),
         is_naked: false,
     };
@@ -196,11 +215,20 @@ pub(crate) fn codegen_inline_asm_inner<'tcx>(
                     outputs.push((asm_gen.stack_slots_output[i].unwrap(), *out_place));
                 }
             }
-            CInlineAsmOperand::Const { value: _ } | CInlineAsmOperand::Symbol { symbol: _ } => {}
+            CInlineAsmOperand::Const { value: _ }
+            | CInlineAsmOperand::Symbol { symbol: _ }
+            | CInlineAsmOperand::Label { block: _ } => {}
         }
     }
 
-    call_inline_asm(fx, &asm_name, asm_gen.stack_slot_size, inputs, outputs);
+    call_inline_asm(
+        fx,
+        &asm_name,
+        asm_gen.stack_slot_size,
+        inputs,
+        outputs,
+        asm_gen.stack_slot_label,
+    )
 }
 
 pub(crate) fn codegen_naked_asm<'tcx>(
@@ -221,7 +249,8 @@ pub(crate) fn codegen_naked_asm<'tcx>(
         .map(|operand| match *operand {
             InlineAsmOperand::In { .. }
             | InlineAsmOperand::Out { .. }
-            | InlineAsmOperand::InOut { .. } => {
+            | InlineAsmOperand::InOut { .. }
+            | InlineAsmOperand::Label { .. } => {
                 span_bug!(span, "invalid operand type for naked asm")
             }
             InlineAsmOperand::Const { ref value } => {
@@ -243,11 +272,6 @@ pub(crate) fn codegen_naked_asm<'tcx>(
                 CInlineAsmOperand::Const { value }
             }
             InlineAsmOperand::SymFn { ref value } => {
-                if cfg!(not(feature = "inline_asm_sym")) {
-                    tcx.dcx()
-                        .span_err(span, "asm! and global_asm! sym operands are not yet supported");
-                }
-
                 let const_ = instance.instantiate_mir_and_normalize_erasing_regions(
                     tcx,
                     ty::ParamEnv::reveal_all(),
@@ -261,23 +285,8 @@ pub(crate) fn codegen_naked_asm<'tcx>(
                         args,
                     )
                     .unwrap();
-                    let symbol = tcx.symbol_name(instance);
-
-                    // Pass a wrapper rather than the function itself as the function itself may not
-                    // be exported from the main codegen unit and may thus be unreachable from the
-                    // object file created by an external assembler.
-                    let inline_asm_index = cx.inline_asm_index.get();
-                    cx.inline_asm_index.set(inline_asm_index + 1);
-                    let wrapper_name = format!(
-                        "__inline_asm_{}_wrapper_n{}",
-                        cx.cgu_name.as_str().replace('.', "__").replace('-', "_"),
-                        inline_asm_index
-                    );
-                    let sig =
-                        get_function_sig(tcx, module.target_config().default_call_conv, instance);
-                    create_wrapper_function(module, sig, &wrapper_name, symbol.name);
-
-                    CInlineAsmOperand::Symbol { symbol: wrapper_name }
+                    let symbol = sym_fn_wrapper(tcx, cx, module, instance);
+                    CInlineAsmOperand::Symbol { symbol }
                 } else {
                     span_bug!(span, "invalid type for asm sym (fn)");
                 }
@@ -287,9 +296,6 @@ pub(crate) fn codegen_naked_asm<'tcx>(
                 let instance = Instance::mono(tcx, def_id);
                 CInlineAsmOperand::Symbol { symbol: tcx.symbol_name(instance).name.to_owned() }
             }
-            InlineAsmOperand::Label { .. } => {
-                span_bug!(span, "asm! label operands are not yet supported");
-            }
         })
         .collect::<Vec<_>>();
 
@@ -304,6 +310,7 @@ pub(crate) fn codegen_naked_asm<'tcx>(
         stack_slots_clobber: Vec::new(),
         stack_slots_input: Vec::new(),
         stack_slots_output: Vec::new(),
+        stack_slot_label: None,
         stack_slot_size: Size::from_bytes(0),
         is_naked: true,
     };
@@ -312,6 +319,32 @@ pub(crate) fn codegen_naked_asm<'tcx>(
     cx.global_asm.push_str(&generated_asm);
 }
 
+/// Returns the symbol a `sym` operand referring to `instance` should use.
+///
+/// This is a wrapper rather than the function itself as the function itself may not be exported
+/// from the main codegen unit and may thus be unreachable from the object file created by an
+/// external assembler.
+pub(crate) fn sym_fn_wrapper<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    cx: &crate::CodegenCx,
+    module: &mut dyn Module,
+    instance: Instance<'tcx>,
+) -> String {
+    let symbol = tcx.symbol_name(instance);
+
+    let inline_asm_index = cx.inline_asm_index.get();
+    cx.inline_asm_index.set(inline_asm_index + 1);
+    let wrapper_name = format!(
+        "__inline_asm_{}_wrapper_n{}",
+        cx.cgu_name.as_str().replace('.', "__").replace('-', "_"),
+        inline_asm_index
+    );
+    let sig = get_function_sig(tcx, module.target_config().default_call_conv, instance);
+    create_wrapper_function(module, sig, &wrapper_name, symbol.name);
+
+    wrapper_name
+}
+
 struct InlineAssemblyGenerator<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
     arch: InlineAsmArch,
@@ -323,6 +356,8 @@ struct InlineAssemblyGenerator<'a, 'tcx> {
     stack_slots_clobber: Vec<Option<Size>>,
     stack_slots_input: Vec<Option<Size>>,
     stack_slots_output: Vec<Option<Size>>,
+    /// Where the wrapper stores the exit taken by the asm block if it has label operands.
+    stack_slot_label: Option<Size>,
     stack_slot_size: Size,
     is_naked: bool,
 }
@@ -540,12 +575,29 @@ fn allocate_stack_slots(&mut self) {
 
         slot_size = slot_size.max(slot_size_after_input);
 
+        // Allocate a stack slot for the exit taken
+        if self.has_labels() {
+            let offset = slot_size.align_to(rustc_target::abi::Align::EIGHT);
+            self.stack_slot_label = Some(offset);
+            slot_size = offset + Size::from_bytes(8);
+        }
+
         self.stack_slots_clobber = slots_clobber;
         self.stack_slots_input = slots_input;
         self.stack_slots_output = slots_output;
         self.stack_slot_size = slot_size;
     }
 
+    fn has_labels(&self) -> bool {
+        self.operands.iter().any(|operand| matches!(operand, CInlineAsmOperand::Label { .. }))
+    }
+
+    /// Whether the wrapper returns to its caller, either because the asm block falls through or
+    /// because it jumps to a label operand.
+    fn may_return(&self) -> bool {
+        !self.options.contains(InlineAsmOptions::NORETURN) || self.has_labels()
+    }
+
     fn generate_asm_wrapper(&self, asm_name: &str) -> String {
         let binary_format = crate::target_triple(self.tcx.sess).binary_format;
 
@@ -580,7 +632,7 @@ fn generate_asm_wrapper(&self, asm_name: &str) -> String {
             Self::prologue(&mut generated_asm, self.arch);
 
             // Save clobbered registers
-            if !self.options.contains(InlineAsmOptions::NORETURN) {
+            if self.may_return() {
                 for (reg, slot) in self
                     .registers
                     .iter()
@@ -652,7 +704,15 @@ fn generate_asm_wrapper(&self, asm_name: &str) -> String {
                         CInlineAsmOperand::Const { ref value } => {
                             generated_asm.push_str(value);
                         }
-                        CInlineAsmOperand::Symbol { ref symbol } => generated_asm.push_str(symbol),
+                        CInlineAsmOperand::Symbol { ref symbol } => {
+                            if binary_format == BinaryFormat::Macho {
+                                generated_asm.push('_');
+                            }
+                            generated_asm.push_str(symbol);
+                        }
+                        CInlineAsmOperand::Label { block: _ } => {
+                            write!(generated_asm, "{asm_name}_label{operand_idx}").unwrap();
+                        }
                     }
                 }
             }
@@ -664,7 +724,29 @@ fn generate_asm_wrapper(&self, asm_name: &str) -> String {
         }
 
         if !self.is_naked {
-            if !self.options.contains(InlineAsmOptions::NORETURN) {
+            if let Some(label_slot) = self.stack_slot_label {
+                // Record which exit was taken, the caller jumps to the corresponding block.
+                let exit_label = format!("{asm_name}_exit");
+                if self.options.contains(InlineAsmOptions::NORETURN) {
+                    Self::epilogue_noreturn(&mut generated_asm, self.arch);
+                } else {
+                    Self::store_label_index(&mut generated_asm, self.arch, 0, label_slot);
+                    Self::jump(&mut generated_asm, self.arch, &exit_label);
+                }
+                let label_operands = self
+                    .operands
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, operand)| matches!(operand, CInlineAsmOperand::Label { .. }));
+                for (i, (operand_idx, _)) in label_operands.enumerate() {
+                    writeln!(generated_asm, "{asm_name}_label{operand_idx}:").unwrap();
+                    Self::store_label_index(&mut generated_asm, self.arch, i + 1, label_slot);
+                    Self::jump(&mut generated_asm, self.arch, &exit_label);
+                }
+                writeln!(generated_asm, "{exit_label}:").unwrap();
+            }
+
+            if self.may_return() {
                 // Read output registers
                 for (reg, slot) in self
                     .registers
@@ -776,6 +858,34 @@ fn epilogue_noreturn(generated_asm: &mut String, arch: InlineAsmArch) {
         }
     }
 
+    fn store_label_index(
+        generated_asm: &mut String,
+        arch: InlineAsmArch,
+        index: usize,
+        offset: Size,
+    ) {
+        match arch {
+            InlineAsmArch::X86_64 => {
+                writeln!(generated_asm, "    mov qword ptr [rbx+0x{:x}], {index}", offset.bytes())
+                    .unwrap();
+            }
+            InlineAsmArch::AArch64 => {
+                // fp is restored by the epilogue and can't be used by the asm block
+                writeln!(generated_asm, "    mov fp, #{index}").unwrap();
+                writeln!(generated_asm, "    str fp, [x19, 0x{:x}]", offset.bytes()).unwrap();
+            }
+            _ => unimplemented!("store_label_index for {:?}", arch),
+        }
+    }
+
+    fn jump(generated_asm: &mut String, arch: InlineAsmArch, label: &str) {
+        match arch {
+            InlineAsmArch::X86_64 => writeln!(generated_asm, "    jmp {label}").unwrap(),
+            InlineAsmArch::AArch64 => writeln!(generated_asm, "    b {label}").unwrap(),
+            _ => unimplemented!("jump for {:?}", arch),
+        }
+    }
+
     fn save_register(
         generated_asm: &mut String,
         arch: InlineAsmArch,
@@ -864,7 +974,8 @@ fn call_inline_asm<'tcx>(
     slot_size: Size,
     inputs: Vec<(Size, Value)>,
     outputs: Vec<(Size, CPlace<'tcx>)>,
-) {
+    label_slot: Option<Size>,
+) -> Option<Value> {
     let stack_slot = fx.create_stack_slot(u32::try_from(slot_size.bytes()).unwrap(), 16);
 
     let inline_asm_func = fx
@@ -909,6 +1020,14 @@ fn call_inline_asm<'tcx>(
         );
         place.write_cvalue(fx, CValue::by_val(value, place.layout()));
     }
+
+    label_slot.map(|offset| {
+        stack_slot.offset(fx, i32::try_from(offset.bytes()).unwrap().into()).load(
+            fx,
+            types::I64,
+            MemFlags::trusted(),
+        )
+    })
 }
 
 fn asm_clif_type<'tcx>(fx: &FunctionCx<'_, '_, 'tcx>, ty: Ty<'tcx>) -> Option<types::Type> {
@@ -31,6 +31,8 @@
 // This prevents duplicating functions and statics that are already part of the host rustc process.
 #[allow(unused_extern_crates)]
 extern crate rustc_driver;
+#[cfg(feature = "jit")]
+extern crate rustc_interface;
 
 use std::any::Any;
 use std::cell::{Cell, RefCell};
@@ -223,7 +225,7 @@ fn codegen_crate(
         let config = self.config.borrow().clone().unwrap();
         match config.codegen_mode {
             CodegenMode::Aot => driver::aot::run_aot(tcx, config, metadata, need_metadata_module),
-            CodegenMode::Jit | CodegenMode::JitLazy => {
+            CodegenMode::Jit | CodegenMode::JitLazy | CodegenMode::JitHotReload => {
                 #[cfg(feature = "jit")]
                 driver::jit::run_jit(tcx, config);
 
@@ -20,7 +20,7 @@
 use rustc_data_structures::small_c_str::SmallCStr;
 use rustc_middle::dep_graph;
 use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrs;
-use rustc_middle::mir::mono::{Linkage, Visibility};
+use rustc_middle::mir::mono::{Linkage, MonoItem, Visibility};
 use rustc_middle::ty::TyCtxt;
 use rustc_session::config::DebugInfo;
 use rustc_span::symbol::Symbol;
@@ -90,8 +90,13 @@ fn module_codegen(tcx: TyCtxt<'_>, cgu_name: Symbol) -> ModuleCodegen<ModuleLlvm
             }
 
             // ... and now that we have everything pre-defined, fill out those definitions.
+            let record_costs = tcx.sess.opts.unstable_opts.dump_item_costs.enabled();
             for &(mono_item, _) in &mono_items {
+                let start_time = record_costs.then(Instant::now);
                 mono_item.define::<Builder<'_, '_, '_>>(&cx);
+                if let Some(start_time) = start_time {
+                    record_mono_item_cost(&cx, mono_item, start_time);
+                }
             }
 
             // If this codegen unit contains the main function, also create the
@@ -143,6 +148,28 @@ fn module_codegen(tcx: TyCtxt<'_>, cgu_name: Symbol) -> ModuleCodegen<ModuleLlvm
     (module, cost)
 }
 
+/// Records the time spent on the code of a mono item and its size for `-Z dump-item-costs`.
+fn record_mono_item_cost<'tcx>(
+    cx: &CodegenCx<'_, 'tcx>,
+    mono_item: MonoItem<'tcx>,
+    start: Instant,
+) {
+    let time = start.elapsed();
+    let llvm_ir_size = match mono_item {
+        MonoItem::Fn(instance) => cx
+            .instances
+            .borrow()
+            .get(&instance)
+            .map(|&llfn| unsafe { llvm::LLVMRustFunctionInstructionCount(llfn) as usize }),
+        MonoItem::Static(_) | MonoItem::GlobalAsm(_) => None,
+    };
+    cx.tcx.sess.code_stats.record_mono_item_codegen(
+        mono_item.symbol_name(cx.tcx).name,
+        time,
+        llvm_ir_size,
+    );
+}
+
 pub(crate) fn set_link_section(llval: &Value, attrs: &CodegenFnAttrs) {
     let Some(sect) = attrs.link_section else { return };
     unsafe {
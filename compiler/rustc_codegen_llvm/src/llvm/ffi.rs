@@ -2354,6 +2354,7 @@ pub fn LLVMRustBuildOperandBundleDef(
     pub fn LLVMRustModuleBufferLen(p: &ModuleBuffer) -> usize;
     pub fn LLVMRustModuleBufferFree(p: &'static mut ModuleBuffer);
     pub fn LLVMRustModuleCost(M: &Module) -> u64;
+    pub fn LLVMRustFunctionInstructionCount(Fn: &Value) -> c_uint;
     #[allow(improper_ctypes)]
     pub fn LLVMRustModuleInstructionStats(M: &Module, Str: &RustString);
 
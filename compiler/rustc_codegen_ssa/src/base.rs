@@ -692,6 +692,14 @@ pub fn codegen_crate<B: ExtraBackendMethods>(
         }
     });
 
+    if tcx.sess.opts.unstable_opts.dump_item_costs.enabled() {
+        for (cgu, &cgu_reuse) in codegen_units.iter().zip(&cgu_reuse) {
+            if cgu_reuse != CguReuse::No {
+                tcx.sess.code_stats.record_reused_cgu(cgu.name());
+            }
+        }
+    }
+
     let mut total_codegen_time = Duration::new(0, 0);
     let start_rss = tcx.sess.opts.unstable_opts.time_passes.then(|| get_resident_set_size());
 
@@ -89,17 +89,22 @@
 use std::path::Path;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use std::{fs, process};
+use std::{fs, io, process};
 
 pub use measureme::EventId;
-use measureme::{EventIdBuilder, Profiler, SerializableString, StringId};
+use measureme::event_id::SEPARATOR_BYTE;
+use measureme::{Profiler, StringComponent, StringId};
 use parking_lot::RwLock;
 use smallvec::SmallVec;
 use tracing::warn;
 
+pub use self::chrome_trace::ProfileString;
+use self::chrome_trace::{ChromeTrace, ChromeTraceGuard};
 use crate::fx::FxHashMap;
 use crate::outline;
 
+mod chrome_trace;
+
 bitflags::bitflags! {
     #[derive(Clone, Copy)]
     struct EventFilter: u16 {
@@ -154,6 +159,9 @@ pub enum TimePassesFormat {
     Text,
     /// Emit structured JSON
     Json,
+    /// Record a trace in the Chrome trace event format, which can be opened in Perfetto,
+    /// instead of printing to stderr
+    Chrome,
 }
 
 /// A reference to the SelfProfiler. It can be cloned and sent across thread
@@ -275,7 +283,7 @@ pub fn generic_activity_with_arg<A>(
         A: Borrow<str> + Into<String>,
     {
         self.exec(EventFilter::GENERIC_ACTIVITIES, |profiler| {
-            let builder = EventIdBuilder::new(&profiler.profiler);
+            let builder = profiler.event_id_builder();
             let event_label = profiler.get_or_alloc_cached_string(event_label);
             let event_id = if profiler.event_filter_mask.contains(EventFilter::FUNCTION_ARGS) {
                 let event_arg = profiler.get_or_alloc_cached_string(event_arg);
@@ -318,7 +326,7 @@ pub fn generic_activity_with_arg_recorder<F>(
     {
         // Ensure this event will only be recorded when self-profiling is turned on.
         self.exec(EventFilter::GENERIC_ACTIVITIES, |profiler| {
-            let builder = EventIdBuilder::new(&profiler.profiler);
+            let builder = profiler.event_id_builder();
             let event_label = profiler.get_or_alloc_cached_string(event_label);
 
             // Ensure the closure to create event arguments will only be called when argument
@@ -357,18 +365,20 @@ pub fn artifact_size<A>(&self, artifact_kind: &str, artifact_name: A, size: u64)
         A: Borrow<str> + Into<String>,
     {
         drop(self.exec(EventFilter::ARTIFACT_SIZES, |profiler| {
-            let builder = EventIdBuilder::new(&profiler.profiler);
+            let builder = profiler.event_id_builder();
             let event_label = profiler.get_or_alloc_cached_string(artifact_kind);
             let event_arg = profiler.get_or_alloc_cached_string(artifact_name);
             let event_id = builder.from_label_and_arg(event_label, event_arg);
             let thread_id = get_thread_id();
 
-            profiler.profiler.record_integer_event(
-                profiler.artifact_size_event_kind,
-                event_id,
-                thread_id,
-                size,
-            );
+            if let Some(raw_profiler) = &profiler.profiler {
+                raw_profiler.record_integer_event(
+                    profiler.artifact_size_event_kind,
+                    event_id,
+                    thread_id,
+                    size,
+                );
+            }
 
             TimingGuard::none()
         }))
@@ -381,7 +391,7 @@ pub fn generic_activity_with_args(
         event_args: &[String],
     ) -> TimingGuard<'_> {
         self.exec(EventFilter::GENERIC_ACTIVITIES, |profiler| {
-            let builder = EventIdBuilder::new(&profiler.profiler);
+            let builder = profiler.event_id_builder();
             let event_label = profiler.get_or_alloc_cached_string(event_label);
             let event_id = if profiler.event_filter_mask.contains(EventFilter::FUNCTION_ARGS) {
                 let event_args: Vec<_> = event_args
@@ -468,11 +478,13 @@ fn instant_query_event(
         let event_id = StringId::new_virtual(query_invocation_id.0);
         let thread_id = get_thread_id();
         let profiler = self.profiler.as_ref().unwrap();
-        profiler.profiler.record_instant_event(
-            event_kind(profiler),
-            EventId::from_virtual(event_id),
-            thread_id,
-        );
+        if let Some(raw_profiler) = &profiler.profiler {
+            raw_profiler.record_instant_event(
+                event_kind(profiler),
+                EventId::from_virtual(event_id),
+                thread_id,
+            );
+        }
     }
 
     pub fn with_profiler(&self, f: impl FnOnce(&SelfProfiler)) {
@@ -532,7 +544,8 @@ pub fn record_arg<A>(&mut self, event_arg: A)
 }
 
 pub struct SelfProfiler {
-    profiler: Profiler,
+    /// The `measureme` profiler, `None` if only a Chrome trace is recorded.
+    profiler: Option<Profiler>,
     event_filter_mask: EventFilter,
 
     string_cache: RwLock<FxHashMap<String, StringId>>,
@@ -544,35 +557,51 @@ pub struct SelfProfiler {
     query_blocked_event_kind: StringId,
     query_cache_hit_event_kind: StringId,
     artifact_size_event_kind: StringId,
+
+    chrome_trace: Option<ChromeTrace>,
 }
 
 impl SelfProfiler {
+    /// Creates a profiler that writes the `measureme` data to `output_directory`, and
+    /// records a Chrome trace to be written to `chrome_trace_directory` by
+    /// [`SelfProfiler::write_chrome_trace`]. At least one of them must be given.
     pub fn new(
-        output_directory: &Path,
+        output_directory: Option<&Path>,
+        chrome_trace_directory: Option<&Path>,
         crate_name: Option<&str>,
         event_filters: Option<&[String]>,
         counter_name: &str,
     ) -> Result<SelfProfiler, Box<dyn Error + Send + Sync>> {
-        fs::create_dir_all(output_directory)?;
+        assert!(output_directory.is_some() || chrome_trace_directory.is_some());
 
         let crate_name = crate_name.unwrap_or("unknown-crate");
         // HACK(eddyb) we need to pad the PID, strange as it may seem, as its
         // length can behave as a source of entropy for heap addresses, when
         // ASLR is disabled and the heap is otherwise deterministic.
         let pid: u32 = process::id();
-        let filename = format!("{crate_name}-{pid:07}.rustc_profile");
-        let path = output_directory.join(filename);
-        let profiler =
-            Profiler::with_counter(&path, measureme::counters::Counter::by_name(counter_name)?)?;
-
-        let query_event_kind = profiler.alloc_string("Query");
-        let generic_activity_event_kind = profiler.alloc_string("GenericActivity");
-        let incremental_load_result_event_kind = profiler.alloc_string("IncrementalLoadResult");
-        let incremental_result_hashing_event_kind =
-            profiler.alloc_string("IncrementalResultHashing");
-        let query_blocked_event_kind = profiler.alloc_string("QueryBlocked");
-        let query_cache_hit_event_kind = profiler.alloc_string("QueryCacheHit");
-        let artifact_size_event_kind = profiler.alloc_string("ArtifactSize");
+        let profiler = match output_directory {
+            Some(output_directory) => {
+                fs::create_dir_all(output_directory)?;
+                let filename = format!("{crate_name}-{pid:07}.rustc_profile");
+                let path = output_directory.join(filename);
+                let counter = measureme::counters::Counter::by_name(counter_name)?;
+                Some(Profiler::with_counter(&path, counter)?)
+            }
+            None => None,
+        };
+        let chrome_trace = chrome_trace_directory.map(|directory| {
+            let path = directory.join(format!("{crate_name}-{pid:07}.trace.json"));
+            ChromeTrace::new(path, crate_name)
+        });
+
+        let alloc_string = |s: &str| alloc_string(profiler.as_ref(), chrome_trace.as_ref(), s);
+        let query_event_kind = alloc_string("Query");
+        let generic_activity_event_kind = alloc_string("GenericActivity");
+        let incremental_load_result_event_kind = alloc_string("IncrementalLoadResult");
+        let incremental_result_hashing_event_kind = alloc_string("IncrementalResultHashing");
+        let query_blocked_event_kind = alloc_string("QueryBlocked");
+        let query_cache_hit_event_kind = alloc_string("QueryCacheHit");
+        let artifact_size_event_kind = alloc_string("ArtifactSize");
 
         let mut event_filter_mask = EventFilter::empty();
 
@@ -618,13 +647,14 @@ pub fn new(
             query_blocked_event_kind,
             query_cache_hit_event_kind,
             artifact_size_event_kind,
+            chrome_trace,
         })
     }
 
     /// Allocates a new string in the profiling data. Does not do any caching
     /// or deduplication.
-    pub fn alloc_string<STR: SerializableString + ?Sized>(&self, s: &STR) -> StringId {
-        self.profiler.alloc_string(s)
+    pub fn alloc_string<STR: ProfileString + ?Sized>(&self, s: &STR) -> StringId {
+        alloc_string(self.profiler.as_ref(), self.chrome_trace.as_ref(), s)
     }
 
     /// Gets a `StringId` for the given string. This method makes sure that
@@ -650,7 +680,7 @@ pub fn get_or_alloc_cached_string<A>(&self, s: A) -> StringId
         match string_cache.entry(s.into()) {
             Entry::Occupied(e) => *e.get(),
             Entry::Vacant(e) => {
-                let string_id = self.profiler.alloc_string(&e.key()[..]);
+                let string_id = self.alloc_string(&e.key()[..]);
                 *e.insert(string_id)
             }
         }
@@ -658,7 +688,12 @@ pub fn get_or_alloc_cached_string<A>(&self, s: A) -> StringId
 
     pub fn map_query_invocation_id_to_string(&self, from: QueryInvocationId, to: StringId) {
         let from = StringId::new_virtual(from.0);
-        self.profiler.map_virtual_to_concrete_string(from, to);
+        if let Some(chrome_trace) = &self.chrome_trace {
+            chrome_trace.map_virtual_string(from, to);
+        }
+        if let Some(profiler) = &self.profiler {
+            profiler.map_virtual_to_concrete_string(from, to);
+        }
     }
 
     pub fn bulk_map_query_invocation_id_to_single_string<I>(&self, from: I, to: StringId)
@@ -666,7 +701,16 @@ pub fn bulk_map_query_invocation_id_to_single_string<I>(&self, from: I, to: Stri
         I: Iterator<Item = QueryInvocationId> + ExactSizeIterator,
     {
         let from = from.map(|qid| StringId::new_virtual(qid.0));
-        self.profiler.bulk_map_virtual_to_single_concrete_string(from, to);
+        match (&self.profiler, &self.chrome_trace) {
+            (Some(profiler), Some(chrome_trace)) => {
+                let from: Vec<_> = from.collect();
+                chrome_trace.bulk_map_virtual_string(from.iter().copied(), to);
+                profiler.bulk_map_virtual_to_single_concrete_string(from.into_iter(), to);
+            }
+            (Some(profiler), None) => profiler.bulk_map_virtual_to_single_concrete_string(from, to),
+            (None, Some(chrome_trace)) => chrome_trace.bulk_map_virtual_string(from, to),
+            (None, None) => {}
+        }
     }
 
     pub fn query_key_recording_enabled(&self) -> bool {
@@ -674,12 +718,69 @@ pub fn query_key_recording_enabled(&self) -> bool {
     }
 
     pub fn event_id_builder(&self) -> EventIdBuilder<'_> {
-        EventIdBuilder::new(&self.profiler)
+        EventIdBuilder { profiler: self }
+    }
+
+    /// The file the Chrome trace is written to, if one is recorded.
+    pub fn chrome_trace_path(&self) -> Option<&Path> {
+        self.chrome_trace.as_ref().map(|chrome_trace| chrome_trace.path())
+    }
+
+    /// Writes the Chrome trace recorded so far to [`SelfProfiler::chrome_trace_path`]. Does
+    /// nothing if no Chrome trace is recorded.
+    pub fn write_chrome_trace(&self) -> io::Result<()> {
+        match &self.chrome_trace {
+            Some(chrome_trace) => chrome_trace.write(),
+            None => Ok(()),
+        }
+    }
+}
+
+fn alloc_string<STR: ProfileString + ?Sized>(
+    profiler: Option<&Profiler>,
+    chrome_trace: Option<&ChromeTrace>,
+    s: &STR,
+) -> StringId {
+    let string_id = match profiler {
+        Some(profiler) => profiler.alloc_string(s),
+        None => chrome_trace.unwrap().next_string_id(),
+    };
+    if let Some(chrome_trace) = chrome_trace {
+        chrome_trace.record_string(string_id, s);
+    }
+    string_id
+}
+
+/// Builds `EventId`s like `measureme::EventIdBuilder` does, but allocates the strings through
+/// the `SelfProfiler`, so that they also end up in the Chrome trace.
+#[derive(Clone, Copy)]
+pub struct EventIdBuilder<'p> {
+    profiler: &'p SelfProfiler,
+}
+
+impl EventIdBuilder<'_> {
+    #[inline]
+    pub fn from_label(&self, label: StringId) -> EventId {
+        EventId::from_label(label)
+    }
+
+    pub fn from_label_and_arg(&self, label: StringId, arg: StringId) -> EventId {
+        self.from_label_and_args(label, &[arg])
+    }
+
+    pub fn from_label_and_args(&self, label: StringId, args: &[StringId]) -> EventId {
+        let mut components: SmallVec<[StringComponent<'_>; 7]> =
+            smallvec::smallvec![StringComponent::Ref(label)];
+        for &arg in args {
+            components.push(StringComponent::Value(SEPARATOR_BYTE));
+            components.push(StringComponent::Ref(arg));
+        }
+        EventId::from_label(self.profiler.alloc_string(&components[..]))
     }
 }
 
 #[must_use]
-pub struct TimingGuard<'a>(Option<measureme::TimingGuard<'a>>);
+pub struct TimingGuard<'a>(Option<measureme::TimingGuard<'a>>, Option<ChromeTraceGuard<'a>>);
 
 impl<'a> TimingGuard<'a> {
     #[inline]
@@ -689,26 +790,35 @@ pub fn start(
         event_id: EventId,
     ) -> TimingGuard<'a> {
         let thread_id = get_thread_id();
-        let raw_profiler = &profiler.profiler;
-        let timing_guard =
-            raw_profiler.start_recording_interval_event(event_kind, event_id, thread_id);
-        TimingGuard(Some(timing_guard))
+        let timing_guard = profiler.profiler.as_ref().map(|raw_profiler| {
+            raw_profiler.start_recording_interval_event(event_kind, event_id, thread_id)
+        });
+        let chrome_trace_guard = profiler.chrome_trace.as_ref().map(|chrome_trace| {
+            ChromeTraceGuard::start(chrome_trace, event_kind, event_id, thread_id)
+        });
+        TimingGuard(timing_guard, chrome_trace_guard)
     }
 
     #[inline]
     pub fn finish_with_query_invocation_id(self, query_invocation_id: QueryInvocationId) {
-        if let Some(guard) = self.0 {
+        let TimingGuard(guard, chrome_trace_guard) = self;
+        if guard.is_some() || chrome_trace_guard.is_some() {
             outline(|| {
                 let event_id = StringId::new_virtual(query_invocation_id.0);
                 let event_id = EventId::from_virtual(event_id);
-                guard.finish_with_override_event_id(event_id);
+                if let Some(chrome_trace_guard) = chrome_trace_guard {
+                    chrome_trace_guard.finish_with_override_event_id(event_id);
+                }
+                if let Some(guard) = guard {
+                    guard.finish_with_override_event_id(event_id);
+                }
             });
         }
     }
 
     #[inline]
     pub fn none() -> TimingGuard<'a> {
-        TimingGuard(None)
+        TimingGuard(None, None)
     }
 
     #[inline(always)]
@@ -804,6 +914,8 @@ pub fn print_time_passes_entry(
             eprintln!(r#"time: {entry}"#);
             return;
         }
+        // The pass is recorded by the self-profiler instead.
+        TimePassesFormat::Chrome => return,
         TimePassesFormat::Text => (),
     }
 
@@ -0,0 +1,279 @@
+//! Recording of self-profiling events in the [Chrome trace event format][format], for
+//! `-Z time-passes-format=chrome`.
+//!
+//! Alongside the `measureme` data, if any, the `SelfProfiler` then keeps the interval events
+//! and the contents of the strings it allocates in memory, and writes them to a JSON file at
+//! the end of the session. That file can be opened in Perfetto or `chrome://tracing`. Events
+//! recorded on the same thread nest by time, so queries show up below the activity or query
+//! that invoked them.
+//!
+//! [format]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use std::{fs, process};
+
+use measureme::event_id::SEPARATOR_BYTE;
+use measureme::{EventId, StringComponent, StringId};
+use parking_lot::{Mutex, RwLock};
+
+use crate::fx::FxHashMap;
+
+/// A string that can be allocated in the profiling data. Its contents are kept for the
+/// Chrome trace, which can't read them back from `measureme`.
+pub trait ProfileString: measureme::SerializableString {
+    #[doc(hidden)]
+    fn push_to(&self, out: &mut String, strings: &FxHashMap<StringId, String>);
+}
+
+impl ProfileString for str {
+    fn push_to(&self, out: &mut String, _: &FxHashMap<StringId, String>) {
+        out.push_str(self);
+    }
+}
+
+impl ProfileString for [StringComponent<'_>] {
+    fn push_to(&self, out: &mut String, strings: &FxHashMap<StringId, String>) {
+        for component in self {
+            match *component {
+                StringComponent::Value(s) => out.push_str(s),
+                StringComponent::Ref(id) => {
+                    out.push_str(strings.get(&id).map_or("<unknown>", |s| &s[..]))
+                }
+            }
+        }
+    }
+}
+
+impl<const N: usize> ProfileString for [StringComponent<'_>; N] {
+    fn push_to(&self, out: &mut String, strings: &FxHashMap<StringId, String>) {
+        self[..].push_to(out, strings)
+    }
+}
+
+pub(super) struct TraceEvent {
+    pub(super) event_kind: StringId,
+    pub(super) event_id: EventId,
+    pub(super) thread_id: u32,
+    pub(super) start: Duration,
+    pub(super) end: Duration,
+}
+
+#[derive(Default)]
+struct Recorded {
+    events: Vec<TraceEvent>,
+    thread_names: FxHashMap<u32, String>,
+}
+
+pub(super) struct ChromeTrace {
+    path: PathBuf,
+    process_name: String,
+    start_time: Instant,
+    /// The next id to allocate for a string, when there is no `measureme` profiler to do so.
+    next_string_id: AtomicU64,
+    strings: RwLock<FxHashMap<StringId, String>>,
+    /// The strings that the query invocation ids, used as virtual event ids, map to.
+    virtual_strings: Mutex<FxHashMap<StringId, StringId>>,
+    recorded: Mutex<Recorded>,
+}
+
+impl ChromeTrace {
+    pub(super) fn new(path: PathBuf, process_name: &str) -> ChromeTrace {
+        ChromeTrace {
+            path,
+            process_name: process_name.to_owned(),
+            start_time: Instant::now(),
+            // Above all virtual string ids, which are query invocation ids.
+            next_string_id: AtomicU64::new(1 << 32),
+            strings: Default::default(),
+            virtual_strings: Default::default(),
+            recorded: Default::default(),
+        }
+    }
+
+    pub(super) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(super) fn next_string_id(&self) -> StringId {
+        StringId::new(self.next_string_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    pub(super) fn record_string<STR: ProfileString + ?Sized>(&self, id: StringId, s: &STR) {
+        let mut strings = self.strings.write();
+        let mut contents = String::new();
+        s.push_to(&mut contents, &strings);
+        strings.insert(id, contents);
+    }
+
+    pub(super) fn map_virtual_string(&self, from: StringId, to: StringId) {
+        self.virtual_strings.lock().insert(from, to);
+    }
+
+    pub(super) fn bulk_map_virtual_string(
+        &self,
+        from: impl Iterator<Item = StringId>,
+        to: StringId,
+    ) {
+        self.virtual_strings.lock().extend(from.map(|from| (from, to)));
+    }
+
+    fn record_event(
+        &self,
+        event_kind: StringId,
+        event_id: EventId,
+        thread_id: u32,
+        start: Instant,
+    ) {
+        let end = Instant::now();
+        let event = TraceEvent {
+            event_kind,
+            event_id,
+            thread_id,
+            start: start - self.start_time,
+            end: end - self.start_time,
+        };
+        let mut recorded = self.recorded.lock();
+        recorded.events.push(event);
+        recorded.thread_names.entry(thread_id).or_insert_with(|| {
+            let thread = std::thread::current();
+            thread.name().map_or_else(|| format!("thread {thread_id}"), |name| name.to_owned())
+        });
+    }
+
+    pub(super) fn write(&self) -> io::Result<()> {
+        let strings = self.strings.read();
+        let virtual_strings = self.virtual_strings.lock();
+        let resolve = |id: StringId| {
+            let id = virtual_strings.get(&id).copied().unwrap_or(id);
+            strings.get(&id).map_or("<unknown>", |s| &s[..])
+        };
+
+        let mut recorded = self.recorded.lock();
+        let Recorded { events, thread_names } = &mut *recorded;
+        // Parents must come before the events nested in them.
+        events.sort_by_key(|event| (event.thread_id, event.start, std::cmp::Reverse(event.end)));
+        let mut thread_names: Vec<_> = thread_names.iter().collect();
+        thread_names.sort();
+
+        let pid = process::id();
+        let mut out = String::new();
+        out.push_str("{\"traceEvents\":[\n");
+        write!(
+            out,
+            r#"{{"name":"process_name","ph":"M","pid":{pid},"tid":0,"args":{{"name":{}}}}}"#,
+            Json(&self.process_name)
+        )
+        .unwrap();
+        for (thread_id, name) in thread_names {
+            write!(
+                out,
+                ",\n{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":{pid},\"tid\":{thread_id},\
+                 \"args\":{{\"name\":{}}}}}",
+                Json(name)
+            )
+            .unwrap();
+        }
+        for event in events.iter() {
+            out.push_str(",\n");
+            write_event(
+                &mut out,
+                pid,
+                event,
+                resolve(event.event_kind),
+                resolve(event.event_id.to_string_id()),
+            );
+        }
+        out.push_str("\n]}\n");
+
+        let mut file = io::BufWriter::new(fs::File::create(&self.path)?);
+        file.write_all(out.as_bytes())?;
+        file.flush()
+    }
+}
+
+/// Writes a complete event. `event_id` is the event's label, followed by its arguments,
+/// each preceded by `SEPARATOR_BYTE`.
+pub(super) fn write_event(
+    out: &mut String,
+    pid: u32,
+    event: &TraceEvent,
+    event_kind: &str,
+    event_id: &str,
+) {
+    let mut parts = event_id.split(SEPARATOR_BYTE);
+    let name = parts.next().unwrap_or_default();
+    write!(
+        out,
+        r#"{{"name":{},"cat":{},"ph":"X","ts":{:.3},"dur":{:.3},"pid":{pid},"tid":{}"#,
+        Json(name),
+        Json(event_kind),
+        event.start.as_secs_f64() * 1e6,
+        (event.end - event.start).as_secs_f64() * 1e6,
+        event.thread_id,
+    )
+    .unwrap();
+    let mut args = parts.enumerate().peekable();
+    if args.peek().is_some() {
+        out.push_str(r#","args":{"#);
+        for (i, arg) in args {
+            let separator = if i == 0 { "" } else { "," };
+            write!(out, r#"{separator}"arg{i}":{}"#, Json(arg)).unwrap();
+        }
+        out.push('}');
+    }
+    out.push('}');
+}
+
+/// Displays a string as a JSON string literal.
+struct Json<'a>(&'a str);
+
+impl std::fmt::Display for Json<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\t' => f.write_str("\\t")?,
+                c if c < ' ' => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+/// Records an interval event in the Chrome trace when dropped.
+pub(super) struct ChromeTraceGuard<'a> {
+    trace: &'a ChromeTrace,
+    event_kind: StringId,
+    event_id: EventId,
+    thread_id: u32,
+    start: Instant,
+}
+
+impl<'a> ChromeTraceGuard<'a> {
+    pub(super) fn start(
+        trace: &'a ChromeTrace,
+        event_kind: StringId,
+        event_id: EventId,
+        thread_id: u32,
+    ) -> ChromeTraceGuard<'a> {
+        ChromeTraceGuard { trace, event_kind, event_id, thread_id, start: Instant::now() }
+    }
+
+    pub(super) fn finish_with_override_event_id(mut self, event_id: EventId) {
+        self.event_id = event_id;
+    }
+}
+
+impl Drop for ChromeTraceGuard<'_> {
+    fn drop(&mut self) {
+        self.trace.record_event(self.event_kind, self.event_id, self.thread_id, self.start);
+    }
+}
@@ -1,4 +1,7 @@
-use super::JsonTimePassesEntry;
+use std::time::Duration;
+
+use super::chrome_trace::{write_event, TraceEvent};
+use super::{EventId, JsonTimePassesEntry};
 
 #[test]
 fn with_rss() {
@@ -17,3 +20,35 @@ fn no_rss() {
         r#"{"pass":"typeck","time":56.1,"rss_start":null,"rss_end":null}"#
     )
 }
+
+#[test]
+fn chrome_trace_event() {
+    let event = TraceEvent {
+        event_kind: EventId::INVALID.to_string_id(),
+        event_id: EventId::INVALID,
+        thread_id: 2,
+        start: Duration::from_micros(1500),
+        end: Duration::from_micros(2250),
+    };
+
+    let mut out = String::new();
+    write_event(&mut out, 7, &event, "GenericActivity", "LLVM_passes");
+    assert_eq!(
+        out,
+        concat!(
+            r#"{"name":"LLVM_passes","cat":"GenericActivity","ph":"X","#,
+            r#""ts":1500.000,"dur":750.000,"pid":7,"tid":2}"#,
+        )
+    );
+
+    let mut out = String::new();
+    write_event(&mut out, 7, &event, "Query", "typeck\u{1e}\"a\\b\"\u{1e}c\n");
+    assert_eq!(
+        out,
+        concat!(
+            r#"{"name":"typeck","cat":"Query","ph":"X","#,
+            r#""ts":1500.000,"dur":750.000,"pid":7,"tid":2,"#,
+            r#""args":{"arg0":"\"a\\b\"","arg1":"c\n"}}"#,
+        )
+    );
+}
@@ -0,0 +1,82 @@
+//! Compiling repeatedly for `--apply-suggestions`, see
+//! [`rustc_errors::apply_suggestions`].
+
+use std::fs;
+use std::path::Path;
+
+use rustc_errors::apply_suggestions::unified_diff;
+use rustc_errors::FatalError;
+use rustc_interface::interface;
+use rustc_session::config::{ApplySuggestions, ErrorOutputType, OutFileName};
+use rustc_session::EarlyDiagCtxt;
+
+use crate::catch_fatal_errors;
+
+/// The most compilations to reach a fixed point, in case suggestions keep
+/// changing the sources.
+const MAX_COMPILATIONS: usize = 4;
+
+/// Calls `compile` until the suggestions of a compilation don't change the
+/// sources anymore, then writes the fixed sources or their diff.
+pub(crate) fn run(
+    apply_suggestions: &ApplySuggestions,
+    error_format: ErrorOutputType,
+    mut compile: impl FnMut() -> interface::Result<()>,
+) -> interface::Result<()> {
+    let mut compilations = 0;
+    let result = loop {
+        compilations += 1;
+        {
+            let mut fixed = apply_suggestions.fixed.lock().unwrap();
+            fixed.last_compilation = compilations == MAX_COMPILATIONS;
+            fixed.changed = false;
+        }
+        // Applying the suggestions may fix the errors of a compilation.
+        let result = catch_fatal_errors(&mut compile);
+        if !apply_suggestions.fixed.lock().unwrap().changed {
+            break result;
+        }
+    };
+
+    write_fixed_sources(apply_suggestions, error_format)?;
+
+    match result {
+        Ok(result) => result,
+        Err(FatalError) => FatalError.raise(),
+    }
+}
+
+fn write_fixed_sources(
+    apply_suggestions: &ApplySuggestions,
+    error_format: ErrorOutputType,
+) -> interface::Result<()> {
+    let early_dcx = EarlyDiagCtxt::new(error_format);
+    let write = |path: &Path, contents: &str| {
+        fs::write(path, contents)
+            .map_err(|e| early_dcx.early_err(format!("failed to write `{}`: {e}", path.display())))
+    };
+
+    let fixed = apply_suggestions.fixed.lock().unwrap();
+    let mut sources: Vec<_> = fixed.sources.iter().collect();
+    sources.sort();
+
+    let Some(diff_file) = &apply_suggestions.diff else {
+        for (path, source) in sources {
+            write(path, source)?;
+        }
+        return Ok(());
+    };
+
+    let mut diff = String::new();
+    for (path, source) in sources {
+        let original = fs::read_to_string(path).map_err(|e| {
+            early_dcx.early_err(format!("failed to read `{}`: {e}", path.display()))
+        })?;
+        diff.push_str(&unified_diff(path, &original, source));
+    }
+    match diff_file {
+        OutFileName::Stdout => crate::print::print(format_args!("{diff}")),
+        OutFileName::Real(path) => write(path, &diff)?,
+    }
+    Ok(())
+}
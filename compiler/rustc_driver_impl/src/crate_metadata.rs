@@ -0,0 +1,157 @@
+//! The `--print crate-items-json`, `--print crate-deps-json` and `--print crate-cfg-json`
+//! requests, which describe the crate being compiled to build tools.
+//!
+//! Unlike the other print requests, these need the crate to be expanded and its names to
+//! be resolved, so they're printed after expansion, and compilation stops right after.
+
+use std::path::PathBuf;
+
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{CRATE_DEF_ID, LOCAL_CRATE};
+use rustc_metadata::creader::CStore;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::config::PrintKind;
+use rustc_session::cstore::CrateDepKind;
+use rustc_session::search_paths::PathKind;
+use serde_json::{json, Value};
+
+pub(crate) fn is_crate_metadata_print(kind: PrintKind) -> bool {
+    matches!(kind, PrintKind::CrateItems | PrintKind::CrateDeps | PrintKind::CrateCfg)
+}
+
+pub(crate) fn print_crate_metadata(tcx: TyCtxt<'_>) {
+    for req in &tcx.sess.opts.prints {
+        let info = match req.kind {
+            PrintKind::CrateItems => crate_items(tcx),
+            PrintKind::CrateDeps => crate_deps(tcx),
+            PrintKind::CrateCfg => crate_cfg(tcx),
+            _ => continue,
+        };
+        let mut info = serde_json::to_string_pretty(&info).unwrap();
+        info.push('\n');
+        req.out.overwrite(&info, tcx.sess);
+    }
+}
+
+/// The items that other crates can name, directly or through re-exports.
+fn crate_items(tcx: TyCtxt<'_>) -> Value {
+    let effective_visibilities = &tcx.resolutions(()).effective_visibilities;
+    let source_map = tcx.sess.source_map();
+    let mut items: Vec<_> = effective_visibilities
+        .iter()
+        .map(|(&def_id, _)| def_id)
+        .filter(|&def_id| {
+            def_id != CRATE_DEF_ID
+                && effective_visibilities.is_exported(def_id)
+                && !matches!(
+                    tcx.def_kind(def_id),
+                    DefKind::Use | DefKind::ExternCrate | DefKind::Ctor(..) | DefKind::GlobalAsm
+                )
+        })
+        .map(|def_id| {
+            let loc = source_map.lookup_char_pos(tcx.def_span(def_id).lo());
+            let path = with_no_trimmed_paths!(tcx.def_path_str(def_id));
+            (path, def_id, loc)
+        })
+        .collect();
+    items.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+    let items: Vec<_> = items
+        .into_iter()
+        .map(|(path, def_id, loc)| {
+            json!({
+                "path": path,
+                "kind": tcx.def_descr(def_id.to_def_id()),
+                "directly_public": effective_visibilities.is_directly_public(def_id),
+                "file": loc.file.name.prefer_remapped_unconditionaly().to_string(),
+                "line": loc.line,
+            })
+        })
+        .collect();
+    json!({ "name": tcx.crate_name(LOCAL_CRATE).as_str(), "items": items })
+}
+
+/// The crates loaded for this crate, with the files they were loaded from and the crates
+/// they depend on. Crates are referred to by an id, since several of them can have the
+/// same name.
+fn crate_deps(tcx: TyCtxt<'_>) -> Value {
+    let path = |source: &Option<(PathBuf, PathKind)>| {
+        source.as_ref().map(|(path, _)| path.display().to_string())
+    };
+
+    let crates: Vec<_> = tcx
+        .used_crates(())
+        .iter()
+        .map(|&cnum| {
+            let dependencies: Vec<_> = CStore::from_tcx(tcx)
+                .crate_dependencies(cnum)
+                .into_iter()
+                .map(|dep| dep.as_u32())
+                .collect();
+            let kind = match tcx.dep_kind(cnum) {
+                CrateDepKind::MacrosOnly => "macros-only",
+                CrateDepKind::Implicit => "implicit",
+                CrateDepKind::Explicit => "explicit",
+            };
+            let source = tcx.used_crate_source(cnum);
+            json!({
+                "id": cnum.as_u32(),
+                "name": tcx.crate_name(cnum).as_str(),
+                "hash": tcx.crate_hash(cnum).to_string(),
+                "kind": kind,
+                "private": tcx.is_private_dep(cnum),
+                "rlib": path(&source.rlib),
+                "rmeta": path(&source.rmeta),
+                "dylib": path(&source.dylib),
+                "dependencies": dependencies,
+            })
+        })
+        .collect();
+    let dependencies: Vec<_> = tcx
+        .used_crates(())
+        .iter()
+        .filter(|&&cnum| {
+            tcx.extern_crate(cnum).is_some_and(|extern_crate| extern_crate.is_direct())
+        })
+        .map(|cnum| cnum.as_u32())
+        .collect();
+
+    json!({
+        "name": tcx.crate_name(LOCAL_CRATE).as_str(),
+        "dependencies": dependencies,
+        "crates": crates,
+    })
+}
+
+/// The cfgs the crate is compiled with, as in `--print cfg`, and its enabled features.
+fn crate_cfg(tcx: TyCtxt<'_>) -> Value {
+    let mut cfg: Vec<_> = crate::printable_cfgs(tcx.sess)
+        .map(|(name, value)| (name.to_string(), value.map(|value| value.to_string())))
+        .collect();
+    cfg.sort();
+    let features: Vec<_> = cfg
+        .iter()
+        .filter(|(name, _)| name == "feature")
+        .filter_map(|(_, value)| value.clone())
+        .collect();
+    let cfg: Vec<_> =
+        cfg.into_iter().map(|(name, value)| json!({ "name": name, "value": value })).collect();
+
+    let crate_features = tcx.features();
+    let mut unstable_features: Vec<_> = crate_features
+        .declared_lang_features
+        .iter()
+        .map(|&(name, ..)| name)
+        .chain(crate_features.declared_lib_features.iter().map(|&(name, _)| name))
+        .map(|name| name.to_string())
+        .collect();
+    unstable_features.sort();
+
+    json!({
+        "name": tcx.crate_name(LOCAL_CRATE).as_str(),
+        "cfg": cfg,
+        "features": features,
+        "unstable_features": unstable_features,
+    })
+}
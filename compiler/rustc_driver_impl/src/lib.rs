@@ -41,6 +41,7 @@
     get_resident_set_size, print_time_passes_entry, TimePassesFormat,
 };
 use rustc_errors::emitter::stderr_destination;
+use rustc_errors::explanation::Explanation;
 use rustc_errors::registry::Registry;
 use rustc_errors::{
     markdown, ColorConfig, DiagCtxt, ErrCode, ErrorGuaranteed, FatalError, PResult,
@@ -61,7 +62,7 @@
 use rustc_session::output::collect_crate_types;
 use rustc_session::{config, filesearch, EarlyDiagCtxt, Session};
 use rustc_span::source_map::FileLoader;
-use rustc_span::symbol::sym;
+use rustc_span::symbol::{sym, Symbol};
 use rustc_span::FileName;
 use rustc_target::json::ToJson;
 use rustc_target::spec::{Target, TargetTriple};
@@ -86,7 +87,9 @@
 #[allow(unused_imports)]
 use {do_not_use_print as print, do_not_use_print as println};
 
+mod apply_suggestions;
 pub mod args;
+mod crate_metadata;
 pub mod pretty;
 #[macro_use]
 mod print;
@@ -308,19 +311,39 @@ fn run_compiler(
     let ice_file = ice_path_with_config(Some(&sopts.unstable_opts)).clone();
 
     if let Some(ref code) = matches.opt_str("explain") {
-        handle_explain(&default_early_dcx, diagnostics_registry(), code, sopts.color);
+        handle_explain(
+            &default_early_dcx,
+            diagnostics_registry(),
+            code,
+            sopts.color,
+            sopts.error_format,
+            sopts.unstable_opts.unstable_options,
+        );
         return Ok(());
     }
 
     let (odir, ofile) = make_output(&matches);
-    let mut config = interface::Config {
-        opts: sopts,
+
+    let (input, has_input) = match make_input(&default_early_dcx, &matches.free) {
+        Err(reported) => return Err(reported),
+        Ok(Some(input)) => (input, true), // has input: normal compilation
+        Ok(None) => match matches.free.as_slice() {
+            [] => (Input::File(PathBuf::new()), false), // no input: we will exit early
+            [_] => panic!("make_input should have provided valid inputs"),
+            [fst, snd, ..] => default_early_dcx.early_fatal(format!(
+                "multiple input filenames provided (first two filenames are `{fst}` and `{snd}`)"
+            )),
+        },
+    };
+
+    let make_config = |file_loader, make_codegen_backend| interface::Config {
+        opts: sopts.clone(),
         crate_cfg: matches.opt_strs("cfg"),
         crate_check_cfg: matches.opt_strs("check-cfg"),
-        input: Input::File(PathBuf::new()),
-        output_file: ofile,
-        output_dir: odir,
-        ice_file,
+        input: input.clone(),
+        output_file: ofile.clone(),
+        output_dir: odir.clone(),
+        ice_file: ice_file.clone(),
         file_loader,
         locale_resources: DEFAULT_LOCALE_RESOURCES,
         lint_caps: Default::default(),
@@ -330,27 +353,35 @@ fn run_compiler(
         override_queries: None,
         make_codegen_backend,
         registry: diagnostics_registry(),
-        using_internal_features,
-        expanded_args: args,
+        using_internal_features: Arc::clone(&using_internal_features),
+        expanded_args: args.clone(),
     };
 
-    let has_input = match make_input(&default_early_dcx, &matches.free) {
-        Err(reported) => return Err(reported),
-        Ok(Some(input)) => {
-            config.input = input;
-            true // has input: normal compilation
-        }
-        Ok(None) => match matches.free.as_slice() {
-            [] => false, // no input: we will exit early
-            [_] => panic!("make_input should have provided valid inputs"),
-            [fst, snd, ..] => default_early_dcx.early_fatal(format!(
-                "multiple input filenames provided (first two filenames are `{fst}` and `{snd}`)"
-            )),
-        },
+    let Some(apply_suggestions) = &sopts.apply_suggestions else {
+        drop(default_early_dcx);
+        return compile(make_config(file_loader, make_codegen_backend), callbacks, has_input);
     };
 
+    // Both can only be used once, but `--apply-suggestions` may compile
+    // several times.
+    if file_loader.is_some() || make_codegen_backend.is_some() {
+        default_early_dcx.early_fatal(
+            "`--apply-suggestions` can't be used with a custom file loader or codegen backend",
+        );
+    }
     drop(default_early_dcx);
 
+    apply_suggestions::run(apply_suggestions, sopts.error_format, || {
+        compile(make_config(None, None), callbacks, has_input)
+    })
+}
+
+/// Compiles once with `config`.
+fn compile(
+    mut config: interface::Config,
+    callbacks: &mut (dyn Callbacks + Send),
+    has_input: bool,
+) -> interface::Result<()> {
     callbacks.config(&mut config);
 
     interface::run_compiler(config, |compiler| {
@@ -437,6 +468,11 @@ fn run_compiler(
                 return early_exit();
             }
 
+            if sess.opts.prints.iter().any(|p| crate_metadata::is_crate_metadata_print(p.kind)) {
+                queries.global_ctxt()?.enter(|tcx| crate_metadata::print_crate_metadata(tcx));
+                return early_exit();
+            }
+
             queries.global_ctxt()?.enter(|tcx| {
                 passes::write_dep_info(tcx);
             });
@@ -533,13 +569,39 @@ pub enum Compilation {
     Continue,
 }
 
-fn handle_explain(early_dcx: &EarlyDiagCtxt, registry: Registry, code: &str, color: ColorConfig) {
+fn handle_explain(
+    early_dcx: &EarlyDiagCtxt,
+    registry: Registry,
+    code: &str,
+    color: ColorConfig,
+    error_format: ErrorOutputType,
+    unstable_options: bool,
+) {
+    if let ErrorOutputType::Json { .. } = error_format
+        && !unstable_options
+    {
+        early_dcx
+            .early_fatal("`--explain` with `--error-format=json` requires `-Z unstable-options`");
+    }
+
     // Allow "E0123" or "0123" form.
     let upper_cased_code = code.to_ascii_uppercase();
     let start = if upper_cased_code.starts_with('E') { 1 } else { 0 };
     if let Ok(code) = upper_cased_code[start..].parse::<u32>()
         && let Ok(description) = registry.try_find_description(ErrCode::from_u32(code))
     {
+        // With `--error-format=json`, print the sections of the explanation for tools to render.
+        if let ErrorOutputType::Json { pretty, .. } = error_format {
+            let explanation = Explanation::parse(ErrCode::from_u32(code), description);
+            let json = if pretty {
+                serde_json::to_string_pretty(&explanation)
+            } else {
+                serde_json::to_string(&explanation)
+            };
+            safe_println!("{}", json.unwrap());
+            return;
+        }
+
         let mut is_in_code_block = false;
         let mut text = String::new();
         // Slice off the leading newline and print.
@@ -692,6 +754,23 @@ fn list_metadata(sess: &Session, metadata_loader: &dyn MetadataLoader) {
     }
 }
 
+/// Returns the cfgs that `--print cfg` shows, in the order they were set.
+fn printable_cfgs(sess: &Session) -> impl Iterator<Item = (Symbol, Option<Symbol>)> + '_ {
+    sess.psess.config.iter().copied().filter(|&(name, value)| {
+        // Note that crt-static is a specially recognized cfg
+        // directive that's printed out here as part of
+        // rust-lang/rust#37406, but in general the
+        // `target_feature` cfg is gated under
+        // rust-lang/rust#29717. For now this is just
+        // specifically allowing the crt-static cfg and that's
+        // it, this is intended to get into Cargo and then go
+        // through to build scripts.
+        (name == sym::target_feature && value == Some(sym::crt_dash_static))
+            || sess.is_nightly_build()
+            || find_gated_cfg(|cfg_sym| cfg_sym == name).is_none()
+    })
+}
+
 fn print_crate_info(
     codegen_backend: &dyn CodegenBackend,
     sess: &Session,
@@ -709,6 +788,9 @@ fn print_crate_info(
     if sess.opts.prints.iter().all(|p| p.kind == NativeStaticLibs || p.kind == LinkArgs) {
         return Compilation::Continue;
     }
+    // The crate metadata prints are special too - printed after expansion
+    let print_crate_metadata =
+        sess.opts.prints.iter().any(|p| crate_metadata::is_crate_metadata_print(p.kind));
 
     let attrs = if parse_attrs {
         let result = parse_crate_attrs(sess);
@@ -772,30 +854,12 @@ fn print_crate_info(
                 println_info!("{id}");
             }
             Cfg => {
-                let mut cfgs = sess
-                    .psess
-                    .config
-                    .iter()
-                    .filter_map(|&(name, value)| {
-                        // Note that crt-static is a specially recognized cfg
-                        // directive that's printed out here as part of
-                        // rust-lang/rust#37406, but in general the
-                        // `target_feature` cfg is gated under
-                        // rust-lang/rust#29717. For now this is just
-                        // specifically allowing the crt-static cfg and that's
-                        // it, this is intended to get into Cargo and then go
-                        // through to build scripts.
-                        if (name != sym::target_feature || value != Some(sym::crt_dash_static))
-                            && !sess.is_nightly_build()
-                            && find_gated_cfg(|cfg_sym| cfg_sym == name).is_some()
-                        {
-                            return None;
-                        }
-
+                let mut cfgs = printable_cfgs(sess)
+                    .map(|(name, value)| {
                         if let Some(value) = value {
-                            Some(format!("{name}=\"{value}\""))
+                            format!("{name}=\"{value}\"")
                         } else {
-                            Some(name.to_string())
+                            name.to_string()
                         }
                     })
                     .collect::<Vec<String>>();
@@ -858,6 +922,7 @@ fn print_crate_info(
             // Any output here interferes with Cargo's parsing of other printed output
             NativeStaticLibs => {}
             LinkArgs => {}
+            CrateItems | CrateDeps | CrateCfg => continue,
             SplitDebuginfo => {
                 use rustc_target::spec::SplitDebuginfo::{Off, Packed, Unpacked};
 
@@ -883,7 +948,7 @@ fn print_crate_info(
 
         req.out.overwrite(&crate_info, sess);
     }
-    Compilation::Stop
+    if print_crate_metadata { Compilation::Continue } else { Compilation::Stop }
 }
 
 /// Prints version information
@@ -19,7 +19,8 @@
 pub use fluent_bundle::types::FluentType;
 use fluent_bundle::FluentResource;
 pub use fluent_bundle::{self, FluentArgs, FluentError, FluentValue};
-use fluent_syntax::parser::ParserError;
+use fluent_syntax::ast;
+use fluent_syntax::parser::{self, ParserError};
 use icu_provider_adapters::fallback::{LocaleFallbackProvider, LocaleFallbacker};
 #[cfg(parallel_compiler)]
 use intl_memoizer::concurrent::IntlLangMemoizer;
@@ -243,6 +244,100 @@ pub fn fallback_fluent_bundle(
     }))
 }
 
+/// Returns the source of the messages of a Fluent `resource` that `bundle` doesn't translate,
+/// or only translates some attributes of. Without a bundle, returns all of them.
+///
+/// Terms (`-term = ...`) are returned along with the messages that use them, since a translation
+/// of those messages needs them too.
+pub fn untranslated_messages<'a>(resource: &'a str, bundle: Option<&FluentBundle>) -> Vec<&'a str> {
+    let entries = fluent_entries(resource);
+    let untranslated: Vec<_> = entries
+        .iter()
+        .filter(|entry| !entry.id.starts_with('-') && !is_translated(bundle, entry))
+        .map(|entry| entry.source)
+        .collect();
+    let is_used = |term: &str| {
+        untranslated.iter().any(|source| {
+            source.match_indices(term).any(|(i, _)| {
+                // `{ -term }` or `{ -term(arg: value) }`, but not `{ -term-suffix }`.
+                !source[i + term.len()..]
+                    .starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '-')
+            })
+        })
+    };
+    entries
+        .iter()
+        .filter(|entry| {
+            let is_term = entry.id.starts_with('-');
+            (is_term && is_used(entry.id)) || (!is_term && untranslated.contains(&entry.source))
+        })
+        .map(|entry| entry.source)
+        .collect()
+}
+
+/// A message or term of a Fluent resource.
+struct FluentEntry<'a> {
+    /// The identifier, starting with `-` for terms.
+    id: &'a str,
+    attributes: Vec<&'a str>,
+    /// The source of the entry, without its comment.
+    source: &'a str,
+}
+
+/// Parses the messages and terms of a Fluent resource.
+fn fluent_entries(resource: &str) -> Vec<FluentEntry<'_>> {
+    // The AST borrows from `resource`, so the offsets of its slices give the extent of each
+    // entry: from the line of its identifier up to the line that starts the next entry.
+    let offset = |slice: &str| slice.as_ptr() as usize - resource.as_ptr() as usize;
+    let line_start = |slice: &str| resource[..offset(slice)].rfind('\n').map_or(0, |i| i + 1);
+    let comment_start =
+        |comment: &ast::Comment<&str>| comment.content.first().map(|s| line_start(s));
+
+    let ast = match parser::parse(resource) {
+        Ok(ast) => ast,
+        // The entries that failed to parse become `Junk`.
+        Err((ast, _)) => ast,
+    };
+    // The start of each entry, including its comment, and the start of its identifier.
+    let mut starts = vec![];
+    for entry in &ast.body {
+        match entry {
+            ast::Entry::Message(ast::Message { id, attributes, comment, .. })
+            | ast::Entry::Term(ast::Term { id, attributes, comment, .. }) => {
+                let id_start = line_start(id.name);
+                let start = comment.as_ref().and_then(comment_start).unwrap_or(id_start);
+                let id = &resource[id_start..offset(id.name) + id.name.len()];
+                let attributes = attributes.iter().map(|attr| attr.id.name).collect();
+                starts.push((start, Some((id_start, id, attributes))));
+            }
+            ast::Entry::Comment(comment)
+            | ast::Entry::GroupComment(comment)
+            | ast::Entry::ResourceComment(comment) => {
+                starts.extend(comment_start(comment).map(|start| (start, None)));
+            }
+            ast::Entry::Junk { content } => starts.push((line_start(content), None)),
+        }
+    }
+
+    let mut entries = vec![];
+    for (i, (_, entry)) in starts.iter().enumerate() {
+        if let Some((id_start, id, attributes)) = entry {
+            let end = starts.get(i + 1).map_or(resource.len(), |&(start, _)| start);
+            let source = resource[*id_start..end].trim_end();
+            entries.push(FluentEntry { id, attributes: attributes.clone(), source });
+        }
+    }
+    entries
+}
+
+/// Whether `bundle` has a translation of the message, and of all of its attributes.
+fn is_translated(bundle: Option<&FluentBundle>, entry: &FluentEntry<'_>) -> bool {
+    let Some(message) = bundle.and_then(|bundle| bundle.get_message(entry.id)) else {
+        return false;
+    };
+    entry.attributes.iter().all(|attr| message.get_attribute(attr).is_some())
+}
+
 /// Identifier for the Fluent message/attribute corresponding to a diagnostic message.
 type FluentId = Cow<'static, str>;
 
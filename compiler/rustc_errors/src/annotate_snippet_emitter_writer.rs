@@ -3,34 +3,59 @@
 //! This is the equivalent of `./emitter.rs` but making use of the
 //! [`annotate-snippets`][annotate_snippets] library instead of building the output ourselves.
 //!
+//! The diagnostic and each child or suggestion that has spans are rendered as separate
+//! messages, in the same order as the `HumanEmitter` renders them. Children without spans
+//! become footers (`= note: ...`) of the message they follow. The library can't render
+//! suggestions as diffs of the code they change, so the `HumanEmitter` renders them.
+//!
 //! [annotate_snippets]: https://docs.rs/crate/annotate-snippets/
 
+use std::error::Report;
+use std::io::{self, Write};
+use std::ops::Range;
+
+use annotate_snippets::renderer::{AnsiColor, Style as AnsiStyle};
 use annotate_snippets::{Renderer, Snippet};
-use rustc_data_structures::sync::Lrc;
+use derive_setters::Setters;
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::{IntoDynSyncSend, Lrc};
 use rustc_error_messages::FluentArgs;
 use rustc_span::source_map::SourceMap;
-use rustc_span::SourceFile;
+use rustc_span::{BytePos, SourceFile};
+use termcolor::NoColor;
 
-use crate::emitter::FileWithAnnotatedLines;
-use crate::snippet::Line;
+use crate::diagnostic::DiagLocation;
+use crate::emitter::{
+    emit_to_destination, should_show_source_code, Destination, HumanEmitter, DEFAULT_COLUMN_WIDTH,
+};
+use crate::snippet::StyledString;
 use crate::translation::{to_fluent_args, Translate};
 use crate::{
     CodeSuggestion, DiagInner, DiagMessage, Emitter, ErrCode, FluentBundle, LazyFallbackBundle,
-    Level, MultiSpan, Style, Subdiag,
+    Level, MultiSpan, Style, Subdiag, SuggestionStyle, TerminalUrl,
 };
 
 /// Generates diagnostics using annotate-snippet
+#[derive(Setters)]
 pub struct AnnotateSnippetEmitter {
-    source_map: Option<Lrc<SourceMap>>,
+    #[setters(skip)]
+    dst: IntoDynSyncSend<Destination>,
+    sm: Option<Lrc<SourceMap>>,
     fluent_bundle: Option<Lrc<FluentBundle>>,
+    #[setters(skip)]
     fallback_bundle: LazyFallbackBundle,
 
     /// If true, hides the longer explanation text
     short_message: bool,
     /// If true, will normalize line numbers with `LL` to prevent noise in UI test diffs.
     ui_testing: bool,
+    ignored_directories_in_source_blocks: Vec<String>,
+    diagnostic_width: Option<usize>,
 
     macro_backtrace: bool,
+    macro_expansion_trace: bool,
+    track_diagnostics: bool,
+    terminal_url: TerminalUrl,
 }
 
 impl Translate for AnnotateSnippetEmitter {
@@ -51,6 +76,10 @@ fn emit_diagnostic(&mut self, mut diag: DiagInner) {
         let mut suggestions = diag.suggestions.unwrap_or(vec![]);
         self.primary_span_formatted(&mut diag.span, &mut suggestions, &fluent_args);
 
+        if self.macro_expansion_trace {
+            self.render_macro_expansion_trace(&diag.span, &mut diag.children);
+        }
+
         self.fix_multispans_in_extern_macros_and_render_macro_backtrace(
             &mut diag.span,
             &mut diag.children,
@@ -66,21 +95,84 @@ fn emit_diagnostic(&mut self, mut diag: DiagInner) {
             &diag.span,
             &diag.children,
             &suggestions,
+            self.track_diagnostics.then_some(&diag.emitted_at),
         );
     }
 
     fn source_map(&self) -> Option<&Lrc<SourceMap>> {
-        self.source_map.as_ref()
+        self.sm.as_ref()
     }
 
     fn should_show_explain(&self) -> bool {
         !self.short_message
     }
+
+    fn supports_color(&self) -> bool {
+        self.dst.supports_color()
+    }
+}
+
+/// A part of a diagnostic, rendered separately from the others.
+enum Piece {
+    Message(OwnedMessage),
+    /// A suggestion, rendered by the `HumanEmitter`.
+    Suggestion(Vec<Vec<StyledString>>),
+}
+
+/// A message to render, owning the strings that [`annotate_snippets::Message`] borrows.
+struct OwnedMessage {
+    level: Level,
+    title: String,
+    id: Option<String>,
+    snippets: Vec<OwnedSnippet>,
+    footers: Vec<(Level, String)>,
+    /// Text printed as is after the message.
+    trailer: Option<String>,
+}
+
+impl OwnedMessage {
+    fn new(level: Level, title: String) -> OwnedMessage {
+        OwnedMessage { level, title, id: None, snippets: vec![], footers: vec![], trailer: None }
+    }
 }
 
-/// Provides the source string for the given `line` of `file`
-fn source_string(file: Lrc<SourceFile>, line: &Line) -> String {
-    file.get_line(line.line_index - 1).map(|a| a.to_string()).unwrap_or_default()
+/// The annotated source of one file.
+struct OwnedSnippet {
+    origin: Option<String>,
+    source: String,
+    line_start: usize,
+    annotations: Vec<OwnedAnnotation>,
+    fold: bool,
+}
+
+struct OwnedAnnotation {
+    /// Byte range in the snippet's source.
+    range: Range<usize>,
+    label: Option<String>,
+    is_primary: bool,
+}
+
+impl OwnedSnippet {
+    fn to_snippet(&self) -> Snippet<'_> {
+        let mut snippet = Snippet::source(&self.source).line_start(self.line_start).fold(self.fold);
+        if let Some(origin) = &self.origin {
+            snippet = snippet.origin(origin);
+        }
+        snippet.annotations(self.annotations.iter().map(|annotation| {
+            // `annotate-snippets` only underlines errors with `^`, so primary spans are
+            // annotated as errors, and the renderer gives errors the colour of the message.
+            let level = if annotation.is_primary {
+                annotate_snippets::Level::Error
+            } else {
+                annotate_snippets::Level::Info
+            };
+            let annotation_ = level.span(annotation.range.clone());
+            match &annotation.label {
+                Some(label) => annotation_.label(label),
+                None => annotation_,
+            }
+        }))
+    }
 }
 
 /// Maps [`crate::Level`] to [`annotate_snippets::Level`]
@@ -92,39 +184,51 @@ fn annotation_level_for_level(level: Level) -> annotate_snippets::Level {
         Level::ForceWarning(_) | Level::Warning => annotate_snippets::Level::Warning,
         Level::Note | Level::OnceNote => annotate_snippets::Level::Note,
         Level::Help | Level::OnceHelp => annotate_snippets::Level::Help,
-        // FIXME(#59346): Not sure how to map this level
+        // Failure notes without spans are printed without a level, see `render`.
         Level::FailureNote => annotate_snippets::Level::Error,
         Level::Allow => panic!("Should not call with Allow"),
         Level::Expect(_) => panic!("Should not call with Expect"),
     }
 }
 
+/// The style of the level's name, as in `Level::color`.
+fn style_for_level(level: Level) -> AnsiStyle {
+    let color = match level {
+        Level::Bug | Level::Fatal | Level::Error | Level::DelayedBug | Level::FailureNote => {
+            AnsiColor::BrightRed
+        }
+        Level::ForceWarning(_) | Level::Warning => {
+            if cfg!(windows) {
+                AnsiColor::BrightYellow
+            } else {
+                AnsiColor::Yellow
+            }
+        }
+        Level::Note | Level::OnceNote => AnsiColor::BrightGreen,
+        Level::Help | Level::OnceHelp => AnsiColor::BrightCyan,
+        Level::Allow | Level::Expect(_) => unreachable!(),
+    };
+    AnsiStyle::new().fg_color(Some(color.into())).bold()
+}
+
 impl AnnotateSnippetEmitter {
-    pub fn new(
-        source_map: Option<Lrc<SourceMap>>,
-        fluent_bundle: Option<Lrc<FluentBundle>>,
-        fallback_bundle: LazyFallbackBundle,
-        short_message: bool,
-        macro_backtrace: bool,
-    ) -> Self {
+    pub fn new(dst: Destination, fallback_bundle: LazyFallbackBundle) -> Self {
         Self {
-            source_map,
-            fluent_bundle,
+            dst: IntoDynSyncSend(dst),
+            sm: None,
+            fluent_bundle: None,
             fallback_bundle,
-            short_message,
+            short_message: false,
             ui_testing: false,
-            macro_backtrace,
+            ignored_directories_in_source_blocks: Vec::new(),
+            diagnostic_width: None,
+            macro_backtrace: false,
+            macro_expansion_trace: false,
+            track_diagnostics: false,
+            terminal_url: TerminalUrl::No,
         }
     }
 
-    /// Allows to modify `Self` to enable or disable the `ui_testing` flag.
-    ///
-    /// If this is set to true, line numbers will be normalized as `LL` in the output.
-    pub fn ui_testing(mut self, ui_testing: bool) -> Self {
-        self.ui_testing = ui_testing;
-        self
-    }
-
     fn emit_messages_default(
         &mut self,
         level: &Level,
@@ -132,80 +236,304 @@ fn emit_messages_default(
         args: &FluentArgs<'_>,
         code: &Option<ErrCode>,
         msp: &MultiSpan,
-        _children: &[Subdiag],
-        _suggestions: &[CodeSuggestion],
+        children: &[Subdiag],
+        suggestions: &[CodeSuggestion],
+        emitted_at: Option<&DiagLocation>,
     ) {
-        let message = self.translate_messages(messages, args);
-        if let Some(source_map) = &self.source_map {
-            // Make sure our primary file comes first
-            let primary_lo = if let Some(primary_span) = msp.primary_span().as_ref() {
-                if primary_span.is_dummy() {
-                    // FIXME(#59346): Not sure when this is the case and what
-                    // should be done if it happens
-                    return;
+        let mut title = self.translate_messages(messages, args).into_owned();
+        if matches!(level, Level::Bug | Level::DelayedBug) {
+            title = format!("internal compiler error: {title}");
+        }
+
+        // Hold the lock for the whole diagnostic, as in `emit_to_destination`, so that the
+        // output of several compiler processes doesn't get interleaved.
+        let _buffer_lock = crate::lock::acquire_global_lock("rustc_errors");
+        let result = if self.short_message {
+            let rendered = self.short_message_text(*level, &title, code, msp, args);
+            self.write_rendered(&rendered)
+        } else {
+            let mut main = OwnedMessage::new(*level, title);
+            main.id = code.map(|code| match self.terminal_url {
+                TerminalUrl::Yes => {
+                    let path = "https://doc.rust-lang.org/error_codes";
+                    format!("\x1b]8;;{path}/{code}.html\x07{code}\x1b]8;;\x07")
+                }
+                TerminalUrl::No | TerminalUrl::Auto => code.to_string(),
+            });
+            main.snippets = self.snippets(msp, args);
+            main.trailer =
+                emitted_at.map(|tracked| format!("-Ztrack-diagnostics: created at {tracked}"));
+
+            let mut pieces = vec![Piece::Message(main)];
+            for child in children {
+                assert!(child.level.can_be_subdiag());
+                let title = self.translate_messages(&child.messages, args).into_owned();
+                let snippets = self.snippets(&child.span, args);
+                if snippets.is_empty() {
+                    push_footer(&mut pieces, child.level, title);
                 } else {
-                    source_map.lookup_char_pos(primary_span.lo())
+                    let mut message = OwnedMessage::new(child.level, title);
+                    message.snippets = snippets;
+                    pieces.push(Piece::Message(message));
                 }
-            } else {
-                // FIXME(#59346): Not sure when this is the case and what
-                // should be done if it happens
-                return;
-            };
-            let mut annotated_files = FileWithAnnotatedLines::collect_annotations(self, args, msp);
-            if let Ok(pos) =
-                annotated_files.binary_search_by(|x| x.file.name.cmp(&primary_lo.file.name))
-            {
-                annotated_files.swap(0, pos);
             }
-            // owned: file name, line source, line index, annotations
-            type Owned = (String, String, usize, Vec<crate::snippet::Annotation>);
-            let annotated_files: Vec<Owned> = annotated_files
-                .into_iter()
-                .flat_map(|annotated_file| {
-                    let file = annotated_file.file;
-                    annotated_file
-                        .lines
-                        .into_iter()
-                        .map(|line| {
-                            // Ensure the source file is present before we try
-                            // to load a string from it.
-                            // FIXME(#115869): support -Z ignore-directory-in-diagnostics-source-blocks
-                            source_map.ensure_source_file_source_present(&file);
-                            (
-                                format!("{}", source_map.filename_for_diagnostics(&file.name)),
-                                source_string(file.clone(), &line),
-                                line.line_index,
-                                line.annotations,
-                            )
-                        })
-                        .collect::<Vec<Owned>>()
-                })
-                .collect();
-            let code = code.map(|code| code.to_string());
-
-            let snippets =
-                annotated_files.iter().map(|(file_name, source, line_index, annotations)| {
-                    Snippet::source(source)
-                        .line_start(*line_index)
-                        .origin(file_name)
-                        // FIXME(#59346): Not really sure when `fold` should be true or false
-                        .fold(false)
-                        .annotations(annotations.iter().map(|annotation| {
-                            annotation_level_for_level(*level)
-                                .span(annotation.start_col.display..annotation.end_col.display)
-                                .label(annotation.label.as_deref().unwrap_or_default())
-                        }))
-                });
-            let mut message = annotation_level_for_level(*level).title(&message).snippets(snippets);
-            if let Some(code) = code.as_deref() {
-                message = message.id(code)
+            let mut human_emitter = None;
+            for suggestion in suggestions {
+                match suggestion.style {
+                    SuggestionStyle::CompletelyHidden => {
+                        // do not display this suggestion, it is meant only for tools
+                    }
+                    SuggestionStyle::HideCodeAlways => {
+                        let title = self.translate_message(&suggestion.msg, args);
+                        let title = title.map_err(Report::new).unwrap().into_owned();
+                        push_footer(&mut pieces, Level::Help, title);
+                    }
+                    SuggestionStyle::HideCodeInline
+                    | SuggestionStyle::ShowCode
+                    | SuggestionStyle::ShowAlways => {
+                        let (human_emitter, max_line_num_len) =
+                            human_emitter.get_or_insert_with(|| {
+                                let mut human_emitter = self.human_emitter();
+                                let max_line_num_len =
+                                    human_emitter.max_line_num_len(msp, children);
+                                (human_emitter, max_line_num_len)
+                            });
+                        let buffer = human_emitter.render_suggestion(
+                            msp,
+                            suggestion,
+                            args,
+                            &Level::Help,
+                            *max_line_num_len,
+                        );
+                        if let Some(buffer) = buffer {
+                            pieces.push(Piece::Suggestion(buffer.render()));
+                        }
+                    }
+                }
+            }
+            self.write_pieces(&pieces, *level)
+        };
+        if let Err(e) = result {
+            panic!("failed to emit error: {e}")
+        }
+    }
+
+    /// A `HumanEmitter` with the same settings, to render suggestions.
+    fn human_emitter(&self) -> HumanEmitter {
+        HumanEmitter::new(Box::new(NoColor::new(io::sink())), self.fallback_bundle.clone())
+            .sm(self.sm.clone())
+            .fluent_bundle(self.fluent_bundle.clone())
+            .ui_testing(self.ui_testing)
+            .ignored_directories_in_source_blocks(self.ignored_directories_in_source_blocks.clone())
+            .diagnostic_width(self.diagnostic_width)
+    }
+
+    fn write_rendered(&mut self, rendered: &str) -> io::Result<()> {
+        writeln!(self.dst, "{rendered}")?;
+        self.dst.flush()
+    }
+
+    fn write_pieces(&mut self, pieces: &[Piece], level: Level) -> io::Result<()> {
+        for (i, piece) in pieces.iter().enumerate() {
+            match piece {
+                Piece::Message(message) => {
+                    let rendered = self.render(message);
+                    write!(self.dst, "{rendered}")?;
+                    // A failure note without spans is only its title, without a line break.
+                    if i + 1 < pieces.len() || !level.is_failure_note() {
+                        writeln!(self.dst)?;
+                    }
+                }
+                Piece::Suggestion(lines) => {
+                    emit_to_destination(lines, &Level::Help, &mut self.dst, false)?
+                }
+            }
+        }
+        writeln!(self.dst)?;
+        self.dst.flush()
+    }
+
+    fn render(&self, message: &OwnedMessage) -> String {
+        if message.level == Level::FailureNote && message.snippets.is_empty() {
+            // The failure note level itself does not provide any useful diagnostic information
+            return message.title.clone();
+        }
+
+        let mut rendered_message = annotation_level_for_level(message.level)
+            .title(&message.title)
+            .snippets(message.snippets.iter().map(OwnedSnippet::to_snippet))
+            .footers(
+                message
+                    .footers
+                    .iter()
+                    .map(|(level, footer)| annotation_level_for_level(*level).title(footer)),
+            );
+        if let Some(id) = &message.id {
+            rendered_message = rendered_message.id(id);
+        }
+
+        let mut rendered = self.renderer(message.level).render(rendered_message).to_string();
+        if let Some(trailer) = &message.trailer {
+            rendered.push('\n');
+            rendered.push_str(trailer);
+        }
+        rendered
+    }
+
+    fn renderer(&self, level: Level) -> Renderer {
+        let renderer = if self.dst.supports_color() {
+            // Primary spans are annotated as errors, so errors get the colour of the level of
+            // the message being rendered.
+            Renderer::styled()
+                .warning(style_for_level(Level::Warning))
+                .error(style_for_level(level))
+        } else {
+            Renderer::plain()
+        };
+        let term_width = if let Some(width) = self.diagnostic_width {
+            width
+        } else if self.ui_testing {
+            DEFAULT_COLUMN_WIDTH
+        } else {
+            termize::dimensions().map(|(w, _)| w).unwrap_or(DEFAULT_COLUMN_WIDTH)
+        };
+        renderer.anonymized_line_numbers(self.ui_testing).term_width(term_width)
+    }
+
+    /// Renders a diagnostic on a single line, with the location of its primary span and the
+    /// labels of its primary spans, like the `HumanEmitter` does for `--error-format=short`.
+    fn short_message_text(
+        &self,
+        level: Level,
+        title: &str,
+        code: &Option<ErrCode>,
+        msp: &MultiSpan,
+        args: &FluentArgs<'_>,
+    ) -> String {
+        let mut text = String::new();
+        if let (Some(sm), Some(primary_span)) = (&self.sm, msp.primary_span()) {
+            if !primary_span.is_dummy() {
+                let loc = sm.lookup_char_pos(primary_span.lo());
+                text = format!(
+                    "{}:{}:{}: ",
+                    sm.filename_for_diagnostics(&loc.file.name),
+                    sm.doctest_offset_line(&loc.file.name, loc.line),
+                    loc.col.0 + 1,
+                );
+            }
+        }
+        if level != Level::FailureNote {
+            text.push_str(level.to_str());
+            if let Some(code) = code {
+                text.push_str(&format!("[{code}]"));
+            }
+            text.push_str(": ");
+        }
+        text.push_str(title);
+
+        let labels = msp
+            .span_labels()
+            .into_iter()
+            .filter_map(|label| match label.label {
+                Some(msg) if label.is_primary => {
+                    let text = self.translate_message(&msg, args).ok()?;
+                    if !text.trim().is_empty() { Some(text.to_string()) } else { None }
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        if !labels.is_empty() {
+            text.push_str(": ");
+            text.push_str(&labels);
+        }
+        text
+    }
+
+    /// Collects the annotated source of each file the spans of `msp` point into, starting with
+    /// the file of the primary span.
+    fn snippets(&self, msp: &MultiSpan, args: &FluentArgs<'_>) -> Vec<OwnedSnippet> {
+        let Some(sm) = &self.sm else {
+            return vec![];
+        };
+
+        // The annotations of each file, as absolute byte ranges.
+        let mut files: FxIndexMap<BytePos, (Lrc<SourceFile>, Vec<OwnedAnnotation>)> =
+            Default::default();
+        for span_label in msp.span_labels() {
+            if span_label.span.is_dummy() {
+                continue;
+            }
+            let file = sm.lookup_source_file(span_label.span.lo());
+            let lo = span_label.span.lo();
+            let hi = span_label.span.hi().min(file.end_position()).max(lo);
+            let label = span_label.label.and_then(|label| {
+                let label = self.translate_message(&label, args).map_err(Report::new).unwrap();
+                (!label.is_empty()).then(|| label.into_owned())
+            });
+            files.entry(file.start_pos).or_insert_with(|| (file, vec![])).1.push(OwnedAnnotation {
+                range: lo.0 as usize..hi.0 as usize,
+                label,
+                is_primary: span_label.is_primary,
+            });
+        }
+
+        let mut files: Vec<_> = files.into_values().collect();
+        files.sort_by(|(a, _), (b, _)| a.name.cmp(&b.name));
+        // Make sure our primary file comes first
+        if let Some(primary_span) = msp.primary_span() {
+            let primary_file = sm.lookup_source_file(primary_span.lo());
+            if let Some(pos) = files.iter().position(|(file, _)| file.name == primary_file.name) {
+                files.swap(0, pos);
             }
-            // FIXME(#59346): Figure out if we can _always_ print to stderr or not.
-            // `emitter.rs` has the `Destination` enum that lists various possible output
-            // destinations.
-            let renderer = Renderer::plain().anonymized_line_numbers(self.ui_testing);
-            eprintln!("{}", renderer.render(message))
         }
-        // FIXME(#59346): Is it ok to return None if there's no source_map?
+
+        files
+            .into_iter()
+            .filter_map(|(file, mut annotations)| {
+                // we can't annotate anything if the source is unavailable.
+                if !should_show_source_code(&self.ignored_directories_in_source_blocks, sm, &file) {
+                    return None;
+                }
+
+                let line_of = |pos: usize| {
+                    file.lookup_line(file.relative_position(BytePos(pos as u32))).unwrap_or(0)
+                };
+                let first_line = annotations.iter().map(|a| line_of(a.range.start)).min()?;
+                let last_line = annotations.iter().map(|a| line_of(a.range.end)).max()?;
+                let source = (first_line..=last_line)
+                    .map(|line| file.get_line(line).unwrap_or_default())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                // Make the ranges relative to the first line, and give empty spans a width.
+                let start = file.line_bounds(first_line).start.0 as usize;
+                for annotation in &mut annotations {
+                    let lo = (annotation.range.start - start).min(source.len());
+                    let mut hi = (annotation.range.end - start).min(source.len());
+                    if lo == hi {
+                        hi = source[lo..].chars().next().map_or(hi, |c| hi + c.len_utf8());
+                    }
+                    annotation.range = lo..hi;
+                }
+
+                Some(OwnedSnippet {
+                    origin: Some(sm.filename_for_diagnostics(&file.name).to_string()),
+                    source,
+                    line_start: sm.doctest_offset_line(&file.name, first_line + 1),
+                    annotations,
+                    fold: true,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Adds a footer for a child or suggestion without spans to the last message, or a message of
+/// its own if it follows a suggestion.
+fn push_footer(pieces: &mut Vec<Piece>, level: Level, footer: String) {
+    match pieces.last_mut() {
+        Some(Piece::Message(message)) => message.footers.push((level, footer)),
+        _ => pieces.push(Piece::Message(OwnedMessage::new(level, footer))),
     }
 }
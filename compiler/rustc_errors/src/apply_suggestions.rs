@@ -0,0 +1,449 @@
+//! Support for `--apply-suggestions`, which makes the compiler apply the
+//! machine-applicable suggestions of chosen lints and errors to its sources.
+//!
+//! While compiling, [`SuggestionCollector`] records these suggestions and holds
+//! back all diagnostics. Once compilation is done, it applies the suggestions
+//! to the [`FixedSources`], which are loaded instead of the files on disk. If
+//! that changed any source, the driver compiles again, until no suggestion
+//! applies anymore. Only the diagnostics of that last compilation are reported,
+//! since those of earlier compilations may be about code that has changed
+//! since. The driver finally writes the fixed sources, or a diff of them.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::{fs, io, iter, mem};
+
+use rustc_data_structures::fx::{FxHashSet, FxIndexMap};
+use rustc_data_structures::sync::Lrc;
+use rustc_span::source_map::{FileLoader, SourceMap};
+use rustc_span::FileName;
+
+use crate::emitter::{DynEmitter, Emitter};
+use crate::translation::Translate;
+use crate::{Applicability, DiagInner, FluentBundle, Level, SubstitutionPart};
+
+/// Replaces the bytes `start..end` of a source file, as it is on disk, with
+/// `text`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Replacement {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// The replacements of one suggestion, which are only applied together.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Fix {
+    pub replacements: Vec<Replacement>,
+}
+
+/// The sources fixed so far, shared by the compilations of one invocation of
+/// the compiler.
+#[derive(Default, Debug)]
+pub struct FixedSources {
+    /// The fixed contents of each file, keyed by the path it is loaded from.
+    pub sources: FxIndexMap<PathBuf, String>,
+    /// Whether this is the last compilation, whose suggestions aren't applied
+    /// anymore.
+    pub last_compilation: bool,
+    /// Whether the suggestions of this compilation changed any source, which
+    /// means that the compiler will compile them again.
+    pub changed: bool,
+}
+
+/// Loads fixed sources instead of the files they were read from.
+pub struct FixedSourcesLoader {
+    pub inner: Box<dyn FileLoader + Send + Sync>,
+    pub fixed: Arc<Mutex<FixedSources>>,
+}
+
+impl FileLoader for FixedSourcesLoader {
+    fn file_exists(&self, path: &Path) -> bool {
+        self.fixed.lock().unwrap().sources.contains_key(path) || self.inner.file_exists(path)
+    }
+
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        match self.fixed.lock().unwrap().sources.get(path) {
+            Some(source) => Ok(source.clone()),
+            None => self.inner.read_file(path),
+        }
+    }
+
+    fn read_binary_file(&self, path: &Path) -> io::Result<Lrc<[u8]>> {
+        self.inner.read_binary_file(path)
+    }
+}
+
+/// What the [`SuggestionCollector`] holds back from the emitter it wraps.
+enum HeldBack {
+    Diagnostic(DiagInner),
+    ArtifactNotification(PathBuf, String),
+    FutureBreakageReport(Vec<DiagInner>),
+    UnusedExterns(rustc_lint_defs::Level, Vec<String>),
+}
+
+/// Wraps the emitter of a compilation with `--apply-suggestions`, see the
+/// module documentation.
+pub struct SuggestionCollector {
+    inner: Box<DynEmitter>,
+    sm: Lrc<SourceMap>,
+    /// The lints and error codes whose suggestions are applied.
+    names: FxHashSet<String>,
+    fixed: Arc<Mutex<FixedSources>>,
+    fixes: FxIndexMap<PathBuf, Vec<Fix>>,
+    held_back: Vec<HeldBack>,
+}
+
+impl SuggestionCollector {
+    pub fn new(
+        inner: Box<DynEmitter>,
+        sm: Lrc<SourceMap>,
+        names: &[String],
+        fixed: Arc<Mutex<FixedSources>>,
+    ) -> SuggestionCollector {
+        SuggestionCollector {
+            inner,
+            sm,
+            names: names.iter().cloned().collect(),
+            fixed,
+            fixes: FxIndexMap::default(),
+            held_back: Vec::new(),
+        }
+    }
+
+    fn is_selected(&self, diag: &DiagInner) -> bool {
+        if !matches!(diag.level, Level::Error | Level::ForceWarning(_) | Level::Warning) {
+            return false;
+        }
+        diag.is_lint.as_ref().is_some_and(|lint| self.names.contains(&lint.name))
+            || diag.code.is_some_and(|code| self.names.contains(&code.to_string()))
+    }
+
+    fn collect(&mut self, diag: &DiagInner) {
+        let Ok(suggestions) = &diag.suggestions else { return };
+        for suggestion in suggestions {
+            if suggestion.applicability != Applicability::MachineApplicable {
+                continue;
+            }
+            // Several alternatives leave the choice to the user.
+            let [substitution] = &suggestion.substitutions[..] else { continue };
+            if let Some((path, fix)) = self.fix(&substitution.parts) {
+                self.fixes.entry(path).or_default().push(fix);
+            }
+        }
+    }
+
+    /// Turns `parts` into a fix, if they all replace code of the same file on
+    /// disk.
+    fn fix(&self, parts: &[SubstitutionPart]) -> Option<(PathBuf, Fix)> {
+        let mut file = None;
+        let mut replacements = Vec::with_capacity(parts.len());
+        for part in parts {
+            // The code of a macro is shared by all its expansions.
+            if part.span.from_expansion() {
+                return None;
+            }
+            let sf = self.sm.lookup_source_file(part.span.lo());
+            if !sf.contains(part.span.hi()) {
+                return None;
+            }
+            replacements.push(Replacement {
+                start: sf.original_relative_byte_pos(part.span.lo()).0 as usize,
+                end: sf.original_relative_byte_pos(part.span.hi()).0 as usize,
+                text: part.snippet.clone(),
+            });
+            match &file {
+                Some(file) if !Lrc::ptr_eq(file, &sf) => return None,
+                _ => file = Some(sf),
+            }
+        }
+        let FileName::Real(name) = &file?.name else { return None };
+        let path = name.local_path()?.to_owned();
+        replacements.sort();
+        Some((path, Fix { replacements }))
+    }
+
+    fn emit_held_back(&mut self) {
+        for held_back in self.held_back.drain(..) {
+            match held_back {
+                HeldBack::Diagnostic(diag) => self.inner.emit_diagnostic(diag),
+                HeldBack::ArtifactNotification(path, artifact_type) => {
+                    self.inner.emit_artifact_notification(&path, &artifact_type)
+                }
+                HeldBack::FutureBreakageReport(diags) => {
+                    self.inner.emit_future_breakage_report(diags)
+                }
+                HeldBack::UnusedExterns(lint_level, unused_externs) => {
+                    let unused_externs: Vec<_> =
+                        unused_externs.iter().map(String::as_str).collect();
+                    self.inner.emit_unused_externs(lint_level, &unused_externs)
+                }
+            }
+        }
+    }
+}
+
+impl Translate for SuggestionCollector {
+    fn fluent_bundle(&self) -> Option<&Lrc<FluentBundle>> {
+        self.inner.fluent_bundle()
+    }
+
+    fn fallback_fluent_bundle(&self) -> &FluentBundle {
+        self.inner.fallback_fluent_bundle()
+    }
+}
+
+impl Emitter for SuggestionCollector {
+    fn emit_diagnostic(&mut self, diag: DiagInner) {
+        if self.is_selected(&diag) {
+            self.collect(&diag);
+        }
+        let is_bug = diag.level == Level::Bug;
+        self.held_back.push(HeldBack::Diagnostic(diag));
+        // The compiler is about to crash, so there won't be another
+        // compilation to report the diagnostics of.
+        if is_bug {
+            self.fixed.lock().unwrap().last_compilation = true;
+            self.emit_held_back();
+        }
+    }
+
+    fn emit_artifact_notification(&mut self, path: &Path, artifact_type: &str) {
+        let held_back = HeldBack::ArtifactNotification(path.to_owned(), artifact_type.to_owned());
+        self.held_back.push(held_back);
+    }
+
+    fn emit_future_breakage_report(&mut self, diags: Vec<DiagInner>) {
+        self.held_back.push(HeldBack::FutureBreakageReport(diags));
+    }
+
+    fn emit_unused_externs(&mut self, lint_level: rustc_lint_defs::Level, unused_externs: &[&str]) {
+        let unused_externs = unused_externs.iter().map(|&s| s.to_owned()).collect();
+        self.held_back.push(HeldBack::UnusedExterns(lint_level, unused_externs));
+    }
+
+    fn should_show_explain(&self) -> bool {
+        self.inner.should_show_explain()
+    }
+
+    fn supports_color(&self) -> bool {
+        self.inner.supports_color()
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        self.inner.source_map()
+    }
+}
+
+impl Drop for SuggestionCollector {
+    /// Applies the collected fixes once compilation is done, and reports the
+    /// diagnostics unless that changed a source.
+    fn drop(&mut self) {
+        let mut fixed = self.fixed.lock().unwrap();
+        if !fixed.last_compilation {
+            for (path, fixes) in mem::take(&mut self.fixes) {
+                let source = match fixed.sources.get(&path) {
+                    Some(source) => source.clone(),
+                    None => match fs::read_to_string(&path) {
+                        Ok(source) => source,
+                        Err(_) => continue,
+                    },
+                };
+                let (source, applied) = apply_fixes(&source, fixes);
+                if applied > 0 {
+                    fixed.sources.insert(path, source);
+                    fixed.changed = true;
+                }
+            }
+        }
+        if !fixed.changed {
+            drop(fixed);
+            self.emit_held_back();
+        }
+    }
+}
+
+/// Applies as many of `fixes` to `source` as possible, and returns the result
+/// and the number of fixes that changed it.
+///
+/// Fixes are considered in sorted order, and skipped if they conflict with a
+/// fix applied before, so that the result doesn't depend on the order the
+/// diagnostics were emitted in. A skipped fix is usually suggested again when
+/// compiling the result.
+pub fn apply_fixes(source: &str, mut fixes: Vec<Fix>) -> (String, usize) {
+    fixes.sort();
+    fixes.dedup();
+
+    let mut applied: Vec<&Replacement> = Vec::new();
+    let mut count = 0;
+    for fix in &fixes {
+        let valid = fix.replacements.iter().all(|r| {
+            r.start <= r.end && source.is_char_boundary(r.start) && source.is_char_boundary(r.end)
+        });
+        if !valid || fix.replacements.iter().all(|r| source[r.start..r.end] == r.text) {
+            continue;
+        }
+        let conflicts = fix.replacements.iter().enumerate().any(|(i, r)| {
+            fix.replacements[..i]
+                .iter()
+                .chain(applied.iter().copied())
+                .any(|other| conflict(r, other))
+        });
+        if !conflicts {
+            applied.extend(&fix.replacements);
+            count += 1;
+        }
+    }
+
+    applied.sort();
+    let mut result = String::with_capacity(source.len());
+    let mut pos = 0;
+    for r in applied {
+        result.push_str(&source[pos..r.start]);
+        result.push_str(&r.text);
+        pos = r.end;
+    }
+    result.push_str(&source[pos..]);
+    (result, count)
+}
+
+/// Whether `a` and `b` can't both be applied. Two insertions at the same
+/// position conflict as well, since it's not clear which one goes first.
+fn conflict(a: &Replacement, b: &Replacement) -> bool {
+    (a.start < b.end && b.start < a.end) || a.start == b.start
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Edit {
+    Keep,
+    Remove,
+    Insert,
+}
+
+/// The number of unchanged lines shown around changes in a diff.
+const CONTEXT_LINES: usize = 3;
+
+/// Renders the changes from `old` to `new`, the contents of `path`, as a
+/// unified diff.
+pub fn unified_diff(path: &Path, old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.split_inclusive('\n').collect();
+    let new: Vec<&str> = new.split_inclusive('\n').collect();
+
+    // Each line of the diff, with the number of old and new lines before it.
+    let mut lines = Vec::new();
+    let (mut o, mut n) = (0, 0);
+    for edit in diff_lines(&old, &new) {
+        match edit {
+            Edit::Keep => lines.push((' ', old[o], o, n)),
+            Edit::Remove => lines.push(('-', old[o], o, n)),
+            Edit::Insert => lines.push(('+', new[n], o, n)),
+        }
+        if edit != Edit::Insert {
+            o += 1;
+        }
+        if edit != Edit::Remove {
+            n += 1;
+        }
+    }
+
+    let changes: Vec<usize> = (0..lines.len()).filter(|&i| lines[i].0 != ' ').collect();
+    let mut diff = String::new();
+    if changes.is_empty() {
+        return diff;
+    }
+    diff.push_str(&format!("--- {}\n+++ {}\n", path.display(), path.display()));
+
+    let mut i = 0;
+    while i < changes.len() {
+        let start = changes[i].saturating_sub(CONTEXT_LINES);
+        let mut end = changes[i] + 1;
+        i += 1;
+        // Changes whose context would touch are shown in the same hunk.
+        while i < changes.len() && changes[i] <= end + 2 * CONTEXT_LINES {
+            end = changes[i] + 1;
+            i += 1;
+        }
+        let end = (end + CONTEXT_LINES).min(lines.len());
+
+        let hunk = &lines[start..end];
+        let old_len = hunk.iter().filter(|line| line.0 != '+').count();
+        let new_len = hunk.iter().filter(|line| line.0 != '-').count();
+        // An empty range starts at the line before it.
+        let old_start = hunk[0].2 + usize::from(old_len > 0);
+        let new_start = hunk[0].3 + usize::from(new_len > 0);
+        diff.push_str(&format!("@@ -{old_start},{old_len} +{new_start},{new_len} @@\n"));
+        for &(tag, line, _, _) in hunk {
+            diff.push(tag);
+            diff.push_str(line);
+            if !line.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    diff
+}
+
+/// Finds a shortest sequence of edits that turns `old` into `new`.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let prefix = iter::zip(old, new).take_while(|(a, b)| a == b).count();
+    let suffix = iter::zip(old[prefix..].iter().rev(), new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+
+    // Myers' algorithm: `v[k]` is the furthest position in `a` reached on
+    // diagonal `k` with `d` edits, and `trace[d]` is `v` before the `d`-th
+    // edit, to walk back along the path found.
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let offset = n + m + 1;
+    let index = |k: isize| (k + offset) as usize;
+    let mut v = vec![0; 2 * offset as usize + 1];
+    let mut trace = Vec::new();
+    'search: for d in 0..=n + m {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = vec![Edit::Keep; suffix];
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k =
+            if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) { k + 1 } else { k - 1 };
+        let prev_x = v[index(prev_k)];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            edits.push(if x == prev_x { Edit::Insert } else { Edit::Remove });
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.resize(edits.len() + prefix, Edit::Keep);
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests;
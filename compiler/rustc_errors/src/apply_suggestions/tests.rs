@@ -0,0 +1,111 @@
+use super::*;
+
+fn fix(replacements: &[(usize, usize, &str)]) -> Fix {
+    let replacements = replacements
+        .iter()
+        .map(|&(start, end, text)| Replacement { start, end, text: text.to_owned() })
+        .collect();
+    Fix { replacements }
+}
+
+#[test]
+fn apply_disjoint_fixes() {
+    let source = "use a;\nuse b;\nfn main() {}\n";
+    let fixes = vec![fix(&[(7, 14, "")]), fix(&[(0, 0, "// x\n")]), fix(&[(10, 11, "c")])];
+    // Renaming `b` conflicts with removing `use b;`, which comes first.
+    assert_eq!(apply_fixes(source, fixes), ("// x\nuse a;\nfn main() {}\n".to_owned(), 2));
+}
+
+#[test]
+fn apply_fixes_in_sorted_order() {
+    let source = "let X = 1;";
+    let a = fix(&[(4, 5, "x")]);
+    let b = fix(&[(4, 5, "y")]);
+    let expected = ("let x = 1;".to_owned(), 1);
+    assert_eq!(apply_fixes(source, vec![a.clone(), b.clone()]), expected);
+    assert_eq!(apply_fixes(source, vec![b, a]), expected);
+}
+
+#[test]
+fn apply_multipart_fix() {
+    let source = "foo(bar)";
+    let wrap = fix(&[(0, 0, "Some("), (8, 8, ")")]);
+    // Both parts of `wrap` are dropped if one of them conflicts.
+    let borrow = fix(&[(0, 0, "&")]);
+    assert_eq!(apply_fixes(source, vec![wrap.clone()]), ("Some(foo(bar))".to_owned(), 1));
+    assert_eq!(apply_fixes(source, vec![wrap, borrow]), ("&foo(bar)".to_owned(), 1));
+}
+
+#[test]
+fn skip_duplicate_and_unchanged_fixes() {
+    let source = "let x = 1;";
+    let fixes = vec![fix(&[(4, 5, "y")]), fix(&[(4, 5, "y")]), fix(&[(8, 9, "1")])];
+    assert_eq!(apply_fixes(source, fixes), ("let y = 1;".to_owned(), 1));
+}
+
+#[test]
+fn skip_invalid_fixes() {
+    let source = "let é = 1;";
+    let fixes = vec![fix(&[(4, 5, "e")]), fix(&[(9, 20, "")])];
+    assert_eq!(apply_fixes(source, fixes), (source.to_owned(), 0));
+}
+
+#[test]
+fn diff_hunks() {
+    let line = |i| format!("{i}\n");
+    let old: String = (1..=20).map(line).collect();
+    let new: String = (1..=20)
+        .flat_map(|i| match i {
+            2 => vec!["two\n".to_owned()],
+            5 => vec![],
+            18 => vec![line(i), "18.5\n".to_owned()],
+            _ => vec![line(i)],
+        })
+        .collect();
+    let expected = "\
+--- src/lib.rs
++++ src/lib.rs
+@@ -1,8 +1,7 @@
+ 1
+-2
++two
+ 3
+ 4
+-5
+ 6
+ 7
+ 8
+@@ -16,5 +15,6 @@
+ 16
+ 17
+ 18
++18.5
+ 19
+ 20
+";
+    assert_eq!(unified_diff(Path::new("src/lib.rs"), &old, &new), expected);
+}
+
+#[test]
+fn diff_without_trailing_newline() {
+    let expected = "\
+--- a.rs
++++ a.rs
+@@ -1,2 +1,2 @@
+ a
+-b
+\\ No newline at end of file
++c
+\\ No newline at end of file
+";
+    assert_eq!(unified_diff(Path::new("a.rs"), "a\nb", "a\nc"), expected);
+}
+
+#[test]
+fn diff_of_empty_file() {
+    assert_eq!(unified_diff(Path::new("a.rs"), "", ""), "");
+    assert_eq!(
+        unified_diff(Path::new("a.rs"), "", "a\n"),
+        "--- a.rs\n+++ a.rs\n@@ -0,0 +1,1 @@\n+a\n"
+    );
+}
@@ -38,7 +38,7 @@
 };
 
 /// Default column width, used in tests and when terminal dimensions cannot be determined.
-const DEFAULT_COLUMN_WIDTH: usize = 140;
+pub(crate) const DEFAULT_COLUMN_WIDTH: usize = 140;
 
 /// Describes the way the content of the `rendered` field of the json output is generated
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -439,6 +439,36 @@ fn render_multispan_macro_backtrace(&self, span: &mut MultiSpan, always_backtrac
         }
     }
 
+    /// Adds a note for each `macro_rules!` expansion that the primary spans come from, showing
+    /// the code it produced with the part they point at highlighted. Expansions are only
+    /// recorded with `-Z macro-expansion-trace`.
+    ///
+    /// This must be called before `fix_multispans_in_extern_macros` moves the spans out of the
+    /// expansions.
+    fn render_macro_expansion_trace(&self, span: &MultiSpan, children: &mut Vec<Subdiag>) {
+        let mut notes = vec![];
+        let context: Vec<Span> = span.span_labels().into_iter().map(|label| label.span).collect();
+        for &primary_span in span.primary_spans() {
+            let mut sp = primary_span;
+            while !sp.ctxt().is_root() {
+                let expn_id = sp.ctxt().outer_expn();
+                let expn_data = expn_id.expn_data();
+                if let Some(expanded_span) =
+                    expn_id.expansion_trace().and_then(|trace| trace.expanded_span(sp, &context))
+                {
+                    let msg = format!("in this expansion of `{}`", expn_data.kind.descr());
+                    notes.push(Subdiag {
+                        level: Level::Note,
+                        messages: vec![(DiagMessage::from(msg), Style::NoStyle)],
+                        span: MultiSpan::from_span(expanded_span),
+                    });
+                }
+                sp = expn_data.call_site;
+            }
+        }
+        children.splice(0..0, notes);
+    }
+
     // This does a small "fix" for multispans by looking to see if it can find any that
     // point directly at external macros. Since these are often difficult to read,
     // this will change the span to point at the use site.
@@ -501,6 +531,10 @@ fn emit_diagnostic(&mut self, mut diag: DiagInner) {
         let mut suggestions = diag.suggestions.unwrap_or(vec![]);
         self.primary_span_formatted(&mut diag.span, &mut suggestions, &fluent_args);
 
+        if self.macro_expansion_trace {
+            self.render_macro_expansion_trace(&diag.span, &mut diag.children);
+        }
+
         self.fix_multispans_in_extern_macros_and_render_macro_backtrace(
             &mut diag.span,
             &mut diag.children,
@@ -611,6 +645,7 @@ pub struct HumanEmitter {
     diagnostic_width: Option<usize>,
 
     macro_backtrace: bool,
+    macro_expansion_trace: bool,
     track_diagnostics: bool,
     terminal_url: TerminalUrl,
 }
@@ -635,6 +670,7 @@ pub fn new(dst: Destination, fallback_bundle: LazyFallbackBundle) -> HumanEmitte
             ignored_directories_in_source_blocks: Vec::new(),
             diagnostic_width: None,
             macro_backtrace: false,
+            macro_expansion_trace: false,
             track_diagnostics: false,
             terminal_url: TerminalUrl::No,
         }
@@ -1197,6 +1233,16 @@ fn get_multispan_max_line_num(&mut self, msp: &MultiSpan) -> usize {
         max
     }
 
+    /// The width of the line numbers in the margin of a diagnostic.
+    pub(crate) fn max_line_num_len(&mut self, span: &MultiSpan, children: &[Subdiag]) -> usize {
+        if self.ui_testing {
+            ANONYMIZED_LINE_NUM.len()
+        } else {
+            let n = self.get_max_line_num(span, children);
+            num_decimal_digits(n)
+        }
+    }
+
     fn get_max_line_num(&mut self, span: &MultiSpan, children: &[Subdiag]) -> usize {
         let primary = self.get_multispan_max_line_num(span);
         children
@@ -1783,9 +1829,24 @@ fn emit_suggestion_default(
         level: &Level,
         max_line_num_len: usize,
     ) -> io::Result<()> {
-        let Some(ref sm) = self.sm else {
+        let Some(buffer) = self.render_suggestion(span, suggestion, args, level, max_line_num_len)
+        else {
             return Ok(());
         };
+        emit_to_destination(&buffer.render(), level, &mut self.dst, self.short_message)
+    }
+
+    /// Renders a suggestion as a diff of the code it changes. Also used by the
+    /// `AnnotateSnippetEmitter`, since `annotate-snippets` can't render diffs.
+    pub(crate) fn render_suggestion(
+        &self,
+        span: &MultiSpan,
+        suggestion: &CodeSuggestion,
+        args: &FluentArgs<'_>,
+        level: &Level,
+        max_line_num_len: usize,
+    ) -> Option<StyledBuffer> {
+        let sm = self.sm.as_ref()?;
 
         // Render the replacements for each suggestion
         let suggestions = suggestion.splice_lines(sm);
@@ -1797,7 +1858,7 @@ fn emit_suggestion_default(
             // suggestions and filtering there, we just don't emit the suggestion.
             // Suggestions coming from macros can also have malformed spans. This is a heavy handed
             // approach to avoid ICEs by ignoring the suggestion outright.
-            return Ok(());
+            return None;
         }
 
         let mut buffer = StyledBuffer::new();
@@ -2125,8 +2186,7 @@ fn emit_suggestion_default(
             let msg = format!("and {} other candidate{}", others, pluralize!(others));
             buffer.puts(row_num, max_line_num_len + 3, &msg, Style::NoStyle);
         }
-        emit_to_destination(&buffer.render(), level, &mut self.dst, self.short_message)?;
-        Ok(())
+        Some(buffer)
     }
 
     #[instrument(level = "trace", skip(self, args, code, children, suggestions))]
@@ -2141,12 +2201,7 @@ fn emit_messages_default(
         suggestions: &[CodeSuggestion],
         emitted_at: Option<&DiagLocation>,
     ) {
-        let max_line_num_len = if self.ui_testing {
-            ANONYMIZED_LINE_NUM.len()
-        } else {
-            let n = self.get_max_line_num(span, children);
-            num_decimal_digits(n)
-        };
+        let max_line_num_len = self.max_line_num_len(span, children);
 
         match self.emit_messages_default_inner(
             span,
@@ -2726,7 +2781,7 @@ fn overlaps(a1: &Annotation, a2: &Annotation, padding: usize) -> bool {
     )
 }
 
-fn emit_to_destination(
+pub(crate) fn emit_to_destination(
     rendered_buffer: &[Vec<StyledString>],
     lvl: &Level,
     dst: &mut Destination,
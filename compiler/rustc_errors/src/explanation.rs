@@ -0,0 +1,194 @@
+//! Structured versions of the error code explanations in `rustc_error_codes`, for
+//! `rustc --explain EXXXX --error-format=json` and `--json=explanation-summary`.
+//!
+//! The markdown of an explanation is parsed with [`crate::markdown`] and split into
+//! sections: the prose, the erroneous examples (`compile_fail` code blocks, or code
+//! blocks tagged with the error code) and the fixed examples (the other Rust code blocks).
+
+use serde::Serialize;
+
+use crate::codes::ErrCode;
+use crate::markdown::{MdStream, MdTree};
+
+#[cfg(test)]
+mod tests;
+
+/// An error code explanation, split into sections.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Explanation {
+    /// The error code, e.g. "E0384".
+    pub code: String,
+    /// The first paragraph of the explanation, which describes the error in a sentence.
+    pub summary: String,
+    /// The page of the explanation in the error code index.
+    pub url: String,
+    pub sections: Vec<ExplanationSection>,
+    /// The links in the explanation, in the order they first appear in.
+    pub links: Vec<ExplanationLink>,
+}
+
+/// What [`Explanation`] contributes to JSON diagnostics with `--json=explanation-summary`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ExplanationSummary {
+    pub summary: String,
+    pub url: String,
+    pub links: Vec<ExplanationLink>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum ExplanationSection {
+    /// Prose, as markdown.
+    Description { text: String },
+    /// Code that produces the error.
+    ErroneousExample { code: String },
+    /// Code that compiles, usually a fixed version of an erroneous example.
+    FixedExample { code: String },
+    /// A code block that isn't Rust code, such as a `Cargo.toml` or a shell session.
+    Code { language: String, code: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ExplanationLink {
+    pub text: String,
+    pub url: String,
+}
+
+impl Explanation {
+    /// Parses the explanation of `code`, as found in the registry.
+    pub fn parse(code: ErrCode, description: &str) -> Explanation {
+        let text = remove_hidden_lines(description);
+        let stream = MdStream::parse_str(&text);
+
+        let mut sections = vec![];
+        let mut prose = String::new();
+        let mut links = vec![];
+        for tree in stream.trees() {
+            collect_links(tree, &mut links);
+            let MdTree::CodeBlock { txt, lang } = tree else {
+                write_markdown(tree, &mut prose);
+                continue;
+            };
+            let prose = std::mem::take(&mut prose);
+            if !prose.trim().is_empty() {
+                sections.push(ExplanationSection::Description { text: prose.trim().to_string() });
+            }
+            sections.push(code_section(code, txt, lang.unwrap_or("")));
+        }
+        if !prose.trim().is_empty() {
+            sections.push(ExplanationSection::Description { text: prose.trim().to_string() });
+        }
+
+        // Skip headings such as "#### Note: this error code is no longer emitted by the compiler."
+        let summary = match sections.first() {
+            Some(ExplanationSection::Description { text }) => text
+                .split("\n\n")
+                .find(|paragraph| !paragraph.starts_with('#'))
+                .unwrap_or_default()
+                .to_string(),
+            _ => String::new(),
+        };
+        Explanation {
+            code: code.to_string(),
+            summary,
+            url: format!("https://doc.rust-lang.org/error_codes/{code}.html"),
+            sections,
+            links,
+        }
+    }
+
+    pub fn into_summary(self) -> ExplanationSummary {
+        let Explanation { summary, url, links, .. } = self;
+        ExplanationSummary { summary, url, links }
+    }
+}
+
+/// Removes the lines of Rust code blocks that rustdoc hides (`# line`), like
+/// `rustc --explain` does, but keeps the language of code blocks.
+fn remove_hidden_lines(description: &str) -> String {
+    let mut is_in_code_block = false;
+    let mut text = String::new();
+    for line in description.lines() {
+        let dedented_line = line.trim_start();
+        if dedented_line.starts_with("```") {
+            is_in_code_block = !is_in_code_block;
+        } else if is_in_code_block && (dedented_line.starts_with("# ") || dedented_line == "#") {
+            continue;
+        }
+        text.push_str(line);
+        text.push('\n');
+    }
+    text
+}
+
+fn code_section(code: ErrCode, txt: &str, lang: &str) -> ExplanationSection {
+    let code_str = code.to_string();
+    let tags: Vec<_> = lang.split(',').map(str::trim).filter(|tag| !tag.is_empty()).collect();
+    let is_rust = tags.iter().all(|tag| {
+        matches!(*tag, "rust" | "compile_fail" | "should_panic" | "no_run" | "ignore")
+            || tag.starts_with("edition")
+            || tag.starts_with("ignore-")
+            || (tag.starts_with('E') && tag[1..].bytes().all(|b| b.is_ascii_digit()))
+    });
+    let code = txt.to_string();
+    if !is_rust {
+        ExplanationSection::Code { language: lang.to_string(), code }
+    } else if tags.iter().any(|tag| *tag == "compile_fail" || *tag == code_str) {
+        ExplanationSection::ErroneousExample { code }
+    } else {
+        ExplanationSection::FixedExample { code }
+    }
+}
+
+fn collect_links(tree: &MdTree<'_>, links: &mut Vec<ExplanationLink>) {
+    match tree {
+        MdTree::Link { disp, link } if !link.is_empty() => {
+            if !links.iter().any(|known| known.url == *link) {
+                links.push(ExplanationLink { text: disp.to_string(), url: link.to_string() });
+            }
+        }
+        MdTree::Heading(_, stream)
+        | MdTree::OrderedListItem(_, stream)
+        | MdTree::UnorderedListItem(stream) => {
+            stream.trees().iter().for_each(|tree| collect_links(tree, links));
+        }
+        _ => {}
+    }
+}
+
+/// Writes a parsed tree back out as markdown.
+fn write_markdown(tree: &MdTree<'_>, out: &mut String) {
+    let write_stream = |stream: &MdStream<'_>, out: &mut String| {
+        stream.trees().iter().for_each(|tree| write_markdown(tree, out));
+    };
+    match tree {
+        MdTree::Comment(_) | MdTree::LinkDef { .. } => {}
+        MdTree::CodeBlock { txt, lang } => {
+            out.push_str(&format!("```{}\n{txt}\n```", lang.unwrap_or("")));
+        }
+        MdTree::CodeInline(txt) => out.push_str(&format!("`{txt}`")),
+        MdTree::Strong(txt) => out.push_str(&format!("**{txt}**")),
+        MdTree::Emphasis(txt) => out.push_str(&format!("*{txt}*")),
+        MdTree::Strikethrough(txt) => out.push_str(&format!("~~{txt}~~")),
+        MdTree::PlainText(txt) => out.push_str(txt),
+        MdTree::Link { disp, link } if link.is_empty() => out.push_str(disp),
+        MdTree::Link { disp, link } => out.push_str(&format!("[{disp}]({link})")),
+        MdTree::RefLink { disp, .. } => out.push_str(disp),
+        MdTree::ParagraphBreak => out.push_str("\n\n"),
+        MdTree::LineBreak => out.push('\n'),
+        MdTree::HorizontalRule => out.push_str("---"),
+        MdTree::Heading(level, stream) => {
+            out.push_str(&format!("{} ", "#".repeat(usize::from(*level))));
+            write_stream(stream, out);
+            out.push_str("\n\n");
+        }
+        MdTree::OrderedListItem(n, stream) => {
+            out.push_str(&format!("{n}. "));
+            write_stream(stream, out);
+        }
+        MdTree::UnorderedListItem(stream) => {
+            out.push_str("- ");
+            write_stream(stream, out);
+        }
+    }
+}
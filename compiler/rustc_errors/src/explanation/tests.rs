@@ -0,0 +1,83 @@
+use super::*;
+use crate::E0384;
+
+const DESCRIPTION: &str = "An immutable variable was reassigned.
+
+Erroneous code example:
+
+```compile_fail,E0384
+fn main() {
+    let x = 3;
+    x = 5; // error, reassignment of immutable variable
+}
+```
+
+By default, variables in Rust are immutable. To fix this error, add the keyword
+`mut` after the keyword `let` when declaring the variable. For example:
+
+```
+# #![allow(unused_assignments)]
+fn main() {
+    let mut x = 3;
+    x = 5;
+}
+```
+
+Alternatively, you might consider [shadowing] the variable:
+
+[shadowing]: https://doc.rust-lang.org/book/ch03-01-variables-and-mutability.html#shadowing
+
+```text
+$ cargo build
+```
+";
+
+#[test]
+fn sections() {
+    let explanation = Explanation::parse(E0384, DESCRIPTION);
+    assert_eq!(explanation.code, "E0384");
+    assert_eq!(explanation.summary, "An immutable variable was reassigned.");
+    assert_eq!(explanation.url, "https://doc.rust-lang.org/error_codes/E0384.html");
+    assert_eq!(
+        explanation.sections,
+        [
+            ExplanationSection::Description {
+                text: "An immutable variable was reassigned.\n\nErroneous code example:"
+                    .to_string(),
+            },
+            ExplanationSection::ErroneousExample {
+                code:
+                    "fn main() {\n    let x = 3;\n    x = 5; // error, reassignment of immutable \
+                   variable\n}"
+                        .to_string(),
+            },
+            ExplanationSection::Description {
+                text: "By default, variables in Rust are immutable. To fix this error, add the \
+                   keyword `mut` after the keyword `let` when declaring the variable. For \
+                   example:"
+                    .to_string(),
+            },
+            ExplanationSection::FixedExample {
+                code: "fn main() {\n    let mut x = 3;\n    x = 5;\n}".to_string(),
+            },
+            ExplanationSection::Description {
+                text:
+                    "Alternatively, you might consider [shadowing](https://doc.rust-lang.org/book/\
+                   ch03-01-variables-and-mutability.html#shadowing) the variable:"
+                        .to_string(),
+            },
+            ExplanationSection::Code {
+                language: "text".to_string(),
+                code: "$ cargo build".to_string()
+            },
+        ]
+    );
+    assert_eq!(
+        explanation.links,
+        [ExplanationLink {
+            text: "shadowing".to_string(),
+            url: "https://doc.rust-lang.org/book/ch03-01-variables-and-mutability.html#shadowing"
+                .to_string(),
+        }]
+    );
+}
@@ -25,11 +25,13 @@
 use serde::Serialize;
 use termcolor::{ColorSpec, WriteColor};
 
+use crate::annotate_snippet_emitter_writer::AnnotateSnippetEmitter;
 use crate::diagnostic::IsLint;
 use crate::emitter::{
     should_show_source_code, ColorConfig, Destination, Emitter, HumanEmitter,
     HumanReadableErrorType,
 };
+use crate::explanation::{Explanation, ExplanationSummary};
 use crate::registry::Registry;
 use crate::translation::{to_fluent_args, Translate};
 use crate::{
@@ -58,8 +60,10 @@ pub struct JsonEmitter {
     color_config: ColorConfig,
     diagnostic_width: Option<usize>,
     macro_backtrace: bool,
+    macro_expansion_trace: bool,
     track_diagnostics: bool,
     terminal_url: TerminalUrl,
+    explanation_summary: bool,
 }
 
 impl JsonEmitter {
@@ -84,8 +88,10 @@ pub fn new(
             color_config,
             diagnostic_width: None,
             macro_backtrace: false,
+            macro_expansion_trace: false,
             track_diagnostics: false,
             terminal_url: TerminalUrl::No,
+            explanation_summary: false,
         }
     }
 
@@ -254,6 +260,9 @@ struct DiagnosticCode {
     code: String,
     /// An explanation for the code.
     explanation: Option<&'static str>,
+    /// A summary of the explanation, with `--json=explanation-summary`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explanation_summary: Option<ExplanationSummary>,
 }
 
 #[derive(Serialize)]
@@ -336,12 +345,17 @@ fn reset(&mut self) -> io::Result<()> {
         let translated_message = je.translate_messages(&diag.messages, &args);
 
         let code = if let Some(code) = diag.code {
+            let explanation = je.registry.as_ref().unwrap().try_find_description(code).ok();
+            let explanation_summary = explanation
+                .filter(|_| je.explanation_summary)
+                .map(|description| Explanation::parse(code, description).into_summary());
+            Some(DiagnosticCode { code: code.to_string(), explanation, explanation_summary })
+        } else if let Some(IsLint { name, .. }) = &diag.is_lint {
             Some(DiagnosticCode {
-                code: code.to_string(),
-                explanation: je.registry.as_ref().unwrap().try_find_description(code).ok(),
+                code: name.to_string(),
+                explanation: None,
+                explanation_summary: None,
             })
-        } else if let Some(IsLint { name, .. }) = &diag.is_lint {
-            Some(DiagnosticCode { code: name.to_string(), explanation: None })
         } else {
             None
         };
@@ -361,17 +375,36 @@ fn reset(&mut self) -> io::Result<()> {
             ColorConfig::Always | ColorConfig::Auto => dst = Box::new(termcolor::Ansi::new(dst)),
             ColorConfig::Never => {}
         }
-        HumanEmitter::new(dst, je.fallback_bundle.clone())
-            .short_message(short)
-            .sm(Some(je.sm.clone()))
-            .fluent_bundle(je.fluent_bundle.clone())
-            .diagnostic_width(je.diagnostic_width)
-            .macro_backtrace(je.macro_backtrace)
-            .track_diagnostics(je.track_diagnostics)
-            .terminal_url(je.terminal_url)
-            .ui_testing(je.ui_testing)
-            .ignored_directories_in_source_blocks(je.ignored_directories_in_source_blocks.clone())
-            .emit_diagnostic(diag);
+        if let HumanReadableErrorType::AnnotateSnippet = je.json_rendered {
+            AnnotateSnippetEmitter::new(dst, je.fallback_bundle.clone())
+                .sm(Some(je.sm.clone()))
+                .fluent_bundle(je.fluent_bundle.clone())
+                .diagnostic_width(je.diagnostic_width)
+                .macro_backtrace(je.macro_backtrace)
+                .macro_expansion_trace(je.macro_expansion_trace)
+                .track_diagnostics(je.track_diagnostics)
+                .terminal_url(je.terminal_url)
+                .ui_testing(je.ui_testing)
+                .ignored_directories_in_source_blocks(
+                    je.ignored_directories_in_source_blocks.clone(),
+                )
+                .emit_diagnostic(diag);
+        } else {
+            HumanEmitter::new(dst, je.fallback_bundle.clone())
+                .short_message(short)
+                .sm(Some(je.sm.clone()))
+                .fluent_bundle(je.fluent_bundle.clone())
+                .diagnostic_width(je.diagnostic_width)
+                .macro_backtrace(je.macro_backtrace)
+                .macro_expansion_trace(je.macro_expansion_trace)
+                .track_diagnostics(je.track_diagnostics)
+                .terminal_url(je.terminal_url)
+                .ui_testing(je.ui_testing)
+                .ignored_directories_in_source_blocks(
+                    je.ignored_directories_in_source_blocks.clone(),
+                )
+                .emit_diagnostic(diag);
+        }
         let buf = Arc::try_unwrap(buf.0).unwrap().into_inner().unwrap();
         let buf = String::from_utf8(buf).unwrap();
 
@@ -60,8 +60,8 @@
 use rustc_data_structures::sync::{Lock, Lrc};
 use rustc_data_structures::AtomicRef;
 pub use rustc_error_messages::{
-    fallback_fluent_bundle, fluent_bundle, DiagMessage, FluentBundle, LanguageIdentifier,
-    LazyFallbackBundle, MultiSpan, SpanLabel, SubdiagMessage,
+    fallback_fluent_bundle, fluent_bundle, untranslated_messages, DiagMessage, FluentBundle,
+    LanguageIdentifier, LazyFallbackBundle, MultiSpan, SpanLabel, SubdiagMessage,
 };
 use rustc_lint_defs::LintExpectationId;
 pub use rustc_lint_defs::{pluralize, Applicability};
@@ -78,20 +78,24 @@
 use Level::*;
 
 pub mod annotate_snippet_emitter_writer;
+pub mod apply_suggestions;
 pub mod codes;
 mod diagnostic;
 mod diagnostic_impls;
 pub mod emitter;
 pub mod error;
+pub mod explanation;
 pub mod json;
 mod lock;
 pub mod markdown;
 pub mod registry;
+pub mod sarif;
 mod snippet;
 mod styled_buffer;
 #[cfg(test)]
 mod tests;
 pub mod translation;
+pub mod translation_report;
 
 pub type PErr<'a> = Diag<'a>;
 pub type PResult<'a, T> = Result<T, PErr<'a>>;
@@ -19,6 +19,11 @@ pub fn parse_str(s: &str) -> MdStream<'_> {
         parse::entrypoint(s)
     }
 
+    /// The trees of the document, in order
+    pub fn trees(&self) -> &[MdTree<'a>] {
+        &self.0
+    }
+
     /// Write formatted output to a termcolor buffer
     pub fn write_termcolor_buf(&self, buf: &mut Buffer) -> io::Result<()> {
         term::entrypoint(self, buf)
@@ -0,0 +1,493 @@
+//! A SARIF emitter for errors.
+//!
+//! Unlike the JSON emitter, which prints each diagnostic as soon as it is
+//! emitted, this collects diagnostics and writes them as a single [SARIF 2.1.0]
+//! log when the emitter is dropped, since a SARIF log is one JSON document.
+//!
+//! Every diagnostic becomes a result. Its error code or lint name is the rule
+//! it violates, its primary spans are its locations, and its secondary spans
+//! and sub-diagnostics are related locations. Suggestions become fixes.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use std::error::Report;
+use std::io::{self, Write};
+use std::path::Path;
+use std::{mem, thread};
+
+use derive_setters::Setters;
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::{IntoDynSyncSend, Lrc};
+use rustc_error_messages::FluentArgs;
+use rustc_lint_defs::Applicability;
+use rustc_span::source_map::SourceMap;
+use rustc_span::{FileName, Span};
+use serde::Serialize;
+
+use crate::diagnostic::IsLint;
+use crate::emitter::Emitter;
+use crate::registry::Registry;
+use crate::translation::{to_fluent_args, Translate};
+use crate::{CodeSuggestion, DiagInner, DiagMessage, FluentBundle, LazyFallbackBundle, Level};
+
+#[cfg(test)]
+mod tests;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+#[derive(Setters)]
+pub struct SarifEmitter {
+    #[setters(skip)]
+    dst: IntoDynSyncSend<Box<dyn Write + Send>>,
+    registry: Option<Registry>,
+    #[setters(skip)]
+    sm: Lrc<SourceMap>,
+    fluent_bundle: Option<Lrc<FluentBundle>>,
+    #[setters(skip)]
+    fallback_bundle: LazyFallbackBundle,
+    /// Whether to write a log without results, which says that there was
+    /// nothing to report.
+    write_empty_log: bool,
+    /// The rules referenced by `results`, by ID, in the order results refer
+    /// to them with `ruleIndex`.
+    #[setters(skip)]
+    rules: FxIndexMap<String, Rule>,
+    #[setters(skip)]
+    results: Vec<SarifResult>,
+}
+
+impl SarifEmitter {
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        sm: Lrc<SourceMap>,
+        fallback_bundle: LazyFallbackBundle,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst: IntoDynSyncSend(dst),
+            registry: None,
+            sm,
+            fluent_bundle: None,
+            fallback_bundle,
+            write_empty_log: true,
+            rules: FxIndexMap::default(),
+            results: Vec::new(),
+        }
+    }
+
+    fn write_log(&mut self) -> io::Result<()> {
+        if self.results.is_empty() && !self.write_empty_log {
+            return Ok(());
+        }
+        let log = Log {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![Run {
+                tool: Tool {
+                    driver: ToolComponent {
+                        name: "rustc",
+                        information_uri: "https://www.rust-lang.org/",
+                        rules: self.rules.values().collect(),
+                    },
+                },
+                column_kind: "unicodeCodePoints",
+                results: mem::take(&mut self.results),
+            }],
+        };
+        serde_json::to_writer_pretty(&mut *self.dst, &log)?;
+        self.dst.write_all(b"\n")?;
+        self.dst.flush()
+    }
+
+    fn result_from_diagnostic(&mut self, diag: DiagInner) -> SarifResult {
+        let args = to_fluent_args(diag.args.iter());
+        let (rule_id, rule_index) = self.rule_for_diagnostic(&diag).unzip();
+        let mut message = self.translate_messages(&diag.messages, &args).into_owned();
+
+        let mut locations = vec![];
+        let mut related_locations = vec![];
+        for span_label in diag.span.span_labels() {
+            let label = self.translate_label(&span_label.label, &args);
+            if span_label.is_primary {
+                locations.extend(self.location(span_label.span, label));
+            } else {
+                related_locations.extend(self.location(span_label.span, label));
+            }
+        }
+
+        for child in &diag.children {
+            let child_message = format!(
+                "{}: {}",
+                child.level.to_str(),
+                self.translate_messages(&child.messages, &args)
+            );
+            // Notes and helps that don't point at any code can only be kept as
+            // part of the result's message.
+            if child.span.primary_spans().is_empty() {
+                message.push('\n');
+                message.push_str(&child_message);
+                continue;
+            }
+            for span_label in child.span.span_labels() {
+                let label = if span_label.is_primary {
+                    Some(child_message.clone())
+                } else {
+                    self.translate_label(&span_label.label, &args)
+                };
+                related_locations.extend(self.location(span_label.span, label));
+            }
+        }
+
+        let fixes = diag
+            .suggestions
+            .iter()
+            .flatten()
+            .flat_map(|sugg| self.fixes_from_suggestion(sugg, &args))
+            .collect();
+
+        SarifResult {
+            rule_id,
+            rule_index,
+            level: sarif_level(diag.level),
+            message: Message { text: message },
+            locations,
+            related_locations,
+            fixes,
+        }
+    }
+
+    /// Returns the ID and index of the rule `diag` reports a violation of,
+    /// which is its error code or lint name, adding the rule if it is new.
+    fn rule_for_diagnostic(&mut self, diag: &DiagInner) -> Option<(String, usize)> {
+        let rule = if let Some(code) = diag.code {
+            Rule {
+                id: code.to_string(),
+                full_description: self
+                    .registry
+                    .as_ref()
+                    .and_then(|registry| registry.try_find_description(code).ok())
+                    .map(|explanation| Message { text: explanation.to_owned() }),
+                help_uri: Some(format!("https://doc.rust-lang.org/error_codes/{code}.html")),
+            }
+        } else if let Some(IsLint { name, .. }) = &diag.is_lint {
+            Rule { id: name.clone(), full_description: None, help_uri: None }
+        } else {
+            return None;
+        };
+        let id = rule.id.clone();
+        let entry = self.rules.entry(id.clone());
+        let index = entry.index();
+        entry.or_insert(rule);
+        Some((id, index))
+    }
+
+    fn translate_label(
+        &self,
+        label: &Option<DiagMessage>,
+        args: &FluentArgs<'_>,
+    ) -> Option<String> {
+        label.as_ref().map(|label| {
+            self.translate_message(label, args).map_err(Report::new).unwrap().into_owned()
+        })
+    }
+
+    fn location(&self, span: Span, message: Option<String>) -> Option<Location> {
+        let (artifact_location, region) = self.artifact_location_and_region(span)?;
+        Some(Location {
+            physical_location: PhysicalLocation { artifact_location, region },
+            message: message.map(|text| Message { text }),
+        })
+    }
+
+    /// Returns where `span` is, unless it doesn't point into a file on disk,
+    /// for example when it comes from a proc macro.
+    fn artifact_location_and_region(&self, span: Span) -> Option<(ArtifactLocation, Region)> {
+        if span.is_dummy() {
+            return None;
+        }
+        let start = self.sm.lookup_char_pos(span.lo());
+        if !matches!(start.file.name, FileName::Real(_)) {
+            return None;
+        }
+        let end = self.sm.lookup_char_pos(span.hi());
+        let byte_start = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        let path = self.sm.filename_for_diagnostics(&start.file.name).to_string();
+        Some((
+            ArtifactLocation { uri: path_to_uri(&path) },
+            Region {
+                start_line: start.line,
+                start_column: start.col.0 + 1,
+                end_line: end.line,
+                end_column: end.col.0 + 1,
+                byte_offset: byte_start,
+                byte_length: byte_end - byte_start,
+            },
+        ))
+    }
+
+    /// Converts each alternative of `sugg` into a fix.
+    fn fixes_from_suggestion(&self, sugg: &CodeSuggestion, args: &FluentArgs<'_>) -> Vec<Fix> {
+        let description = self.translate_message(&sugg.msg, args).map_err(Report::new).unwrap();
+        sugg.substitutions
+            .iter()
+            .filter_map(|substitution| {
+                let mut changes = FxIndexMap::<String, ArtifactChange>::default();
+                for part in &substitution.parts {
+                    // A fix that can only be partially applied is worse than none.
+                    let (artifact_location, deleted_region) =
+                        self.artifact_location_and_region(part.span)?;
+                    changes
+                        .entry(artifact_location.uri.clone())
+                        .or_insert_with(|| ArtifactChange {
+                            artifact_location,
+                            replacements: vec![],
+                        })
+                        .replacements
+                        .push(Replacement {
+                            deleted_region,
+                            inserted_content: ArtifactContent { text: part.snippet.clone() },
+                        });
+                }
+                Some(Fix {
+                    description: Message { text: description.to_string() },
+                    artifact_changes: changes.into_values().collect(),
+                    properties: FixProperties { applicability: sugg.applicability },
+                })
+            })
+            .collect()
+    }
+}
+
+impl Translate for SarifEmitter {
+    fn fluent_bundle(&self) -> Option<&Lrc<FluentBundle>> {
+        self.fluent_bundle.as_ref()
+    }
+
+    fn fallback_fluent_bundle(&self) -> &FluentBundle {
+        &self.fallback_bundle
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: DiagInner) {
+        // Failure notes such as "aborting due to 2 previous errors" are about the
+        // compilation session rather than the code.
+        if let Level::FailureNote = diag.level {
+            return;
+        }
+        let result = self.result_from_diagnostic(diag);
+        self.results.push(result);
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        // Explanations are attached to the rules instead.
+        false
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        let result = self.write_log();
+        // Panicking again while unwinding would abort before the first panic
+        // is reported.
+        if let Err(e) = result
+            && !thread::panicking()
+        {
+            panic!("failed to print diagnostics: {e:?}");
+        }
+    }
+}
+
+fn sarif_level(level: Level) -> &'static str {
+    match level {
+        Level::Bug | Level::Fatal | Level::Error | Level::DelayedBug => "error",
+        Level::ForceWarning(_) | Level::Warning => "warning",
+        Level::Note | Level::OnceNote | Level::Help | Level::OnceHelp | Level::FailureNote => {
+            "note"
+        }
+        Level::Allow | Level::Expect(_) => "none",
+    }
+}
+
+/// Converts a path as rustc prints it into the URI reference SARIF expects:
+/// relative paths stay relative and absolute paths become `file` URIs.
+fn path_to_uri(path: &str) -> String {
+    let mut uri = String::new();
+    if Path::new(path).is_absolute() {
+        uri.push_str("file://");
+        // Windows paths start with a drive letter rather than a slash.
+        if !path.starts_with(['/', '\\']) {
+            uri.push('/');
+        }
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'\\' if cfg!(windows) => uri.push('/'),
+            b'a'..=b'z'
+            | b'A'..=b'Z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b'/'
+            | b':'
+            | b'@'
+            | b'!'
+            | b'$'
+            | b'&'
+            | b'\''
+            | b'('
+            | b')'
+            | b'*'
+            | b'+'
+            | b','
+            | b';'
+            | b'=' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
+// The following data types are provided just for serialisation, and only
+// cover the parts of the SARIF object model rustc fills in.
+
+#[derive(Serialize)]
+struct Log<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool<'a>,
+    /// How `startColumn` and `endColumn` count, which for rustc is in chars.
+    column_kind: &'static str,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool<'a> {
+    driver: ToolComponent<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent<'a> {
+    name: &'static str,
+    information_uri: &'static str,
+    rules: Vec<&'a Rule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    /// The error code (e.g. "E1234") or lint name.
+    id: String,
+    /// The explanation of the error code, in Markdown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_description: Option<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    /// "error", "warning", "note" or "none".
+    level: &'static str,
+    /// The primary message, followed by the sub-diagnostics without a span.
+    message: Message,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+    /// The span's label, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    /// 1-based.
+    start_line: usize,
+    /// 1-based, character offset.
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+    properties: FixProperties,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: ArtifactContent,
+}
+
+#[derive(Serialize)]
+struct ArtifactContent {
+    text: String,
+}
+
+/// SARIF has no notion of how reliable a fix is, so rustc's applicability is
+/// kept as a custom property.
+#[derive(Serialize)]
+struct FixProperties {
+    applicability: Applicability,
+}
@@ -0,0 +1,167 @@
+use std::str;
+use std::sync::{Arc, Mutex};
+
+use rustc_span::source_map::FilePathMapping;
+use rustc_span::BytePos;
+use serde_json::{json, Value};
+
+use super::*;
+use crate::{DiagCtxt, DiagCtxtHandle, E0308};
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+/// Runs `emit` against a `SarifEmitter` for a file `test.rs` containing `code`,
+/// and returns the log written once the emitter is dropped.
+fn sarif_log(code: &str, emit: impl FnOnce(DiagCtxtHandle<'_>)) -> Value {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("test.rs").to_owned().into(), code.to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let emitter =
+            SarifEmitter::new(Box::new(Shared { data: output.clone() }), sm, fallback_bundle);
+        let dcx = DiagCtxt::new(Box::new(emitter));
+        emit(dcx.handle());
+        drop(dcx);
+
+        let bytes = output.lock().unwrap();
+        serde_json::from_str(str::from_utf8(&bytes).unwrap()).unwrap()
+    })
+}
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span::with_root_ctxt(BytePos(lo), BytePos(hi))
+}
+
+#[test]
+fn empty_log() {
+    let log = sarif_log("", |_| {});
+    assert_eq!(log["version"], "2.1.0");
+    assert_eq!(log["runs"][0]["tool"]["driver"]["name"], "rustc");
+    assert_eq!(log["runs"][0]["tool"]["driver"]["rules"], json!([]));
+    assert_eq!(log["runs"][0]["results"], json!([]));
+}
+
+#[test]
+fn error_with_code_and_label() {
+    let log = sarif_log("fn main() {\n    let x: u8 = \"é\";\n}\n", |dcx| {
+        dcx.struct_span_err(span(28, 32), "mismatched types")
+            .with_code(E0308)
+            .with_span_label(span(28, 32), "expected `u8`, found `&str`")
+            .with_span_label(span(23, 25), "expected due to this")
+            .emit();
+    });
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "E0308");
+    assert_eq!(
+        run["tool"]["driver"]["rules"][0]["helpUri"],
+        "https://doc.rust-lang.org/error_codes/E0308.html"
+    );
+
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "E0308");
+    assert_eq!(result["ruleIndex"], 0);
+    assert_eq!(result["level"], "error");
+    assert_eq!(result["message"]["text"], "mismatched types");
+    assert_eq!(
+        result["locations"],
+        json!([{
+            "physicalLocation": {
+                "artifactLocation": { "uri": "test.rs" },
+                "region": {
+                    "startLine": 2,
+                    "startColumn": 17,
+                    "endLine": 2,
+                    "endColumn": 20,
+                    "byteOffset": 28,
+                    "byteLength": 4,
+                },
+            },
+            "message": { "text": "expected `u8`, found `&str`" },
+        }])
+    );
+    assert_eq!(result["relatedLocations"][0]["message"]["text"], "expected due to this");
+    assert_eq!(result["relatedLocations"][0]["physicalLocation"]["region"]["startColumn"], 12);
+}
+
+#[test]
+fn children_and_suggestions() {
+    let log = sarif_log("fn main() {\n    let X = 1;\n}\n", |dcx| {
+        dcx.struct_span_warn(span(20, 21), "variable `X` should have a snake case name")
+            .with_note("`#[warn(non_snake_case)]` on by default")
+            .with_span_note(span(0, 9), "in this function")
+            .with_span_suggestion(
+                span(20, 21),
+                "convert the identifier to snake case",
+                "x",
+                Applicability::MaybeIncorrect,
+            )
+            .emit();
+    });
+    let result = &log["runs"][0]["results"][0];
+    assert_eq!(result["level"], "warning");
+    assert_eq!(result.get("ruleId"), None);
+    assert_eq!(
+        result["message"]["text"],
+        "variable `X` should have a snake case name\n\
+         note: `#[warn(non_snake_case)]` on by default"
+    );
+    assert_eq!(result["relatedLocations"][0]["message"]["text"], "note: in this function");
+    assert_eq!(
+        result["fixes"],
+        json!([{
+            "description": { "text": "convert the identifier to snake case" },
+            "artifactChanges": [{
+                "artifactLocation": { "uri": "test.rs" },
+                "replacements": [{
+                    "deletedRegion": {
+                        "startLine": 2,
+                        "startColumn": 9,
+                        "endLine": 2,
+                        "endColumn": 10,
+                        "byteOffset": 20,
+                        "byteLength": 1,
+                    },
+                    "insertedContent": { "text": "x" },
+                }],
+            }],
+            "properties": { "applicability": "MaybeIncorrect" },
+        }])
+    );
+}
+
+#[test]
+fn rules_are_shared_between_results() {
+    let log = sarif_log("fn main() {}\n", |dcx| {
+        dcx.struct_span_warn(span(3, 7), "first").with_code(E0308).emit();
+        dcx.struct_span_warn(span(3, 7), "second").with_code(E0308).emit();
+    });
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 1);
+    assert_eq!(run["results"][1]["ruleIndex"], 0);
+}
+
+#[test]
+fn uris() {
+    assert_eq!(path_to_uri("src/lib.rs"), "src/lib.rs");
+    assert_eq!(path_to_uri("my crate/src/100%.rs"), "my%20crate/src/100%25.rs");
+    assert_eq!(path_to_uri("src/é.rs"), "src/%C3%A9.rs");
+    #[cfg(unix)]
+    assert_eq!(path_to_uri("/home/ferris/src/lib.rs"), "file:///home/ferris/src/lib.rs");
+    #[cfg(windows)]
+    assert_eq!(path_to_uri(r"C:\src\lib.rs"), "file:///C:/src/lib.rs");
+}
@@ -5,7 +5,7 @@
 use crate::error::{TranslateError, TranslateErrorKind};
 use crate::fluent_bundle::*;
 use crate::translation::Translate;
-use crate::FluentBundle;
+use crate::{untranslated_messages, FluentBundle};
 
 struct Dummy {
     bundle: FluentBundle,
@@ -194,3 +194,58 @@ fn misformed_fluent() {
         );
     }
 }
+
+#[test]
+fn untranslated_fluent() {
+    let resource = "-brand = Rust
+
+# Comments are left out.
+foo_translated = translated
+    .label = label
+
+foo_partial = partially translated
+    .label = label
+    .note = note
+
+foo_missing = written in { -brand }
+
+foo_select = calls in {$kind ->
+    [const_fn] constant function
+    *[other] {\"\"}
+}
+    .label = label
+
+# The select above closes at the start of a line.
+foo_after_select = after
+";
+    let dummy = make_dummy(
+        "foo_translated = traduit
+    .label = étiquette
+foo_partial = partiellement traduit
+    .label = étiquette",
+    );
+
+    assert_eq!(
+        untranslated_messages(resource, Some(&dummy.bundle)),
+        [
+            "-brand = Rust",
+            "foo_partial = partially translated\n    .label = label\n    .note = note",
+            "foo_missing = written in { -brand }",
+            "foo_select = calls in {$kind ->\n    [const_fn] constant function\n    \
+             *[other] {\"\"}\n}\n    .label = label",
+            "foo_after_select = after",
+        ]
+    );
+    assert_eq!(
+        untranslated_messages(resource, None),
+        [
+            "-brand = Rust",
+            "foo_translated = translated\n    .label = label",
+            "foo_partial = partially translated\n    .label = label\n    .note = note",
+            "foo_missing = written in { -brand }",
+            "foo_select = calls in {$kind ->\n    [const_fn] constant function\n    \
+             *[other] {\"\"}\n}\n    .label = label",
+            "foo_after_select = after",
+        ]
+    );
+}
@@ -0,0 +1,167 @@
+//! Support for `-Z translate-report`, which records the diagnostic messages that
+//! couldn't be shown in the language requested with `-Z translate-lang`.
+//!
+//! [`TranslationReporter`] looks at the messages of each diagnostic before
+//! passing it on to the emitter it wraps. Messages written as plain strings in
+//! the compiler can't be translated at all, and Fluent messages missing from
+//! the bundle of the requested locale fall back to English. Once compilation is
+//! done, the reporter writes each of these messages out once, with where in the
+//! compiler the first diagnostic using it was created, so that translators and
+//! compiler developers know what is left to do. The session writes the
+//! [`TranslationReport`] that the reporter shares with it.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::{fs, io};
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::Lrc;
+use rustc_span::source_map::SourceMap;
+use serde::Serialize;
+
+use crate::emitter::{DynEmitter, Emitter};
+use crate::translation::Translate;
+use crate::{DiagInner, DiagMessage, FluentBundle};
+
+/// Why a message was shown in English.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum UntranslatedKind {
+    /// The message is a plain string in the compiler, not a Fluent message.
+    NotTranslatable,
+    /// The Fluent message isn't in the bundle of the requested locale.
+    MissingTranslation,
+}
+
+#[derive(Serialize)]
+struct UntranslatedMessage<'a> {
+    kind: UntranslatedKind,
+    /// The text of plain strings, or the Fluent identifier (`id` or
+    /// `id.attribute`) of Fluent messages.
+    message: &'a str,
+    /// The number of times the message was emitted.
+    count: usize,
+    /// Where in the compiler the first diagnostic with this message was created.
+    created_at: &'a str,
+}
+
+/// The untranslated messages recorded by a [`TranslationReporter`].
+#[derive(Default)]
+pub struct TranslationReport {
+    /// The count and creation site of each untranslated message, in the order
+    /// they were first emitted in.
+    untranslated: FxIndexMap<(UntranslatedKind, String), (usize, String)>,
+}
+
+impl TranslationReport {
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let untranslated: Vec<_> = self
+            .untranslated
+            .iter()
+            .map(|((kind, message), (count, created_at))| UntranslatedMessage {
+                kind: *kind,
+                message,
+                count: *count,
+                created_at,
+            })
+            .collect();
+        let mut report = serde_json::to_string_pretty(&untranslated)?;
+        report.push('\n');
+        fs::write(path, report)
+    }
+}
+
+/// Wraps the emitter of a compilation with `-Z translate-report`, see the
+/// module documentation.
+pub struct TranslationReporter {
+    inner: Box<DynEmitter>,
+    report: Arc<Mutex<TranslationReport>>,
+}
+
+impl TranslationReporter {
+    pub fn new(inner: Box<DynEmitter>, report: Arc<Mutex<TranslationReport>>) -> Self {
+        TranslationReporter { inner, report }
+    }
+
+    fn record(&mut self, diag: &DiagInner) {
+        let mut report = self.report.lock().unwrap();
+        let messages = diag
+            .messages
+            .iter()
+            .chain(diag.children.iter().flat_map(|child| &child.messages))
+            .map(|(message, _)| message)
+            .chain(diag.suggestions.iter().flatten().map(|suggestion| &suggestion.msg));
+        for message in messages {
+            let key = match message {
+                DiagMessage::Str(text) => (UntranslatedKind::NotTranslatable, text.to_string()),
+                // Translated when the diagnostic was created, with the same bundle.
+                DiagMessage::Translated(_) => continue,
+                DiagMessage::FluentIdentifier(id, attr) => {
+                    if self.is_translated(id, attr.as_deref()) {
+                        continue;
+                    }
+                    let message = match attr {
+                        Some(attr) => format!("{id}.{attr}"),
+                        None => id.to_string(),
+                    };
+                    (UntranslatedKind::MissingTranslation, message)
+                }
+            };
+            let (count, _) =
+                report.untranslated.entry(key).or_insert_with(|| (0, diag.emitted_at.to_string()));
+            *count += 1;
+        }
+    }
+
+    fn is_translated(&self, id: &str, attr: Option<&str>) -> bool {
+        let Some(message) = self.inner.fluent_bundle().and_then(|bundle| bundle.get_message(id))
+        else {
+            return false;
+        };
+        match attr {
+            Some(attr) => message.get_attribute(attr).is_some(),
+            None => message.value().is_some(),
+        }
+    }
+}
+
+impl Translate for TranslationReporter {
+    fn fluent_bundle(&self) -> Option<&Lrc<FluentBundle>> {
+        self.inner.fluent_bundle()
+    }
+
+    fn fallback_fluent_bundle(&self) -> &FluentBundle {
+        self.inner.fallback_fluent_bundle()
+    }
+}
+
+impl Emitter for TranslationReporter {
+    fn emit_diagnostic(&mut self, diag: DiagInner) {
+        self.record(&diag);
+        self.inner.emit_diagnostic(diag);
+    }
+
+    fn emit_artifact_notification(&mut self, path: &Path, artifact_type: &str) {
+        self.inner.emit_artifact_notification(path, artifact_type);
+    }
+
+    fn emit_future_breakage_report(&mut self, diags: Vec<DiagInner>) {
+        self.inner.emit_future_breakage_report(diags);
+    }
+
+    fn emit_unused_externs(&mut self, lint_level: rustc_lint_defs::Level, unused_externs: &[&str]) {
+        self.inner.emit_unused_externs(lint_level, unused_externs);
+    }
+
+    fn should_show_explain(&self) -> bool {
+        self.inner.should_show_explain()
+    }
+
+    fn supports_color(&self) -> bool {
+        self.inner.supports_color()
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        self.inner.source_map()
+    }
+}
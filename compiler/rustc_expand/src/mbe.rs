@@ -6,6 +6,7 @@
 pub(crate) mod diagnostics;
 pub(crate) mod macro_rules;
 
+mod expansion_trace;
 mod macro_check;
 mod macro_parser;
 mod metavar_expr;
@@ -0,0 +1,102 @@
+//! Recording of the code produced by `macro_rules!` expansions for `-Z macro-expansion-trace`.
+//!
+//! The transcribed tokens are written out as text to a `<macro expansion>` source file, so that
+//! diagnostics can show the expanded code and highlight the tokens their spans cover.
+
+use std::ops::Range;
+
+use rustc_ast::token::{self, Delimiter};
+use rustc_ast::tokenstream::{TokenStream, TokenTree};
+use rustc_ast_pretty::pprust;
+use rustc_session::parse::ParseSess;
+use rustc_span::hygiene::{ExpansionTrace, LocalExpnId};
+use rustc_span::{BytePos, FileName, Span};
+
+pub(super) fn record_expansion_trace(psess: &ParseSess, id: LocalExpnId, tts: &TokenStream) {
+    let mut writer = ExpansionWriter::default();
+    writer.write_stream(tts);
+
+    let ExpansionWriter { text, tokens, .. } = writer;
+    let file =
+        psess.source_map().new_source_file(FileName::macro_expansion_source_code(&text), text);
+    let tokens = tokens
+        .into_iter()
+        .map(|(span, range)| {
+            let lo = file.start_pos + BytePos(range.start as u32);
+            let hi = file.start_pos + BytePos(range.end as u32);
+            (span, Span::with_root_ctxt(lo, hi))
+        })
+        .collect();
+    id.set_expansion_trace(ExpansionTrace { tokens });
+}
+
+/// Lays out a token stream as code: tokens are separated as they were in the macro definition
+/// or input, and statements and blocks go on their own lines.
+#[derive(Default)]
+struct ExpansionWriter {
+    text: String,
+    /// Each written token, with the byte range of its text.
+    tokens: Vec<(Span, Range<usize>)>,
+    depth: usize,
+    newline: bool,
+    /// The end of the previous token in the macro definition or input. The transcribed tokens
+    /// are all marked `Spacing::Alone`, so their spans tell whether they were separated.
+    prev_hi: Option<BytePos>,
+}
+
+impl ExpansionWriter {
+    fn write_stream(&mut self, tts: &TokenStream) {
+        for tt in tts.trees() {
+            match tt {
+                TokenTree::Token(token, _) => {
+                    let text = pprust::token_to_string(token);
+                    self.write_token(&text, token.span, token.uninterpolated_span());
+                    if token.kind == token::Semi {
+                        self.newline = true;
+                    }
+                }
+                TokenTree::Delimited(span, _, delim, tts) => {
+                    let is_block = *delim == Delimiter::Brace;
+                    let open = pprust::token_kind_to_string(&token::OpenDelim(*delim));
+                    self.write_token(&open, span.open, span.open);
+                    if is_block {
+                        self.depth += 1;
+                        self.newline = true;
+                    }
+                    self.write_stream(tts);
+                    if is_block {
+                        self.depth -= 1;
+                        self.newline = true;
+                    }
+                    let close = pprust::token_kind_to_string(&token::CloseDelim(*delim));
+                    self.write_token(&close, span.close, span.close);
+                    if is_block {
+                        self.newline = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes a token at `span` in the compiled code. `source` is the span that diagnostics
+    /// refer to it by, which differs for the tokens substituted for a metavariable.
+    fn write_token(&mut self, text: &str, span: Span, source: Span) {
+        // Invisible delimiters have no text.
+        if text.is_empty() {
+            return;
+        }
+        if self.newline {
+            if !self.text.is_empty() {
+                self.text.push('\n');
+            }
+            self.text.push_str(&"    ".repeat(self.depth));
+        } else if self.prev_hi.is_some_and(|hi| hi != span.lo()) {
+            self.text.push(' ');
+        }
+        let lo = self.text.len();
+        self.text.push_str(text);
+        self.tokens.push((source, lo..self.text.len()));
+        self.newline = false;
+        self.prev_hi = Some(span.hi());
+    }
+}
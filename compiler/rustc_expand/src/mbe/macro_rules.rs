@@ -28,6 +28,7 @@
 use tracing::{debug, instrument, trace, trace_span};
 
 use super::diagnostics;
+use super::expansion_trace::record_expansion_trace;
 use super::macro_parser::{NamedMatches, NamedParseResult};
 use crate::base::{
     DummyResult, ExpandResult, ExtCtxt, MacResult, MacroExpanderResult, SyntaxExtension,
@@ -240,6 +241,10 @@ fn expand_macro<'cx>(
                 trace_macros_note(&mut cx.expansions, sp, msg);
             }
 
+            if cx.sess.opts.unstable_opts.macro_expansion_trace {
+                record_expansion_trace(psess, id, &tts);
+            }
+
             let p = Parser::new(psess, tts, None);
 
             if is_local {
@@ -337,6 +337,14 @@ pub struct BuiltinAttribute {
         template!(Word, List: r#"expected = "reason""#, NameValueStr: "reason"), FutureWarnFollowing,
         EncodeCrossCrate::No,
     ),
+    gated!(
+        test_generator, Normal, template!(Word), ErrorFollowing,
+        EncodeCrossCrate::No, experimental!(test_generator)
+    ),
+    gated!(
+        test_timeout, Normal, template!(NameValueStr: "duration"), ErrorFollowing,
+        EncodeCrossCrate::No, experimental!(test_timeout)
+    ),
     // FIXME(Centril): This can be used on stable but shouldn't.
     ungated!(
         reexport_test_harness_main, CrateLevel, template!(NameValueStr: "name"), ErrorFollowing,
@@ -367,6 +367,8 @@ pub fn internal(&self, feature: Symbol) -> bool {
     (unstable, async_fn_track_caller, "1.73.0", Some(110011)),
     /// Allows `for await` loops.
     (unstable, async_for_loop, "1.77.0", Some(118898)),
+    /// Allows `#[test]` on async functions, which are run on libtest's built-in executor.
+    (unstable, async_test, "CURRENT_RUSTC_VERSION", None),
     /// Allows using C-variadics.
     (unstable, c_variadic, "1.34.0", Some(44930)),
     /// Allows the use of `#[cfg(overflow_checks)` to check if integer overflow behaviour.
@@ -601,6 +603,10 @@ pub fn internal(&self, feature: Symbol) -> bool {
     (incomplete, struct_target_features, "CURRENT_RUSTC_VERSION", Some(129107)),
     /// Allows the use of `#[target_feature]` on safe functions.
     (unstable, target_feature_11, "1.45.0", Some(69098)),
+    /// Allows a `#[test]` function marked with `#[test_generator]` to generate test cases.
+    (unstable, test_generator, "CURRENT_RUSTC_VERSION", None),
+    /// Allows setting a time limit on a test with `#[test_timeout]`.
+    (unstable, test_timeout, "CURRENT_RUSTC_VERSION", None),
     /// Allows using `#[thread_local]` on `static` items.
     (unstable, thread_local, "1.0.0", Some(29594)),
     /// Allows defining `trait X = A + B;` alias items.
@@ -8,6 +8,7 @@
 use rustc_data_structures::stable_hasher::StableHasher;
 use rustc_data_structures::sync::Lrc;
 use rustc_data_structures::{defer, jobserver};
+use rustc_errors::apply_suggestions::FixedSourcesLoader;
 use rustc_errors::registry::Registry;
 use rustc_errors::{DiagCtxtHandle, ErrorGuaranteed};
 use rustc_lint::LintStore;
@@ -384,7 +385,11 @@ pub fn run_compiler<R: Send>(config: Config, f: impl FnOnce(&Compiler) -> R + Se
 
     let sysroot = filesearch::materialize_sysroot(config.opts.maybe_sysroot.clone());
     let target = config::build_target_config(&early_dcx, &config.opts, &sysroot);
-    let file_loader = config.file_loader.unwrap_or_else(|| Box::new(RealFileLoader));
+    let mut file_loader = config.file_loader.unwrap_or_else(|| Box::new(RealFileLoader));
+    if let Some(apply_suggestions) = &config.opts.apply_suggestions {
+        let fixed = Arc::clone(&apply_suggestions.fixed);
+        file_loader = Box::new(FixedSourcesLoader { inner: file_loader, fixed });
+    }
     let path_mapping = config.opts.file_path_mapping();
     let hash_kind = config.opts.unstable_opts.src_hash_algorithm(&target);
 
@@ -428,6 +433,22 @@ pub fn run_compiler<R: Send>(config: Config, f: impl FnOnce(&Compiler) -> R + Se
             let mut locale_resources = Vec::from(config.locale_resources);
             locale_resources.push(codegen_backend.locale_resource());
 
+            if let Some(path) = &config.opts.unstable_opts.translate_template {
+                let template = locale_resources
+                    .iter()
+                    .flat_map(|resource| {
+                        rustc_errors::untranslated_messages(resource, bundle.as_deref())
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                if let Err(e) = std::fs::write(path, template + "\n") {
+                    early_dcx.early_fatal(format!(
+                        "failed to write translation template `{}`: {e}",
+                        path.display()
+                    ));
+                }
+            }
+
             let mut sess = rustc_session::build_session(
                 early_dcx,
                 config.opts,
@@ -1063,6 +1063,10 @@ pub(crate) fn start_codegen<'tcx>(
         rustc_symbol_mangling::test::report_symbol_names(tcx);
     }
 
+    if tcx.sess.opts.output_types.should_codegen() {
+        rustc_monomorphize::dump_item_costs(tcx);
+    }
+
     info!("Post-codegen\n{:?}", tcx.debug_stats());
 
     if tcx.sess.opts.output_types.contains_key(&OutputType::Mir) {
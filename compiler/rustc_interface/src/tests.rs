@@ -685,6 +685,8 @@ macro_rules! untracked {
     untracked!(assert_incr_state, Some(String::from("loaded")));
     untracked!(deduplicate_diagnostics, false);
     untracked!(dump_dep_graph, true);
+    untracked!(dump_item_costs, SwitchWithOptPath::Enabled(Some("item-costs-dir/".into())));
+    untracked!(dump_item_costs_format, DumpMonoStatsFormat::Json);
     untracked!(dump_mir, Some(String::from("abc")));
     untracked!(dump_mir_dataflow, true);
     untracked!(dump_mir_dir, String::from("abc"));
@@ -705,6 +707,7 @@ macro_rules! untracked {
     untracked!(llvm_time_trace, true);
     untracked!(ls, vec!["all".to_owned()]);
     untracked!(macro_backtrace, true);
+    untracked!(macro_expansion_trace, true);
     untracked!(meta_stats, true);
     untracked!(mir_include_spans, MirIncludeSpans::On);
     untracked!(nll_facts, true);
@@ -734,6 +737,8 @@ macro_rules! untracked {
     untracked!(time_passes_format, TimePassesFormat::Json);
     untracked!(trace_macros, true);
     untracked!(track_diagnostics, true);
+    untracked!(translate_report, Some(PathBuf::from("report.json")));
+    untracked!(translate_template, Some(PathBuf::from("template.ftl")));
     untracked!(trim_diagnostic_paths, false);
     untracked!(ui_testing, true);
     untracked!(unpretty, Some("expanded".to_string()));
@@ -1859,6 +1859,10 @@ extern "C" uint64_t LLVMRustModuleCost(LLVMModuleRef M) {
   return std::distance(std::begin(f), std::end(f));
 }
 
+extern "C" unsigned LLVMRustFunctionInstructionCount(LLVMValueRef Fn) {
+  return unwrap<Function>(Fn)->getInstructionCount();
+}
+
 extern "C" void LLVMRustModuleInstructionStats(LLVMModuleRef M,
                                                RustStringRef Str) {
   auto OS = RawRustStringOstream(Str);
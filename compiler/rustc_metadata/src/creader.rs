@@ -244,6 +244,11 @@ pub(crate) fn crate_dependencies_in_postorder(&self, cnum: CrateNum) -> Vec<Crat
         deps
     }
 
+    /// Returns the crates that the foreign crate `cnum` directly depends on.
+    pub fn crate_dependencies(&self, cnum: CrateNum) -> Vec<CrateNum> {
+        self.get_crate_data(cnum).dependencies().filter(|&dep| dep != cnum).collect()
+    }
+
     fn crate_dependencies_in_reverse_postorder(&self, cnum: CrateNum) -> Vec<CrateNum> {
         let mut deps = self.crate_dependencies_in_postorder(cnum);
         deps.reverse();
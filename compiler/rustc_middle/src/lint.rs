@@ -7,6 +7,7 @@
 use rustc_macros::HashStable;
 use rustc_session::lint::builtin::{self, FORBIDDEN_LINT_GROUPS};
 use rustc_session::lint::{FutureIncompatibilityReason, Level, Lint, LintId};
+use rustc_session::lint_baseline::LintBaselineKey;
 use rustc_session::Session;
 use rustc_span::hygiene::{ExpnKind, MacroKind};
 use rustc_span::{symbol, DesugaringKind, Span, Symbol, DUMMY_SP};
@@ -349,6 +350,18 @@ fn lint_level_impl(
             }
         }
 
+        // Occurrences of warnings known from the `--lint-baseline` are not reported again.
+        // Future breakage is always reported since it will turn into an error regardless.
+        if let Some(baseline) = &sess.lint_baseline
+            && !has_future_breakage
+            && is_baselined_level(level, src)
+            && let Some(key) = lint_baseline_key(sess, lint, &err.span)
+            && baseline.suppress(key)
+        {
+            err.cancel();
+            return;
+        }
+
         err.is_lint(lint.name_lower(), has_future_breakage);
 
         // Lint diagnostics that are covered by the expect level will not be emitted outside
@@ -416,6 +429,32 @@ fn lint_level_impl(
     lint_level_impl(sess, lint, level, src, span, Box::new(decorate))
 }
 
+/// Returns whether a lint at `level` is a warning that `--lint-baseline` applies to. This includes
+/// warnings that are made errors by `-D warnings` or `#![deny(warnings)]`, so that a baseline can
+/// be adopted by builds that deny warnings.
+fn is_baselined_level(level: Level, src: LintLevelSource) -> bool {
+    match level {
+        Level::Warn => true,
+        Level::Deny | Level::Forbid => src.name() == symbol::sym::warnings,
+        Level::Allow | Level::Expect(_) | Level::ForceWarn(_) => false,
+    }
+}
+
+/// Identifies the occurrence of `lint` at `span` in the `--lint-baseline`, by the code it points
+/// at in the user's crate rather than in macro expansions.
+fn lint_baseline_key(
+    sess: &Session,
+    lint: &'static Lint,
+    span: &MultiSpan,
+) -> Option<LintBaselineKey> {
+    let span = span.primary_span()?.source_callsite();
+    let sm = sess.source_map();
+    let text = sm.span_to_snippet(span).ok()?;
+    let file = sm.lookup_source_file(span.lo());
+    let file = sm.filename_for_diagnostics(&file.name).to_string();
+    Some(LintBaselineKey::new(lint.name_lower(), file, &text))
+}
+
 /// Returns whether `span` originates in a foreign crate's external macro.
 ///
 /// This is used to test whether a lint should not even begin to figure out whether it should
@@ -1,3 +1,6 @@
+monomorphize_couldnt_dump_item_costs =
+    unexpected error occurred while dumping item costs: {$error}
+
 monomorphize_couldnt_dump_mono_stats =
     unexpected error occurred while dumping monomorphization stats: {$error}
 
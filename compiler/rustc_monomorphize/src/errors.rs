@@ -68,6 +68,12 @@ pub(crate) struct SymbolAlreadyDefined {
     pub symbol: String,
 }
 
+#[derive(Diagnostic)]
+#[diag(monomorphize_couldnt_dump_item_costs)]
+pub(crate) struct CouldntDumpItemCosts {
+    pub error: String,
+}
+
 #[derive(Diagnostic)]
 #[diag(monomorphize_couldnt_dump_mono_stats)]
 pub(crate) struct CouldntDumpMonoStats {
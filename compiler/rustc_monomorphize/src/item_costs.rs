@@ -0,0 +1,282 @@
+//! `-Z dump-item-costs`: attributing code size and codegen time to source items.
+//!
+//! For every source item, the report sums up the cost of its mono items over all codegen
+//! units they are placed in, including the copies of inlined items. Mono items that are
+//! instantiations of generics, or that come from other crates, are attributed to the
+//! nearest items of the local crate that use them, found with the collector's usage map.
+//! Since that map doesn't outlive partitioning, the requesters are recorded in the session's
+//! `CodeStats` then, together with the codegen time and LLVM IR size that the backend
+//! records for each mono item. The report is written once codegen is done.
+//!
+//! Codegen units that are reused from the incremental cache aren't codegened again, so their
+//! copies of mono items have no codegen time and LLVM IR size. The report counts them as
+//! reused copies instead.
+
+use std::cmp;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use rustc_data_structures::fx::{FxIndexMap, FxIndexSet};
+use rustc_data_structures::graph::scc::Sccs;
+use rustc_data_structures::graph::vec_graph::VecGraph;
+use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::config::{DumpMonoStatsFormat, SwitchWithOptPath};
+
+use crate::collector::UsageMap;
+use crate::errors::CouldntDumpItemCosts;
+
+/// The most requesting items shown per row of the markdown table.
+const MAX_REQUESTERS_SHOWN: usize = 3;
+
+/// Records the items of the local crate that requested each mono item.
+///
+/// The requesters of a mono item are its users from the local crate, and the requesters of its
+/// users from other crates. To compute them only once for each mono item, even though the users
+/// may recurse, the graph of mono items and their users from other crates is condensed into its
+/// strongly connected components, which are then visited in dependency order.
+pub(crate) fn record_requesters<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &[MonoItem<'tcx>],
+    usage_map: &UsageMap<'tcx>,
+) {
+    let mut nodes: FxIndexSet<MonoItem<'tcx>> = items.iter().copied().collect();
+    let mut edges = Vec::new();
+    let mut i = 0;
+    while i < nodes.len() {
+        let item = nodes[i];
+        for &user in usage_map.get_user_items(item) {
+            if user.krate() != LOCAL_CRATE {
+                edges.push((i, nodes.insert_full(user).0));
+            }
+        }
+        i += 1;
+    }
+    let sccs: Sccs<usize, usize> = Sccs::new(&VecGraph::new(nodes.len(), edges));
+
+    let mut members: Vec<Vec<MonoItem<'tcx>>> = vec![Vec::new(); sccs.num_sccs()];
+    for (i, &item) in nodes.iter().enumerate() {
+        members[sccs.scc(i)].push(item);
+    }
+    let mut requesters: Vec<FxIndexSet<MonoItem<'tcx>>> = Vec::with_capacity(sccs.num_sccs());
+    for scc in sccs.all_sccs() {
+        let mut scc_requesters = FxIndexSet::default();
+        for &item in &members[scc] {
+            scc_requesters.extend(
+                usage_map
+                    .get_user_items(item)
+                    .iter()
+                    .filter(|&&user| user.krate() == LOCAL_CRATE && user != item),
+            );
+        }
+        for &successor in sccs.successors(scc) {
+            scc_requesters.extend(requesters[successor].iter().copied());
+        }
+        requesters.push(scc_requesters);
+    }
+
+    for &item in items {
+        // Non-generic items of the local crate are codegened for their own sake.
+        if item.krate() == LOCAL_CRATE && !item.is_generic_fn(tcx) {
+            continue;
+        }
+
+        let scc = sccs.scc(nodes.get_index_of(&item).unwrap());
+        let mut names: Vec<_> =
+            requesters[scc].iter().map(|user| with_no_trimmed_paths!(user.to_string())).collect();
+        names.sort();
+        names.dedup();
+
+        tcx.sess.code_stats.record_mono_item_requesters(item.symbol_name(tcx).name, names);
+    }
+}
+
+/// Outputs the costs of the items to a file in the `-Z dump-item-costs` directory.
+pub fn dump_item_costs(tcx: TyCtxt<'_>) {
+    if let SwitchWithOptPath::Enabled(ref path) = tcx.sess.opts.unstable_opts.dump_item_costs {
+        if let Err(err) = write_item_costs(tcx, path) {
+            tcx.dcx().emit_fatal(CouldntDumpItemCosts { error: err.to_string() });
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, serde::Serialize)]
+struct Cost {
+    size_estimate: usize,
+    llvm_ir_size: Option<usize>,
+    /// In seconds. Only the time spent generating the backend's IR, not optimizing it.
+    codegen_time: f64,
+}
+
+impl Cost {
+    fn add(self, other: Cost) -> Cost {
+        let llvm_ir_size = match (self.llvm_ir_size, other.llvm_ir_size) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+        Cost {
+            size_estimate: self.size_estimate + other.size_estimate,
+            llvm_ir_size,
+            codegen_time: self.codegen_time + other.codegen_time,
+        }
+    }
+
+    /// Orders the highest costs first, by codegen time, then by size.
+    fn rank(&self, other: &Cost) -> cmp::Ordering {
+        other
+            .codegen_time
+            .total_cmp(&self.codegen_time)
+            .then(other.llvm_ir_size.cmp(&self.llvm_ir_size))
+            .then(other.size_estimate.cmp(&self.size_estimate))
+    }
+}
+
+#[derive(serde::Serialize)]
+struct InstanceCost {
+    name: String,
+    copies: usize,
+    /// The copies in codegen units reused from the incremental cache, without recorded costs.
+    reused_copies: usize,
+    #[serde(flatten)]
+    cost: Cost,
+    requested_by: Vec<String>,
+}
+
+#[derive(serde::Serialize)]
+struct ItemCost {
+    name: String,
+    #[serde(rename = "crate")]
+    krate: String,
+    instantiations: usize,
+    copies: usize,
+    reused_copies: usize,
+    #[serde(flatten)]
+    cost: Cost,
+    requested_by: Vec<String>,
+    instances: Vec<InstanceCost>,
+}
+
+fn write_item_costs(
+    tcx: TyCtxt<'_>,
+    output_directory: &Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output_directory = if let Some(ref directory) = output_directory {
+        fs::create_dir_all(directory)?;
+        directory
+    } else {
+        Path::new(".")
+    };
+
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
+    let format = tcx.sess.opts.unstable_opts.dump_item_costs_format;
+    let ext = format.extension();
+    let output_path = output_directory.join(format!("{crate_name}.item_costs.{ext}"));
+    let mut file = BufWriter::new(File::create(&output_path)?);
+
+    // Count the copies of each mono item, and group the mono items by source item.
+    let (_, codegen_units) = tcx.collect_and_partition_mono_items(());
+    let reused_cgus = tcx.sess.code_stats.take_reused_cgus();
+    let mut copies: FxIndexMap<MonoItem<'_>, (usize, usize)> = Default::default();
+    for cgu in codegen_units {
+        let reused = reused_cgus.contains(&cgu.name());
+        for &mono_item in cgu.items().keys() {
+            let (copies, reused_copies) = copies.entry(mono_item).or_default();
+            *copies += 1;
+            *reused_copies += usize::from(reused);
+        }
+    }
+    let mut instances_per_def_id: FxIndexMap<_, Vec<_>> = Default::default();
+    for (mono_item, copies) in copies {
+        instances_per_def_id.entry(mono_item.def_id()).or_default().push((mono_item, copies));
+    }
+
+    let mut recorded = tcx.sess.code_stats.take_mono_item_costs();
+    let mut items: Vec<_> = instances_per_def_id
+        .into_iter()
+        .map(|(def_id, mono_items)| {
+            let mut instances: Vec<_> = mono_items
+                .into_iter()
+                .map(|(mono_item, (copies, reused_copies))| {
+                    let recorded =
+                        recorded.remove(mono_item.symbol_name(tcx).name).unwrap_or_default();
+                    let cost = Cost {
+                        size_estimate: copies * mono_item.size_estimate(tcx),
+                        llvm_ir_size: recorded.llvm_ir_size,
+                        codegen_time: recorded.codegen_time.as_secs_f64(),
+                    };
+                    InstanceCost {
+                        name: with_no_trimmed_paths!(mono_item.to_string()),
+                        copies,
+                        reused_copies,
+                        cost,
+                        requested_by: recorded.requested_by,
+                    }
+                })
+                .collect();
+            instances.sort_by(|a, b| a.cost.rank(&b.cost).then_with(|| a.name.cmp(&b.name)));
+
+            let mut requested_by: Vec<_> =
+                instances.iter().flat_map(|instance| instance.requested_by.clone()).collect();
+            requested_by.sort();
+            requested_by.dedup();
+            ItemCost {
+                name: with_no_trimmed_paths!(tcx.def_path_str(def_id)),
+                krate: tcx.crate_name(def_id.krate).to_string(),
+                instantiations: instances.len(),
+                copies: instances.iter().map(|instance| instance.copies).sum(),
+                reused_copies: instances.iter().map(|instance| instance.reused_copies).sum(),
+                cost: instances
+                    .iter()
+                    .fold(Cost::default(), |cost, instance| cost.add(instance.cost)),
+                requested_by,
+                instances,
+            }
+        })
+        .collect();
+    items.sort_by(|a, b| a.cost.rank(&b.cost).then_with(|| a.name.cmp(&b.name)));
+
+    match format {
+        DumpMonoStatsFormat::Json => serde_json::to_writer(&mut file, &items)?,
+        DumpMonoStatsFormat::Markdown => {
+            writeln!(
+                file,
+                "| Item | Crate | Instantiations | Copies | Reused Copies | Estimated Size | \
+                 LLVM IR Size | IR Codegen Time (ms) | Requested By |"
+            )?;
+            writeln!(file, "| --- | --- | ---: | ---: | ---: | ---: | ---: | ---: | --- |")?;
+
+            for item in &items {
+                let llvm_ir_size =
+                    item.cost.llvm_ir_size.map_or_else(|| "-".to_owned(), |size| size.to_string());
+                let mut requested_by: Vec<_> = item
+                    .requested_by
+                    .iter()
+                    .take(MAX_REQUESTERS_SHOWN)
+                    .map(|name| format!("`{name}`"))
+                    .collect();
+                if item.requested_by.len() > MAX_REQUESTERS_SHOWN {
+                    requested_by
+                        .push(format!("{} more", item.requested_by.len() - MAX_REQUESTERS_SHOWN));
+                }
+                writeln!(
+                    file,
+                    "| `{}` | {} | {} | {} | {} | {} | {llvm_ir_size} | {:.3} | {} |",
+                    item.name,
+                    item.krate,
+                    item.instantiations,
+                    item.copies,
+                    item.reused_copies,
+                    item.cost.size_estimate,
+                    item.cost.codegen_time * 1000.0,
+                    requested_by.join(", "),
+                )?;
+            }
+        }
+    }
+    file.flush()?;
+
+    Ok(())
+}
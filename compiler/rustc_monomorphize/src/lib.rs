@@ -13,10 +13,13 @@
 
 mod collector;
 mod errors;
+mod item_costs;
 mod partitioning;
 mod polymorphize;
 mod util;
 
+pub use item_costs::dump_item_costs;
+
 rustc_fluent_macro::fluent_messages! { "../messages.ftl" }
 
 fn custom_coerce_unsize_info<'tcx>(
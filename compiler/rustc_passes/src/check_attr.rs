@@ -221,7 +221,10 @@ fn check_attributes(
                 }
                 [sym::path, ..] => self.check_generic_attr(hir_id, attr, target, Target::Mod),
                 [sym::macro_export, ..] => self.check_macro_export(hir_id, attr, target),
-                [sym::ignore, ..] | [sym::should_panic, ..] => {
+                [sym::ignore, ..]
+                | [sym::should_panic, ..]
+                | [sym::test_generator, ..]
+                | [sym::test_timeout, ..] => {
                     self.check_generic_attr(hir_id, attr, target, Target::Fn)
                 }
                 [sym::automatically_derived, ..] => {
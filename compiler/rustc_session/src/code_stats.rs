@@ -1,4 +1,5 @@
 use std::cmp;
+use std::time::Duration;
 
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::sync::Lock;
@@ -90,10 +91,31 @@ pub struct VTableSizeInfo {
     pub upcasting_cost_percent: f64,
 }
 
+/// What `-Z dump-item-costs` knows about a mono item, besides its codegen unit placement.
+#[derive(Default, Debug)]
+pub struct MonoItemCost {
+    /// The nearest items of the local crate that use the mono item, if it is an instantiation
+    /// of a generic or comes from another crate.
+    pub requested_by: Vec<String>,
+
+    /// Time spent generating the backend's IR for the item, summed over all codegen units it's
+    /// placed in. This doesn't include optimizing that IR and emitting machine code, which the
+    /// backend does for whole codegen units.
+    pub codegen_time: Duration,
+
+    /// Number of LLVM IR instructions generated for the item, summed over all codegen units
+    /// it's placed in. `None` if the backend doesn't report it.
+    pub llvm_ir_size: Option<usize>,
+}
+
 #[derive(Default)]
 pub struct CodeStats {
     type_sizes: Lock<FxHashSet<TypeSizeInfo>>,
     vtable_sizes: Lock<FxHashMap<DefId, VTableSizeInfo>>,
+    /// Keyed by symbol name.
+    mono_item_costs: Lock<FxHashMap<String, MonoItemCost>>,
+    /// The codegen units reused from the incremental cache, which have no recorded costs.
+    reused_cgus: Lock<FxHashSet<Symbol>>,
 }
 
 impl CodeStats {
@@ -135,6 +157,38 @@ pub fn record_vtable_size(&self, trait_did: DefId, trait_name: &str, info: VTabl
         );
     }
 
+    pub fn record_mono_item_requesters(&self, symbol_name: &str, requested_by: Vec<String>) {
+        let mut costs = self.mono_item_costs.lock();
+        costs.entry(symbol_name.to_owned()).or_default().requested_by = requested_by;
+    }
+
+    /// Records the code generated for a mono item in one codegen unit.
+    pub fn record_mono_item_codegen(
+        &self,
+        symbol_name: &str,
+        time: Duration,
+        llvm_ir_size: Option<usize>,
+    ) {
+        let mut costs = self.mono_item_costs.lock();
+        let cost = costs.entry(symbol_name.to_owned()).or_default();
+        cost.codegen_time += time;
+        if let Some(size) = llvm_ir_size {
+            *cost.llvm_ir_size.get_or_insert(0) += size;
+        }
+    }
+
+    pub fn take_mono_item_costs(&self) -> FxHashMap<String, MonoItemCost> {
+        std::mem::take(&mut *self.mono_item_costs.lock())
+    }
+
+    pub fn record_reused_cgu(&self, cgu_name: Symbol) {
+        self.reused_cgus.lock().insert(cgu_name);
+    }
+
+    pub fn take_reused_cgus(&self) -> FxHashSet<Symbol> {
+        std::mem::take(&mut *self.reused_cgus.lock())
+    }
+
     pub fn print_type_sizes(&self) {
         let type_sizes = self.type_sizes.borrow();
         // We will soon sort, so the initial order does not matter.
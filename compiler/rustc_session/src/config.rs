@@ -11,11 +11,12 @@
 use std::hash::Hash;
 use std::path::{Path, PathBuf};
 use std::str::{self, FromStr};
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock, Mutex};
 use std::{fmt, fs, iter};
 
 use rustc_data_structures::fx::{FxHashSet, FxIndexMap};
 use rustc_data_structures::stable_hasher::{StableOrd, ToStableHashKey};
+use rustc_errors::apply_suggestions::FixedSources;
 use rustc_errors::emitter::HumanReadableErrorType;
 use rustc_errors::{ColorConfig, DiagArgValue, DiagCtxtFlags, IntoDiagArg};
 use rustc_feature::UnstableFeatures;
@@ -31,6 +32,7 @@
 use tracing::debug;
 
 use crate::errors::FileWriteFail;
+use crate::lint_baseline::LintBaselineOptions;
 pub use crate::options::*;
 use crate::search_paths::SearchPath;
 use crate::utils::{CanonicalizedPath, NativeLib, NativeLibKind};
@@ -631,6 +633,9 @@ pub enum ErrorOutputType {
         json_rendered: HumanReadableErrorType,
         color_config: ColorConfig,
     },
+    /// A SARIF log that's consumed by code-scanning tools, written once
+    /// compilation is done.
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -816,6 +821,9 @@ pub enum PrintKind {
     Sysroot,
     TargetLibdir,
     CrateName,
+    CrateItems,
+    CrateDeps,
+    CrateCfg,
     Cfg,
     CheckCfg,
     CallingConventions,
@@ -965,6 +973,20 @@ pub fn overwrite(&self, content: &str, sess: &Session) {
     }
 }
 
+/// The value of `--apply-suggestions` and `--apply-suggestions-diff`.
+#[derive(Clone, Debug)]
+pub struct ApplySuggestions {
+    /// The lints and error codes whose machine-applicable suggestions are
+    /// applied.
+    pub names: Vec<String>,
+    /// Where to write a diff of the changes, if the source files shouldn't be
+    /// changed.
+    pub diff: Option<OutFileName>,
+    /// The sources fixed so far. The driver compiles several times with
+    /// clones of these options, which share them.
+    pub fixed: Arc<Mutex<FixedSources>>,
+}
+
 #[derive(Clone, Hash, Debug, HashStable_Generic, Encodable, Decodable)]
 pub struct OutputFilenames {
     pub(crate) out_directory: PathBuf,
@@ -1149,6 +1171,8 @@ fn default() -> Options {
             debuginfo_compression: DebugInfoCompression::None,
             lint_opts: Vec::new(),
             lint_cap: None,
+            lint_baseline: None,
+            apply_suggestions: None,
             describe_lints: false,
             output_types: OutputTypes(BTreeMap::new()),
             search_paths: vec![],
@@ -1178,6 +1202,7 @@ fn default() -> Options {
             json_artifact_notifications: false,
             json_unused_externs: JsonUnusedExterns::No,
             json_future_incompat: false,
+            json_explanation_summary: false,
             pretty: None,
             working_dir: RealFileName::LocalPath(std::env::current_dir().unwrap()),
             color: ColorConfig::Auto,
@@ -1447,7 +1472,7 @@ pub(crate) fn flagmulti_s(a: S, b: S, c: S) -> R {
         stable(longer(a, b), move |opts| opts.optflagmulti(a, b, c))
     }
 
-    fn opt(a: S, b: S, c: S, d: S) -> R {
+    pub(crate) fn opt(a: S, b: S, c: S, d: S) -> R {
         unstable(longer(a, b), move |opts| opts.optopt(a, b, c, d))
     }
     pub(crate) fn multi(a: S, b: S, c: S, d: S) -> R {
@@ -1510,7 +1535,8 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
             "print",
             "Compiler information to print on stdout",
             "[crate-name|file-names|sysroot|target-libdir|cfg|check-cfg|calling-conventions|\
-             target-list|target-cpus|target-features|relocation-models|code-models|\
+             crate-items-json|crate-deps-json|crate-cfg-json|target-list|target-cpus|\
+             target-features|relocation-models|code-models|\
              tls-models|target-spec-json|all-target-specs-json|native-static-libs|\
              stack-protector-strategies|link-args|deployment-target]",
         ),
@@ -1571,7 +1597,7 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
             "",
             "error-format",
             "How errors and other messages are produced",
-            "human|json|short",
+            "human|json|pretty-json|short|sarif|human-annotate-rs",
         ),
         opt::multi_s("", "json", "Configure the JSON output of the compiler", "CONFIG"),
         opt::opt_s(
@@ -1596,10 +1622,55 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
             "FROM=TO",
         ),
         opt::multi("", "env-set", "Inject an environment variable", "VAR=VALUE"),
+        opt::opt(
+            "",
+            "lint-baseline",
+            "Only report lint warnings that are not in the baseline FILE, \
+             or record them into FILE if it doesn't exist",
+            "FILE",
+        ),
+        opt::multi(
+            "",
+            "apply-suggestions",
+            "Apply the machine-applicable suggestions of these lints and error codes \
+             to the source files",
+            "NAME[,NAME...]",
+        ),
+        opt::opt(
+            "",
+            "apply-suggestions-diff",
+            "Write the changes made by `--apply-suggestions` as a diff to FILE \
+             instead of the source files",
+            "FILE",
+        ),
     ]);
     opts
 }
 
+fn parse_apply_suggestions(
+    early_dcx: &EarlyDiagCtxt,
+    matches: &getopts::Matches,
+) -> Option<ApplySuggestions> {
+    let names: Vec<String> = matches
+        .opt_strs("apply-suggestions")
+        .iter()
+        .flat_map(|list| list.split(','))
+        .filter(|name| !name.is_empty())
+        .map(|name| name.replace('-', "_"))
+        .collect();
+    let diff = matches.opt_str("apply-suggestions-diff").map(|path| match &*path {
+        "-" => OutFileName::Stdout,
+        path => OutFileName::Real(PathBuf::from(path)),
+    });
+    if names.is_empty() {
+        if diff.is_some() {
+            early_dcx.early_fatal("`--apply-suggestions-diff` requires `--apply-suggestions`");
+        }
+        return None;
+    }
+    Some(ApplySuggestions { names, diff, fixed: Arc::default() })
+}
+
 pub fn get_cmd_lint_options(
     early_dcx: &EarlyDiagCtxt,
     matches: &getopts::Matches,
@@ -1655,6 +1726,7 @@ pub struct JsonConfig {
     json_artifact_notifications: bool,
     pub json_unused_externs: JsonUnusedExterns,
     json_future_incompat: bool,
+    json_explanation_summary: bool,
 }
 
 /// Report unused externs in event stream
@@ -1694,6 +1766,7 @@ pub fn parse_json(early_dcx: &EarlyDiagCtxt, matches: &getopts::Matches) -> Json
     let mut json_artifact_notifications = false;
     let mut json_unused_externs = JsonUnusedExterns::No;
     let mut json_future_incompat = false;
+    let mut json_explanation_summary = false;
     for option in matches.opt_strs("json") {
         // For now conservatively forbid `--color` with `--json` since `--json`
         // won't actually be emitting any colors and anything colorized is
@@ -1705,11 +1778,13 @@ pub fn parse_json(early_dcx: &EarlyDiagCtxt, matches: &getopts::Matches) -> Json
         for sub_option in option.split(',') {
             match sub_option {
                 "diagnostic-short" => json_rendered = HumanReadableErrorType::Short,
+                "diagnostic-annotate-rs" => json_rendered = HumanReadableErrorType::AnnotateSnippet,
                 "diagnostic-rendered-ansi" => json_color = ColorConfig::Always,
                 "artifacts" => json_artifact_notifications = true,
                 "unused-externs" => json_unused_externs = JsonUnusedExterns::Loud,
                 "unused-externs-silent" => json_unused_externs = JsonUnusedExterns::Silent,
                 "future-incompat" => json_future_incompat = true,
+                "explanation-summary" => json_explanation_summary = true,
                 s => early_dcx.early_fatal(format!("unknown `--json` option `{s}`")),
             }
         }
@@ -1721,6 +1796,7 @@ pub fn parse_json(early_dcx: &EarlyDiagCtxt, matches: &getopts::Matches) -> Json
         json_artifact_notifications,
         json_unused_externs,
         json_future_incompat,
+        json_explanation_summary,
     }
 }
 
@@ -1751,14 +1827,15 @@ pub fn parse_error_format(
                 ErrorOutputType::Json { pretty: true, json_rendered, color_config: json_color }
             }
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short, color),
+            Some("sarif") => ErrorOutputType::Sarif,
             Some(arg) => {
                 early_dcx.abort_if_error_and_set_error_format(ErrorOutputType::HumanReadable(
                     HumanReadableErrorType::Default,
                     color,
                 ));
                 early_dcx.early_fatal(format!(
-                    "argument for `--error-format` must be `human`, `json` or \
-                     `short` (instead was `{arg}`)"
+                    "argument for `--error-format` must be `human`, `json`, `pretty-json`, \
+                     `short`, `sarif` or `human-annotate-rs` (instead was `{arg}`)"
                 ))
             }
         }
@@ -1822,6 +1899,16 @@ fn check_error_format_stability(
         {
             early_dcx.early_fatal("`--error-format=human-annotate-rs` is unstable");
         }
+        if let ErrorOutputType::Json {
+            json_rendered: HumanReadableErrorType::AnnotateSnippet,
+            ..
+        } = error_format
+        {
+            early_dcx.early_fatal("`--json=diagnostic-annotate-rs` is unstable");
+        }
+        if let ErrorOutputType::Sarif = error_format {
+            early_dcx.early_fatal("`--error-format=sarif` is unstable");
+        }
     }
 }
 
@@ -1933,6 +2020,9 @@ fn collect_print_requests(
         ("cfg", PrintKind::Cfg),
         ("check-cfg", PrintKind::CheckCfg),
         ("code-models", PrintKind::CodeModels),
+        ("crate-cfg-json", PrintKind::CrateCfg),
+        ("crate-deps-json", PrintKind::CrateDeps),
+        ("crate-items-json", PrintKind::CrateItems),
         ("crate-name", PrintKind::CrateName),
         ("deployment-target", PrintKind::DeploymentTarget),
         ("file-names", PrintKind::FileNames),
@@ -1991,6 +2081,19 @@ fn collect_print_requests(
                     );
                 }
             }
+            Some(&(
+                name,
+                kind @ (PrintKind::CrateItems | PrintKind::CrateDeps | PrintKind::CrateCfg),
+            )) => {
+                if unstable_opts.unstable_options {
+                    kind
+                } else {
+                    early_dcx.early_fatal(format!(
+                        "the `-Z unstable-options` flag must also be passed to \
+                         enable the {name} print option",
+                    ));
+                }
+            }
             Some(&(_, print_kind)) => print_kind,
             None => {
                 let prints =
@@ -2422,6 +2525,7 @@ pub fn build_session_options(early_dcx: &mut EarlyDiagCtxt, matches: &getopts::M
         json_artifact_notifications,
         json_unused_externs,
         json_future_incompat,
+        json_explanation_summary,
     } = parse_json(early_dcx, matches);
 
     let error_format = parse_error_format(early_dcx, matches, color, json_color, json_rendered);
@@ -2438,8 +2542,17 @@ pub fn build_session_options(early_dcx: &mut EarlyDiagCtxt, matches: &getopts::M
 
     let mut unstable_opts = UnstableOptions::build(early_dcx, matches);
     let (lint_opts, describe_lints, lint_cap) = get_cmd_lint_options(early_dcx, matches);
+    let lint_baseline = matches.opt_str("lint-baseline").map(|path| {
+        LintBaselineOptions::load(PathBuf::from(&path)).unwrap_or_else(|e| {
+            early_dcx.early_fatal(format!("failed to read lint baseline `{path}`: {e}"))
+        })
+    });
+    let apply_suggestions = parse_apply_suggestions(early_dcx, matches);
 
     check_error_format_stability(early_dcx, &unstable_opts, error_format);
+    if json_explanation_summary && !unstable_opts.unstable_options {
+        early_dcx.early_fatal("`--json=explanation-summary` is unstable");
+    }
 
     let output_types = parse_output_types(early_dcx, &unstable_opts, matches);
 
@@ -2470,6 +2583,11 @@ pub fn build_session_options(early_dcx: &mut EarlyDiagCtxt, matches: &getopts::M
     if unstable_opts.profile && incremental.is_some() {
         early_dcx.early_fatal("can't instrument with gcov profiling when compiling incrementally");
     }
+    // Warnings replayed from the incremental cache don't go through the baseline, so it could
+    // neither record them nor tell which occurrences of a warning are new.
+    if lint_baseline.is_some() && incremental.is_some() {
+        early_dcx.early_fatal("`--lint-baseline` is incompatible with incremental compilation");
+    }
     if unstable_opts.profile {
         match codegen_units {
             Some(1) => {}
@@ -2695,6 +2813,8 @@ pub fn build_session_options(early_dcx: &mut EarlyDiagCtxt, matches: &getopts::M
         debuginfo_compression,
         lint_opts,
         lint_cap,
+        lint_baseline,
+        apply_suggestions,
         describe_lints,
         output_types,
         search_paths,
@@ -2724,6 +2844,7 @@ pub fn build_session_options(early_dcx: &mut EarlyDiagCtxt, matches: &getopts::M
         json_artifact_notifications,
         json_unused_externs,
         json_future_incompat,
+        json_explanation_summary,
         pretty,
         working_dir,
         color,
@@ -3021,6 +3142,7 @@ pub(crate) mod dep_tracking {
         WasiExecModel,
     };
     use crate::lint;
+    use crate::lint_baseline::LintBaselineOptions;
     use crate::utils::NativeLib;
 
     pub(crate) trait DepTrackingHash {
@@ -3111,6 +3233,7 @@ fn hash(
         BranchProtection,
         OomStrategy,
         LanguageIdentifier,
+        LintBaselineOptions,
         NextSolverConfig,
         PatchableFunctionEntry,
         Polonius,
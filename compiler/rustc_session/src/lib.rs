@@ -19,6 +19,7 @@
 pub mod config;
 pub mod cstore;
 pub mod filesearch;
+pub mod lint_baseline;
 mod options;
 pub mod search_paths;
 
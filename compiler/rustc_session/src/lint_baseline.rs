@@ -0,0 +1,155 @@
+//! Support for `--lint-baseline`, which records the lint warnings a crate
+//! currently has so that later builds only report new ones.
+//!
+//! A warning is identified by its lint, the file it is in and a fingerprint of
+//! the code it points at, rather than by line numbers, so that editing other
+//! parts of the file doesn't make known warnings show up again. The same code
+//! can trigger a lint several times in a file, so the baseline counts how often
+//! each warning occurs, and only occurrences beyond that count are reported.
+//!
+//! Which occurrences are beyond the count depends on the order in which the
+//! warnings are emitted, and warnings replayed from the incremental cache don't
+//! go through the baseline at all, so it can't be used with incremental
+//! compilation.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::hash::Hash;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rustc_data_structures::stable_hasher::{Hash64, StableHasher};
+use rustc_data_structures::sync::Lock;
+
+const HEADER: &str =
+    "# rustc lint baseline: lint, file, fingerprint of the linted code, occurrences";
+
+/// Identifies a lint warning across builds.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct LintBaselineKey {
+    pub lint: String,
+    pub file: String,
+    pub fingerprint: u64,
+}
+
+impl LintBaselineKey {
+    /// Creates the key for a warning from `lint` about the code `text` in `file`.
+    ///
+    /// Whitespace only separates the tokens of `text`, so reformatting the
+    /// code keeps the fingerprint as long as its tokens stay the same.
+    pub fn new(lint: String, file: String, text: &str) -> LintBaselineKey {
+        let mut hasher = StableHasher::new();
+        for word in text.split_whitespace() {
+            word.hash(&mut hasher);
+        }
+        let fingerprint = hasher.finish::<Hash64>().as_u64();
+        LintBaselineKey { lint, file, fingerprint }
+    }
+}
+
+/// The value of the `--lint-baseline` option.
+#[derive(Clone, Hash, Debug)]
+pub struct LintBaselineOptions {
+    pub path: PathBuf,
+    /// The warnings recorded in the baseline, or `None` if the file doesn't
+    /// exist yet and should be recorded by this build.
+    ///
+    /// This is part of the option, rather than being read once the session
+    /// exists, so that a file that can't be read is reported along with the
+    /// other invalid options.
+    pub recorded: Option<Arc<BTreeMap<LintBaselineKey, usize>>>,
+}
+
+impl LintBaselineOptions {
+    /// Reads the baseline at `path`, if there is one.
+    pub fn load(path: PathBuf) -> Result<LintBaselineOptions, String> {
+        let recorded = match std::fs::read_to_string(&path) {
+            Ok(contents) => Some(Arc::new(parse(&contents)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.to_string()),
+        };
+        Ok(LintBaselineOptions { path, recorded })
+    }
+}
+
+/// The state of `--lint-baseline` during a session.
+pub struct LintBaseline {
+    path: PathBuf,
+    mode: Lock<Mode>,
+}
+
+enum Mode {
+    /// Suppress the warnings in the baseline, counting down how many
+    /// occurrences of each are left to suppress.
+    Suppress(BTreeMap<LintBaselineKey, usize>),
+    /// Record every warning into a new baseline.
+    Record(BTreeMap<LintBaselineKey, usize>),
+}
+
+impl LintBaseline {
+    pub fn new(options: &LintBaselineOptions) -> LintBaseline {
+        let mode = match &options.recorded {
+            Some(recorded) => Mode::Suppress((**recorded).clone()),
+            None => Mode::Record(BTreeMap::new()),
+        };
+        LintBaseline { path: options.path.clone(), mode: Lock::new(mode) }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Called for each lint warning about to be emitted. Returns whether it is
+    /// a known occurrence that shouldn't be reported.
+    pub fn suppress(&self, key: LintBaselineKey) -> bool {
+        match &mut *self.mode.lock() {
+            Mode::Suppress(remaining) => match remaining.get_mut(&key) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    true
+                }
+                _ => false,
+            },
+            Mode::Record(recorded) => {
+                *recorded.entry(key).or_default() += 1;
+                false
+            }
+        }
+    }
+
+    /// Writes the recorded warnings, if this session was recording a new
+    /// baseline.
+    pub fn write(&self) -> io::Result<()> {
+        match &*self.mode.lock() {
+            Mode::Suppress(_) => Ok(()),
+            Mode::Record(recorded) => std::fs::write(&self.path, render(recorded)),
+        }
+    }
+}
+
+fn parse(contents: &str) -> Result<BTreeMap<LintBaselineKey, usize>, String> {
+    let mut recorded = BTreeMap::new();
+    for (index, line) in contents.lines().enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || format!("line {} is not `LINT\\tFILE\\tFINGERPRINT\\tCOUNT`", index + 1);
+        let [lint, file, fingerprint, count] = line.split('\t').collect::<Vec<_>>()[..] else {
+            return Err(invalid());
+        };
+        let fingerprint = u64::from_str_radix(fingerprint, 16).map_err(|_| invalid())?;
+        let count = count.parse::<usize>().map_err(|_| invalid())?;
+        let key = LintBaselineKey { lint: lint.to_owned(), file: file.to_owned(), fingerprint };
+        *recorded.entry(key).or_default() += count;
+    }
+    Ok(recorded)
+}
+
+fn render(recorded: &BTreeMap<LintBaselineKey, usize>) -> String {
+    let mut contents = format!("{HEADER}\n");
+    for (LintBaselineKey { lint, file, fingerprint }, count) in recorded {
+        writeln!(contents, "{lint}\t{file}\t{fingerprint:016x}\t{count}").unwrap();
+    }
+    contents
+}
@@ -18,6 +18,7 @@
 };
 
 use crate::config::*;
+use crate::lint_baseline::LintBaselineOptions;
 use crate::search_paths::SearchPath;
 use crate::utils::NativeLib;
 use crate::{lint, EarlyDiagCtxt};
@@ -140,6 +141,8 @@ pub struct Options {
         debuginfo_compression: DebugInfoCompression [TRACKED],
         lint_opts: Vec<(String, lint::Level)> [TRACKED_NO_CRATE_HASH],
         lint_cap: Option<lint::Level> [TRACKED_NO_CRATE_HASH],
+        lint_baseline: Option<LintBaselineOptions> [TRACKED_NO_CRATE_HASH],
+        apply_suggestions: Option<ApplySuggestions> [UNTRACKED],
         describe_lints: bool [UNTRACKED],
         output_types: OutputTypes [TRACKED],
         search_paths: Vec<SearchPath> [UNTRACKED],
@@ -215,6 +218,10 @@ pub struct Options {
         /// `true` if we're emitting a JSON job containing a future-incompat report for lints
         json_future_incompat: bool [TRACKED],
 
+        /// `true` if we're embedding a summary of the error code explanations in
+        /// JSON diagnostics
+        json_explanation_summary: bool [UNTRACKED],
+
         pretty: Option<PpMode> [UNTRACKED],
 
         /// The (potentially remapped) working directory
@@ -375,7 +382,7 @@ mod desc {
     pub const parse_opt_number: &str = parse_number;
     pub const parse_frame_pointer: &str = "one of `true`/`yes`/`on`, `false`/`no`/`off`, or (with -Zunstable-options) `non-leaf` or `always`";
     pub const parse_threads: &str = parse_number;
-    pub const parse_time_passes_format: &str = "`text` (default) or `json`";
+    pub const parse_time_passes_format: &str = "`text` (default), `json` or `chrome`";
     pub const parse_passes: &str = "a space-separated list of passes, or `all`";
     pub const parse_panic_strategy: &str = "either `unwind` or `abort`";
     pub const parse_on_broken_pipe: &str = "either `kill`, `error`, or `inherit`";
@@ -955,6 +962,10 @@ pub(crate) fn parse_time_passes_format(slot: &mut TimePassesFormat, v: Option<&s
                 *slot = TimePassesFormat::Text;
                 true
             }
+            Some("chrome") => {
+                *slot = TimePassesFormat::Chrome;
+                true
+            }
             Some(_) => false,
         }
     }
@@ -1693,6 +1704,12 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
     dump_dep_graph: bool = (false, parse_bool, [UNTRACKED],
         "dump the dependency graph to $RUST_DEP_GRAPH (default: /tmp/dep_graph.gv) \
         (default: no)"),
+    dump_item_costs: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
+        parse_switch_with_opt_path, [UNTRACKED],
+        "output the code size and codegen time of each item, its instantiations and the items \
+        that requested them, ranked by cost"),
+    dump_item_costs_format: DumpMonoStatsFormat = (DumpMonoStatsFormat::Markdown, parse_dump_mono_stats, [UNTRACKED],
+        "the format to use for -Z dump-item-costs (`markdown` (default) or `json`)"),
     dump_mir: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "dump MIR state to file.
         `val` is used to select which passes and functions to dump. For example:
@@ -1843,6 +1860,9 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         (space separated)"),
     macro_backtrace: bool = (false, parse_bool, [UNTRACKED],
         "show macro backtraces (default: no)"),
+    macro_expansion_trace: bool = (false, parse_bool, [UNTRACKED],
+        "show the code produced by each `macro_rules!` expansion a diagnostic points into \
+        (default: no)"),
     maximal_hir_to_mir_coverage: bool = (false, parse_bool, [TRACKED],
         "save as much information as possible about the correspondence between MIR and HIR \
         as source scopes (default: no)"),
@@ -2072,7 +2092,7 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
     time_passes: bool = (false, parse_bool, [UNTRACKED],
         "measure time of each rustc pass (default: no)"),
     time_passes_format: TimePassesFormat = (TimePassesFormat::Text, parse_time_passes_format, [UNTRACKED],
-        "the format to use for -Z time-passes (`text` (default) or `json`)"),
+        "the format to use for -Z time-passes (`text` (default), `json` or `chrome`)"),
     tiny_const_eval_limit: bool = (false, parse_bool, [TRACKED],
         "sets a tiny, non-configurable limit for const eval; useful for compiler tests"),
     #[rustc_lint_opt_deny_field_access("use `Session::tls_model` instead of this field")]
@@ -2093,6 +2113,12 @@ pub(crate) fn parse_mir_include_spans(slot: &mut MirIncludeSpans, v: Option<&str
         "language identifier for diagnostic output"),
     translate_remapped_path_to_local_path: bool = (true, parse_bool, [TRACKED],
         "translate remapped paths into local paths when possible (default: yes)"),
+    translate_report: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "write a report of the diagnostic messages that could not be translated to the \
+        `-Z translate-lang` language to a file"),
+    translate_template: Option<PathBuf> = (None, parse_opt_pathbuf, [UNTRACKED],
+        "write the messages of all diagnostics that the `-Z translate-lang` bundle does not \
+        translate yet (or all of them, without `-Z translate-lang`) to a Fluent file"),
     trap_unreachable: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "generate trap instructions for unreachable intrinsics (default: use target setting, usually yes)"),
     treat_err_as_bug: Option<NonZero<usize>> = (None, parse_treat_err_as_bug, [TRACKED],
@@ -4,21 +4,24 @@
 use std::str::FromStr;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering::SeqCst;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::{env, fmt, io};
 
 use rustc_data_structures::flock;
 use rustc_data_structures::fx::{FxHashMap, FxIndexSet};
 use rustc_data_structures::jobserver::{self, Client};
-use rustc_data_structures::profiling::{SelfProfiler, SelfProfilerRef};
+use rustc_data_structures::profiling::{SelfProfiler, SelfProfilerRef, TimePassesFormat};
 use rustc_data_structures::sync::{
     AtomicU64, DynSend, DynSync, Lock, Lrc, MappedReadGuard, ReadGuard, RwLock,
 };
 use rustc_errors::annotate_snippet_emitter_writer::AnnotateSnippetEmitter;
+use rustc_errors::apply_suggestions::SuggestionCollector;
 use rustc_errors::codes::*;
 use rustc_errors::emitter::{stderr_destination, DynEmitter, HumanEmitter, HumanReadableErrorType};
 use rustc_errors::json::JsonEmitter;
 use rustc_errors::registry::Registry;
+use rustc_errors::sarif::SarifEmitter;
+use rustc_errors::translation_report::{TranslationReport, TranslationReporter};
 use rustc_errors::{
     fallback_fluent_bundle, Diag, DiagCtxt, DiagCtxtHandle, DiagMessage, Diagnostic,
     ErrorGuaranteed, FatalAbort, FluentBundle, LazyFallbackBundle, TerminalUrl,
@@ -41,6 +44,7 @@
     InstrumentCoverage, OptLevel, OutFileName, OutputType, RemapPathScopeComponents,
     SwitchWithOptPath,
 };
+use crate::lint_baseline::LintBaseline;
 use crate::parse::{add_feature_diagnostics, ParseSess};
 use crate::search_paths::{PathKind, SearchPath};
 use crate::{errors, filesearch, lint};
@@ -178,6 +182,13 @@ pub struct Session {
     /// Cap lint level specified by a driver specifically.
     pub driver_lint_caps: FxHashMap<lint::LintId, lint::Level>,
 
+    /// Known lint warnings not to report again, or the warnings to record,
+    /// if `--lint-baseline` is specified.
+    pub lint_baseline: Option<LintBaseline>,
+
+    /// The messages that couldn't be translated, if `-Z translate-report` is specified.
+    pub translation_report: Option<Arc<Mutex<TranslationReport>>>,
+
     /// Tracks the current behavior of the CTFE engine when an error occurs.
     /// Options range from returning the error without a backtrace to returning an error
     /// and immediately printing the backtrace to stderr.
@@ -287,10 +298,34 @@ pub fn finish_diagnostics(&self, registry: &Registry) -> Option<ErrorGuaranteed>
         let mut guar = None;
         guar = guar.or(self.check_miri_unleashed_features());
         guar = guar.or(self.dcx().emit_stashed_diagnostics());
+        // A build that stopped early might not have run all lints, so it
+        // can't record a complete baseline.
+        if let Some(baseline) = &self.lint_baseline
+            && self.dcx().has_errors_excluding_lint_errors().is_none()
+            && let Err(err) = baseline.write()
+        {
+            let err = errors::FileWriteFail { path: baseline.path(), err: err.to_string() };
+            guar = guar.or(Some(self.dcx().emit_err(err)));
+        }
+        if let Some(profiler) = self.prof.get_self_profiler()
+            && let Some(path) = profiler.chrome_trace_path()
+            && let Err(err) = profiler.write_chrome_trace()
+        {
+            let err = errors::FileWriteFail { path, err: err.to_string() };
+            guar = guar.or(Some(self.dcx().emit_err(err)));
+        }
         self.dcx().print_error_count(registry);
         if self.opts.json_future_incompat {
             self.dcx().emit_future_breakage_report();
         }
+        // Written last, so that it includes the messages printed above.
+        if let Some(report) = &self.translation_report
+            && let Some(path) = &self.opts.unstable_opts.translate_report
+            && let Err(err) = report.lock().unwrap().write(path)
+        {
+            let err = errors::FileWriteFail { path, err: err.to_string() };
+            guar = guar.or(Some(self.dcx().emit_err(err)));
+        }
         guar
     }
 
@@ -924,6 +959,7 @@ fn default_emitter(
     fallback_bundle: LazyFallbackBundle,
 ) -> Box<DynEmitter> {
     let macro_backtrace = sopts.unstable_opts.macro_backtrace;
+    let macro_expansion_trace = sopts.unstable_opts.macro_expansion_trace;
     let track_diagnostics = sopts.unstable_opts.track_diagnostics;
     let terminal_url = match sopts.unstable_opts.terminal_urls {
         TerminalUrl::Auto => {
@@ -943,13 +979,19 @@ fn default_emitter(
             let short = kind.short();
 
             if let HumanReadableErrorType::AnnotateSnippet = kind {
-                let emitter = AnnotateSnippetEmitter::new(
-                    Some(source_map),
-                    bundle,
-                    fallback_bundle,
-                    short,
-                    macro_backtrace,
-                );
+                let dst = stderr_destination(color_config);
+                let emitter = AnnotateSnippetEmitter::new(dst, fallback_bundle)
+                    .fluent_bundle(bundle)
+                    .sm(Some(source_map))
+                    .short_message(short)
+                    .diagnostic_width(sopts.diagnostic_width)
+                    .macro_backtrace(macro_backtrace)
+                    .macro_expansion_trace(macro_expansion_trace)
+                    .track_diagnostics(track_diagnostics)
+                    .terminal_url(terminal_url)
+                    .ignored_directories_in_source_blocks(
+                        sopts.unstable_opts.ignore_directory_in_diagnostics_source_blocks.clone(),
+                    );
                 Box::new(emitter.ui_testing(sopts.unstable_opts.ui_testing))
             } else {
                 let emitter = HumanEmitter::new(stderr_destination(color_config), fallback_bundle)
@@ -959,6 +1001,7 @@ fn default_emitter(
                     .teach(sopts.unstable_opts.teach)
                     .diagnostic_width(sopts.diagnostic_width)
                     .macro_backtrace(macro_backtrace)
+                    .macro_expansion_trace(macro_expansion_trace)
                     .track_diagnostics(track_diagnostics)
                     .terminal_url(terminal_url)
                     .ignored_directories_in_source_blocks(
@@ -984,8 +1027,19 @@ fn default_emitter(
             )
             .diagnostic_width(sopts.diagnostic_width)
             .macro_backtrace(macro_backtrace)
+            .macro_expansion_trace(macro_expansion_trace)
             .track_diagnostics(track_diagnostics)
-            .terminal_url(terminal_url),
+            .terminal_url(terminal_url)
+            .explanation_summary(sopts.json_explanation_summary),
+        ),
+        config::ErrorOutputType::Sarif => Box::new(
+            SarifEmitter::new(
+                Box::new(io::BufWriter::new(io::stderr())),
+                source_map,
+                fallback_bundle,
+            )
+            .registry(Some(registry))
+            .fluent_bundle(bundle),
         ),
     }
 }
@@ -1032,7 +1086,21 @@ pub fn build_session(
         sopts.unstable_opts.translate_directionality_markers,
     );
     let source_map = rustc_span::source_map::get_source_map().unwrap();
-    let emitter = default_emitter(&sopts, registry, source_map.clone(), bundle, fallback_bundle);
+    let mut emitter =
+        default_emitter(&sopts, registry, source_map.clone(), bundle, fallback_bundle);
+    if let Some(apply_suggestions) = &sopts.apply_suggestions {
+        emitter = Box::new(SuggestionCollector::new(
+            emitter,
+            source_map.clone(),
+            &apply_suggestions.names,
+            Arc::clone(&apply_suggestions.fixed),
+        ));
+    }
+    let translation_report = sopts.unstable_opts.translate_report.as_ref().map(|_| {
+        let report = Arc::default();
+        emitter = Box::new(TranslationReporter::new(emitter, Arc::clone(&report)));
+        report
+    });
 
     let mut dcx =
         DiagCtxt::new(emitter).with_flags(sopts.unstable_opts.dcx_flags(can_emit_warnings));
@@ -1044,13 +1112,23 @@ pub fn build_session(
     // prevent accidental use.
     drop(early_dcx);
 
-    let self_profiler = if let SwitchWithOptPath::Enabled(ref d) = sopts.unstable_opts.self_profile
-    {
-        let directory =
-            if let Some(ref directory) = d { directory } else { std::path::Path::new(".") };
-
+    // `-Z time-passes-format=chrome` records the passes with the self-profiler, and writes
+    // only the Chrome trace unless `-Z self-profile` is given as well. The trace goes to the
+    // self-profile directory, or the current directory.
+    let self_profile_directory = match sopts.unstable_opts.self_profile {
+        SwitchWithOptPath::Enabled(ref d) => Some(d.as_deref().unwrap_or(Path::new("."))),
+        SwitchWithOptPath::Disabled => None,
+    };
+    let chrome_trace = sopts.unstable_opts.time_passes_format == TimePassesFormat::Chrome;
+    let chrome_trace_directory = match self_profile_directory {
+        Some(directory) if chrome_trace => Some(directory),
+        None if chrome_trace && sopts.unstable_opts.time_passes => Some(Path::new(".")),
+        _ => None,
+    };
+    let self_profiler = if self_profile_directory.is_some() || chrome_trace_directory.is_some() {
         let profiler = SelfProfiler::new(
-            directory,
+            self_profile_directory,
+            chrome_trace_directory,
             sopts.crate_name.as_deref(),
             sopts.unstable_opts.self_profile_events.as_deref(),
             &sopts.unstable_opts.self_profile_counter,
@@ -1099,6 +1177,8 @@ pub fn build_session(
 
     let asm_arch = if target.allow_asm { InlineAsmArch::from_str(&target.arch).ok() } else { None };
 
+    let lint_baseline = sopts.lint_baseline.as_ref().map(LintBaseline::new);
+
     let sess = Session {
         target,
         host,
@@ -1117,6 +1197,8 @@ pub fn build_session(
         lint_store: None,
         registered_lints: false,
         driver_lint_caps,
+        lint_baseline,
+        translation_report,
         ctfe_backtrace,
         miri_unleashed_features: Lock::new(Default::default()),
         asm_arch,
@@ -1450,6 +1532,18 @@ fn mk_emitter(output: ErrorOutputType) -> Box<DynEmitter> {
                 color_config,
             ))
         }
+        config::ErrorOutputType::Sarif => {
+            // The session writes its own log, so an early one is only worth
+            // writing if early diagnostics were emitted.
+            Box::new(
+                SarifEmitter::new(
+                    Box::new(io::BufWriter::new(io::stderr())),
+                    Lrc::new(SourceMap::new(FilePathMapping::empty())),
+                    fallback_bundle,
+                )
+                .write_empty_log(false),
+            )
+        }
     };
     emitter
 }
@@ -172,6 +172,66 @@ pub enum Transparency {
     Opaque,
 }
 
+/// The code produced by a macro expansion, written out to a `<macro expansion>` source file so
+/// that diagnostics can point into it.
+#[derive(Clone, Debug)]
+pub struct ExpansionTrace {
+    /// Pairs each token of the expansion, with the span it has in the compiled code, with the
+    /// span of its text in the `<macro expansion>` file. Tokens substituted for a metavariable
+    /// have the span of the macro input they come from.
+    pub tokens: Lrc<[(Span, Span)]>,
+}
+
+impl ExpansionTrace {
+    /// The part of the expanded code made of tokens that `span` covers.
+    ///
+    /// Each iteration of a `$(...)*` repetition produces tokens with the same spans, so `span` can
+    /// cover several copies of them. The copy closest to a token which occurs only once and which
+    /// one of the `context` spans covers, like a metavariable substituted in that iteration, is
+    /// chosen. Without such a token, the first copy is chosen.
+    pub fn expanded_span(&self, span: Span, context: &[Span]) -> Option<Span> {
+        let tokens = &self.tokens[..];
+        let covered = |&(token, _): &(Span, Span)| span.contains(token);
+        // A copy starts with a token starting where `span` starts, and ends with the next token
+        // ending where `span` ends.
+        let mut starts: Vec<usize> = (0..tokens.len())
+            .filter(|&i| covered(&tokens[i]) && tokens[i].0.lo() == span.lo())
+            .collect();
+        if starts.is_empty() {
+            starts.extend(tokens.iter().position(covered));
+        }
+        let copies: Vec<(usize, usize)> = starts
+            .iter()
+            .enumerate()
+            .filter_map(|(n, &start)| {
+                let copy = &tokens[start..starts.get(n + 1).copied().unwrap_or(tokens.len())];
+                let end = copy
+                    .iter()
+                    .position(|token| covered(token) && token.0.hi() == span.hi())
+                    .or_else(|| copy.iter().rposition(covered))?;
+                Some((start, start + end))
+            })
+            .collect();
+
+        let anchors: Vec<usize> = if copies.len() > 1 {
+            (0..tokens.len())
+                .filter(|&i| {
+                    let token = tokens[i].0;
+                    context.iter().any(|sp| sp.contains(token))
+                        && tokens.iter().filter(|&&(other, _)| other == token).count() == 1
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        let distance = |&&(start, end): &&(usize, usize)| {
+            anchors.iter().map(|&i| if i < start { start - i } else { i.saturating_sub(end) }).min()
+        };
+        let &(start, end) = copies.iter().min_by_key(distance)?;
+        Some(tokens[start].1.to(tokens[end].1))
+    }
+}
+
 impl LocalExpnId {
     /// The ID of the theoretical expansion that generates freshly parsed, unexpanded AST.
     pub const ROOT: LocalExpnId = LocalExpnId::ZERO;
@@ -245,6 +305,10 @@ pub fn is_descendant_of(self, ancestor: LocalExpnId) -> bool {
     pub fn expansion_cause(self) -> Option<Span> {
         self.to_expn_id().expansion_cause()
     }
+
+    pub fn set_expansion_trace(self, trace: ExpansionTrace) {
+        HygieneData::with(|data| data.expansion_traces.insert(self, trace));
+    }
 }
 
 impl ExpnId {
@@ -280,6 +344,12 @@ pub fn expn_data(self) -> ExpnData {
         HygieneData::with(|data| data.expn_data(self).clone())
     }
 
+    /// The code this expansion produced, if it was recorded for `-Z macro-expansion-trace`.
+    pub fn expansion_trace(self) -> Option<ExpansionTrace> {
+        let local_id = self.as_local()?;
+        HygieneData::with(|data| data.expansion_traces.get(&local_id).cloned())
+    }
+
     #[inline]
     pub fn is_descendant_of(self, ancestor: ExpnId) -> bool {
         // a few "fast path" cases to avoid locking HygieneData
@@ -340,6 +410,8 @@ pub(crate) struct HygieneData {
     /// The keys of this map are always computed with `ExpnData.disambiguator`
     /// set to 0.
     expn_data_disambiguators: UnhashMap<Hash64, u32>,
+    /// The code produced by `macro_rules!` expansions, recorded for `-Z macro-expansion-trace`.
+    expansion_traces: FxHashMap<LocalExpnId, ExpansionTrace>,
 }
 
 impl HygieneData {
@@ -369,6 +441,7 @@ pub(crate) fn new(edition: Edition) -> Self {
             }],
             syntax_context_map: FxHashMap::default(),
             expn_data_disambiguators: UnhashMap::default(),
+            expansion_traces: FxHashMap::default(),
         }
     }
 
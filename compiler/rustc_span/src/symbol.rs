@@ -466,6 +466,7 @@
         async_for_loop,
         async_iterator,
         async_iterator_poll_next,
+        async_test,
         atomic,
         atomic_mod,
         atomics,
@@ -1894,8 +1895,10 @@
         test_2018_feature,
         test_accepted_feature,
         test_case,
+        test_generator,
         test_removed_feature,
         test_runner,
+        test_timeout,
         test_unstable_lint,
         thread,
         thread_local,
@@ -2076,6 +2079,7 @@
         vtable_align,
         vtable_size,
         warn,
+        warnings,
         wasip2,
         wasm_abi,
         wasm_import_module,
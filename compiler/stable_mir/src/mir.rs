@@ -1,8 +1,11 @@
 pub mod alloc;
 mod body;
+pub mod builder;
+pub mod dataflow;
 pub mod mono;
 pub mod pretty;
 pub mod visit;
 
 pub use body::*;
-pub use visit::MirVisitor;
+pub use builder::BodyBuilder;
+pub use visit::{MirVisitor, MutMirVisitor};
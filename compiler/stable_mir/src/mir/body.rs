@@ -39,6 +39,9 @@ pub struct Body {
 
 pub type BasicBlockIdx = usize;
 
+/// The index of the basic block where the execution of a function body starts.
+pub const START_BLOCK: BasicBlockIdx = 0;
+
 impl Body {
     /// Constructs a `Body`.
     ///
@@ -0,0 +1,165 @@
+//! Module that provides a builder to create new function bodies or modified copies of existing
+//! ones.
+//!
+//! This is useful for tools that want to instrument a body, e.g.: by inserting checks or
+//! counters, and then pretty-print or interpret the result.
+
+use crate::mir::visit::{Location, MirVisitor, PlaceContext};
+use crate::mir::*;
+use crate::ty::Ty;
+use crate::{error, Error, Span};
+
+/// Builder for a [`Body`].
+///
+/// Statements and blocks are addressed by their index, the same way they are stored in the
+/// body. Indices are not stable: inserting a statement shifts the statements that follow it.
+/// New locals and blocks are always appended, so existing local and block indices stay valid.
+#[derive(Clone, Debug)]
+pub struct BodyBuilder {
+    blocks: Vec<BasicBlock>,
+    locals: Vec<LocalDecl>,
+    arg_count: usize,
+    var_debug_info: Vec<VarDebugInfo>,
+    spread_arg: Option<Local>,
+    span: Span,
+}
+
+impl BodyBuilder {
+    /// Start building an empty body for a function with the given return and argument locals.
+    pub fn new(ret: LocalDecl, args: Vec<LocalDecl>, span: Span) -> Self {
+        let arg_count = args.len();
+        let locals = std::iter::once(ret).chain(args).collect();
+        Self { blocks: vec![], locals, arg_count, var_debug_info: vec![], spread_arg: None, span }
+    }
+
+    /// Start building a copy of `body` that can be modified.
+    pub fn from_body(body: Body) -> Self {
+        let Body { blocks, locals, arg_count, var_debug_info, spread_arg, span } = body;
+        Self { blocks, locals, arg_count, var_debug_info, spread_arg, span }
+    }
+
+    /// The basic blocks of the body built so far.
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    /// Get a mutable reference to the basic block with the given index.
+    pub fn block_mut(&mut self, bb: BasicBlockIdx) -> &mut BasicBlock {
+        &mut self.blocks[bb]
+    }
+
+    /// The local declarations of the body built so far.
+    ///
+    /// This can be used to compute the type of places and operands, e.g.: using [`Place::ty`].
+    pub fn locals(&self) -> &[LocalDecl] {
+        &self.locals
+    }
+
+    /// Declare a new local and return its index.
+    pub fn new_local(&mut self, ty: Ty, span: Span, mutability: Mutability) -> Local {
+        self.locals.push(LocalDecl { ty, span, mutability });
+        self.locals.len() - 1
+    }
+
+    /// Append a new basic block and return its index.
+    pub fn new_block(&mut self, block: BasicBlock) -> BasicBlockIdx {
+        self.blocks.push(block);
+        self.blocks.len() - 1
+    }
+
+    /// Insert a statement in the basic block `bb` before the statement with index `stmt_idx`.
+    ///
+    /// If `stmt_idx` is equal to the number of statements, the new statement is inserted right
+    /// before the terminator.
+    pub fn insert_statement(&mut self, bb: BasicBlockIdx, stmt_idx: usize, stmt: Statement) {
+        self.blocks[bb].statements.insert(stmt_idx, stmt);
+    }
+
+    /// Append a statement to the basic block `bb`, right before its terminator.
+    pub fn push_statement(&mut self, bb: BasicBlockIdx, stmt: Statement) {
+        self.blocks[bb].statements.push(stmt);
+    }
+
+    /// Replace the terminator of the basic block `bb` and return the old one.
+    pub fn set_terminator(&mut self, bb: BasicBlockIdx, terminator: Terminator) -> Terminator {
+        std::mem::replace(&mut self.blocks[bb].terminator, terminator)
+    }
+
+    /// Split the basic block `bb` before the statement with index `stmt_idx`.
+    ///
+    /// The statements starting at `stmt_idx` and the terminator of `bb` are moved to a new basic
+    /// block, whose index is returned. `bb` is terminated by a `Goto` to the new block, so
+    /// statements and blocks can then be inserted between the two halves.
+    ///
+    /// Any edge that targeted `bb` still targets its first half.
+    pub fn split_block(&mut self, bb: BasicBlockIdx, stmt_idx: usize) -> BasicBlockIdx {
+        let new_bb = self.blocks.len();
+        let block = &mut self.blocks[bb];
+        let span = block.terminator.span;
+        let statements = block.statements.split_off(stmt_idx);
+        let terminator = std::mem::replace(
+            &mut block.terminator,
+            Terminator { kind: TerminatorKind::Goto { target: new_bb }, span },
+        );
+        self.new_block(BasicBlock { statements, terminator })
+    }
+
+    /// Add debug information for a user variable.
+    pub fn push_var_debug_info(&mut self, var_debug_info: VarDebugInfo) {
+        self.var_debug_info.push(var_debug_info);
+    }
+
+    /// Mark an argument as getting passed as its individual components.
+    ///
+    /// See [`Body::spread_arg`].
+    pub fn set_spread_arg(&mut self, spread_arg: Option<Local>) {
+        self.spread_arg = spread_arg;
+    }
+
+    /// Build the body.
+    ///
+    /// This returns an error if the body has no basic block, or if it refers to a local or a
+    /// basic block that doesn't exist. No other validation is done.
+    pub fn build(self) -> Result<Body, Error> {
+        let Self { blocks, locals, arg_count, var_debug_info, spread_arg, span } = self;
+        if blocks.is_empty() {
+            return Err(error!("A body must have at least one basic block"));
+        }
+        for (bb, block) in blocks.iter().enumerate() {
+            if let Some(target) =
+                block.terminator.successors().into_iter().find(|target| *target >= blocks.len())
+            {
+                return Err(error!(
+                    "Basic block `{bb}` targets non-existent basic block `{target}`"
+                ));
+            }
+        }
+        if let Some(spread_arg) = spread_arg {
+            if spread_arg == RETURN_LOCAL || spread_arg > arg_count {
+                return Err(error!("Spread argument `{spread_arg}` is not an argument"));
+            }
+        }
+
+        let body = Body::new(blocks, locals, arg_count, var_debug_info, spread_arg, span);
+        let mut checker = LocalChecker { num_locals: body.locals.len(), invalid_local: None };
+        checker.visit_body(&body);
+        if let Some(local) = checker.invalid_local {
+            return Err(error!("Body refers to non-existent local `{local}`"));
+        }
+        Ok(body)
+    }
+}
+
+/// Visitor that finds uses of locals that haven't been declared.
+struct LocalChecker {
+    num_locals: usize,
+    invalid_local: Option<Local>,
+}
+
+impl MirVisitor for LocalChecker {
+    fn visit_local(&mut self, local: &Local, _ptx: PlaceContext, _location: Location) {
+        if *local >= self.num_locals {
+            self.invalid_local.get_or_insert(*local);
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! # Dataflow analysis
+//!
+//! This module provides a generic fixpoint engine for dataflow analyses over a [`Body`], as well
+//! as a few commonly used analyses. Its design follows the dataflow framework that is internal to
+//! `rustc`:
+//!
+//! - An [`Analysis`] defines a domain, which must be a [`JoinSemiLattice`], and the effect that
+//!   each statement and terminator has on it.
+//! - [`Analysis::iterate_to_fixpoint`] computes the state at the entry of every basic block.
+//! - A [`ResultsCursor`] can then be used to inspect the state at any point of the body.
+//!
+//! For example, the following computes the locals that may be live before a given statement:
+//!
+//! ```ignore(needs-compiler-session)
+//! let mut cursor = MaybeLiveLocals.iterate_to_fixpoint(&body).into_cursor(&body);
+//! cursor.seek_before(ProgramPoint { block, statement_index });
+//! let live_locals = cursor.get();
+//! ```
+
+use std::collections::VecDeque;
+
+use crate::mir::{BasicBlockIdx, Body, Place, Statement, Terminator, TerminatorKind, START_BLOCK};
+
+mod impls;
+mod lattice;
+
+pub use impls::*;
+pub use lattice::*;
+
+/// The direction in which an analysis propagates information through the control flow graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Information flows from a statement to the statements that are executed after it.
+    Forward,
+    /// Information flows from a statement to the statements that are executed before it.
+    Backward,
+}
+
+/// A point in the body of a function.
+///
+/// A statement index equal to the number of statements of the block refers to its terminator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ProgramPoint {
+    pub block: BasicBlockIdx,
+    pub statement_index: usize,
+}
+
+/// A dataflow analysis.
+///
+/// Effects are always applied in the direction of the analysis. E.g.: for a backward analysis,
+/// the effect of a statement turns the state after the statement into the state before it.
+pub trait Analysis {
+    /// The type that holds the dataflow state at any given point in the body.
+    type Domain: Clone + JoinSemiLattice;
+
+    /// The direction of this analysis.
+    const DIRECTION: Direction;
+
+    /// The initial value of the dataflow state of every basic block.
+    ///
+    /// This must be the bottom value of the lattice, such that joining it with any other value
+    /// returns that value.
+    fn bottom_value(&self, body: &Body) -> Self::Domain;
+
+    /// Mutate the initial state of the start block of a forward analysis, e.g.: to mark the
+    /// function arguments as initialized.
+    ///
+    /// This isn't used by backward analyses.
+    fn initialize_start_block(&self, body: &Body, state: &mut Self::Domain) {
+        let _ = (body, state);
+    }
+
+    /// Update the state with the effect of a statement.
+    fn apply_statement_effect(
+        &mut self,
+        state: &mut Self::Domain,
+        statement: &Statement,
+        point: ProgramPoint,
+    );
+
+    /// Update the state with the effect of a terminator.
+    ///
+    /// The effect of a call on its destination place should be applied in
+    /// [`Analysis::apply_call_return_effect`] instead, since it only happens if the call returns.
+    fn apply_terminator_effect(
+        &mut self,
+        state: &mut Self::Domain,
+        terminator: &Terminator,
+        point: ProgramPoint,
+    );
+
+    /// Update the state with the effect of a successful return from the call that terminates the
+    /// basic block `block`.
+    ///
+    /// This is applied on the edge to the return target of the call only.
+    fn apply_call_return_effect(
+        &mut self,
+        state: &mut Self::Domain,
+        block: BasicBlockIdx,
+        destination: &Place,
+    ) {
+        let _ = (state, block, destination);
+    }
+
+    /// Run this analysis on the given body until the dataflow state of every basic block reaches
+    /// a fixpoint.
+    fn iterate_to_fixpoint(mut self, body: &Body) -> Results<Self>
+    where
+        Self: Sized,
+    {
+        let num_blocks = body.blocks.len();
+        let mut entry_states = vec![self.bottom_value(body); num_blocks];
+        if Self::DIRECTION == Direction::Forward && num_blocks > 0 {
+            self.initialize_start_block(body, &mut entry_states[START_BLOCK]);
+        }
+
+        let predecessors = match Self::DIRECTION {
+            Direction::Forward => vec![],
+            Direction::Backward => predecessors(body),
+        };
+
+        // Visit the blocks in an order that is likely to propagate information quickly.
+        let mut worklist: VecDeque<BasicBlockIdx> = match Self::DIRECTION {
+            Direction::Forward => (0..num_blocks).collect(),
+            Direction::Backward => (0..num_blocks).rev().collect(),
+        };
+        let mut in_worklist = vec![true; num_blocks];
+
+        while let Some(bb) = worklist.pop_front() {
+            in_worklist[bb] = false;
+            let mut state = entry_states[bb].clone();
+            apply_block_effects(&mut self, body, bb, &mut state);
+
+            let mut propagate = |target: BasicBlockIdx, state: &Self::Domain| {
+                if entry_states[target].join(state) && !in_worklist[target] {
+                    in_worklist[target] = true;
+                    worklist.push_back(target);
+                }
+            };
+            match Self::DIRECTION {
+                Direction::Forward => {
+                    let terminator = &body.blocks[bb].terminator;
+                    for target in terminator.successors() {
+                        match &terminator.kind {
+                            TerminatorKind::Call { destination, target: Some(ret), .. }
+                                if *ret == target =>
+                            {
+                                let mut state = state.clone();
+                                self.apply_call_return_effect(&mut state, bb, destination);
+                                propagate(target, &state);
+                            }
+                            _ => propagate(target, &state),
+                        }
+                    }
+                }
+                Direction::Backward => {
+                    for &pred in &predecessors[bb] {
+                        match &body.blocks[pred].terminator.kind {
+                            TerminatorKind::Call { destination, target: Some(ret), .. }
+                                if *ret == bb =>
+                            {
+                                let mut state = state.clone();
+                                self.apply_call_return_effect(&mut state, pred, destination);
+                                propagate(pred, &state);
+                            }
+                            _ => propagate(pred, &state),
+                        }
+                    }
+                }
+            }
+        }
+
+        Results { analysis: self, entry_states }
+    }
+}
+
+/// Apply the effects of all statements and the terminator of a basic block, in the direction of
+/// the analysis.
+fn apply_block_effects<A: Analysis>(
+    analysis: &mut A,
+    body: &Body,
+    block: BasicBlockIdx,
+    state: &mut A::Domain,
+) {
+    let statements = &body.blocks[block].statements;
+    let terminator = &body.blocks[block].terminator;
+    let terminator_point = ProgramPoint { block, statement_index: statements.len() };
+    match A::DIRECTION {
+        Direction::Forward => {
+            for (statement_index, statement) in statements.iter().enumerate() {
+                let point = ProgramPoint { block, statement_index };
+                analysis.apply_statement_effect(state, statement, point);
+            }
+            analysis.apply_terminator_effect(state, terminator, terminator_point);
+        }
+        Direction::Backward => {
+            analysis.apply_terminator_effect(state, terminator, terminator_point);
+            for (statement_index, statement) in statements.iter().enumerate().rev() {
+                let point = ProgramPoint { block, statement_index };
+                analysis.apply_statement_effect(state, statement, point);
+            }
+        }
+    }
+}
+
+/// The predecessors of every basic block.
+fn predecessors(body: &Body) -> Vec<Vec<BasicBlockIdx>> {
+    let mut predecessors = vec![vec![]; body.blocks.len()];
+    for (bb, block) in body.blocks.iter().enumerate() {
+        for target in block.terminator.successors() {
+            if !predecessors[target].contains(&bb) {
+                predecessors[target].push(bb);
+            }
+        }
+    }
+    predecessors
+}
+
+/// The result of a dataflow analysis.
+pub struct Results<A: Analysis> {
+    analysis: A,
+    /// The state of every basic block at its entry in the direction of the analysis. I.e.: before
+    /// its first statement for a forward analysis and after its terminator for a backward one.
+    entry_states: Vec<A::Domain>,
+}
+
+impl<A: Analysis> Results<A> {
+    /// The analysis that produced these results.
+    pub fn analysis(&self) -> &A {
+        &self.analysis
+    }
+
+    /// The state at the entry of a basic block in the direction of the analysis. I.e.: before its
+    /// first statement for a forward analysis and after its terminator for a backward one.
+    pub fn entry_state(&self, block: BasicBlockIdx) -> &A::Domain {
+        &self.entry_states[block]
+    }
+
+    /// Create a cursor to inspect the state at any point of the body these results were computed
+    /// for.
+    pub fn into_cursor(self, body: &Body) -> ResultsCursor<'_, A> {
+        let state = self.analysis.bottom_value(body);
+        ResultsCursor { body, results: self, state }
+    }
+}
+
+/// Allows inspecting the dataflow state at any point of a body.
+///
+/// Seeking recomputes the state starting from the entry state of the basic block.
+pub struct ResultsCursor<'body, A: Analysis> {
+    body: &'body Body,
+    results: Results<A>,
+    state: A::Domain,
+}
+
+impl<'body, A: Analysis> ResultsCursor<'body, A> {
+    /// The body being analyzed.
+    pub fn body(&self) -> &'body Body {
+        self.body
+    }
+
+    /// The underlying results of the analysis.
+    pub fn results(&self) -> &Results<A> {
+        &self.results
+    }
+
+    /// The state at the point the cursor was last moved to.
+    pub fn get(&self) -> &A::Domain {
+        &self.state
+    }
+
+    /// Move the cursor to the state right before the statement or terminator at `point` is
+    /// executed.
+    pub fn seek_before(&mut self, point: ProgramPoint) {
+        match A::DIRECTION {
+            Direction::Forward => self.seek_forward(point, false),
+            Direction::Backward => self.seek_backward(point, true),
+        }
+    }
+
+    /// Move the cursor to the state right after the statement or terminator at `point` is
+    /// executed.
+    ///
+    /// After a call terminator, this doesn't include the effect of the call returning, as it
+    /// depends on the edge being taken.
+    pub fn seek_after(&mut self, point: ProgramPoint) {
+        match A::DIRECTION {
+            Direction::Forward => self.seek_forward(point, true),
+            Direction::Backward => self.seek_backward(point, false),
+        }
+    }
+
+    /// Move the cursor to the entry of a basic block in the direction of the analysis.
+    pub fn seek_to_block_entry(&mut self, block: BasicBlockIdx) {
+        self.state.clone_from(&self.results.entry_states[block]);
+    }
+
+    fn seek_forward(&mut self, point: ProgramPoint, include_point: bool) {
+        self.seek_to_block_entry(point.block);
+        let block = &self.body.blocks[point.block];
+        let end = if include_point { point.statement_index + 1 } else { point.statement_index };
+        let analysis = &mut self.results.analysis;
+        for (statement_index, statement) in block.statements.iter().enumerate().take(end) {
+            let point = ProgramPoint { block: point.block, statement_index };
+            analysis.apply_statement_effect(&mut self.state, statement, point);
+        }
+        let terminator_index = block.statements.len();
+        if end > terminator_index {
+            let point = ProgramPoint { block: point.block, statement_index: terminator_index };
+            analysis.apply_terminator_effect(&mut self.state, &block.terminator, point);
+        }
+    }
+
+    fn seek_backward(&mut self, point: ProgramPoint, include_point: bool) {
+        self.seek_to_block_entry(point.block);
+        let block = &self.body.blocks[point.block];
+        let start = if include_point { point.statement_index } else { point.statement_index + 1 };
+        let analysis = &mut self.results.analysis;
+        let terminator_index = block.statements.len();
+        if start <= terminator_index {
+            let point = ProgramPoint { block: point.block, statement_index: terminator_index };
+            analysis.apply_terminator_effect(&mut self.state, &block.terminator, point);
+        }
+        for (statement_index, statement) in block.statements.iter().enumerate().skip(start).rev() {
+            let point = ProgramPoint { block: point.block, statement_index };
+            analysis.apply_statement_effect(&mut self.state, statement, point);
+        }
+    }
+}
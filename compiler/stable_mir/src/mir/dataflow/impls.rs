@@ -0,0 +1,285 @@
+//! Dataflow analyses that are commonly needed by tools.
+//!
+//! These analyses track whole locals. A local is considered to be affected by an operation on any
+//! of its fields, but not by operations on places that are reached through a dereference.
+
+use super::{Analysis, Direction, LocalSet, ProgramPoint};
+use crate::mir::visit::{Location, PlaceContext};
+use crate::mir::{
+    BasicBlockIdx, Body, InlineAsmOperand, Local, MirVisitor, Operand, Place, ProjectionElem,
+    Rvalue, Statement, StatementKind, Terminator, TerminatorKind,
+};
+
+/// The local that is affected by writing to or borrowing `place`, if any.
+fn local_without_deref(place: &Place) -> Option<Local> {
+    if place.projection.iter().any(|elem| matches!(elem, ProjectionElem::Deref)) {
+        None
+    } else {
+        Some(place.local)
+    }
+}
+
+/// A backward analysis that computes the locals that may be live, i.e.: the locals whose current
+/// value may be read later.
+///
+/// A local is defined only if it is fully overwritten, so assigning to a field of a local keeps
+/// it live.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MaybeLiveLocals;
+
+impl Analysis for MaybeLiveLocals {
+    type Domain = LocalSet;
+
+    const DIRECTION: Direction = Direction::Backward;
+
+    fn bottom_value(&self, body: &Body) -> LocalSet {
+        LocalSet::new_empty(body.locals().len())
+    }
+
+    fn apply_statement_effect(
+        &mut self,
+        state: &mut LocalSet,
+        statement: &Statement,
+        _point: ProgramPoint,
+    ) {
+        LivenessTransfer(state).visit_statement(statement, Location(statement.span));
+    }
+
+    fn apply_terminator_effect(
+        &mut self,
+        state: &mut LocalSet,
+        terminator: &Terminator,
+        _point: ProgramPoint,
+    ) {
+        LivenessTransfer(state).visit_terminator(terminator, Location(terminator.span));
+    }
+
+    fn apply_call_return_effect(
+        &mut self,
+        state: &mut LocalSet,
+        _block: BasicBlockIdx,
+        destination: &Place,
+    ) {
+        if destination.projection.is_empty() {
+            state.remove(destination.local);
+        } else {
+            gen_place_uses(state, destination);
+        }
+    }
+}
+
+/// Mark all locals that are read to access `place` as live.
+fn gen_place_uses(state: &mut LocalSet, place: &Place) {
+    state.insert(place.local);
+    for elem in &place.projection {
+        if let ProjectionElem::Index(local) = elem {
+            state.insert(*local);
+        }
+    }
+}
+
+struct LivenessTransfer<'a>(&'a mut LocalSet);
+
+impl MirVisitor for LivenessTransfer<'_> {
+    fn visit_statement(&mut self, stmt: &Statement, location: Location) {
+        match &stmt.kind {
+            // The assigned local is dead before the assignment, unless it is used by the rvalue.
+            StatementKind::Assign(place, rvalue) if place.projection.is_empty() => {
+                self.0.remove(place.local);
+                self.visit_rvalue(rvalue, location);
+            }
+            StatementKind::Deinit(place) if place.projection.is_empty() => {
+                self.0.remove(place.local);
+            }
+            StatementKind::StorageLive(_) | StatementKind::StorageDead(_) => {}
+            _ => self.super_statement(stmt, location),
+        }
+    }
+
+    fn visit_terminator(&mut self, term: &Terminator, location: Location) {
+        match &term.kind {
+            // The destination is handled by `apply_call_return_effect`.
+            TerminatorKind::Call { func, args, destination: _, target: _, unwind: _ } => {
+                self.visit_operand(func, location);
+                for arg in args {
+                    self.visit_operand(arg, location);
+                }
+            }
+            _ => self.super_terminator(term, location),
+        }
+    }
+
+    fn visit_local(&mut self, local: &Local, _ptx: PlaceContext, _location: Location) {
+        self.0.insert(*local);
+    }
+}
+
+/// A forward analysis that computes the locals that may be initialized.
+///
+/// A local becomes initialized when it, or any of its fields, is assigned to. It is only
+/// considered uninitialized again once it is moved out of as a whole, dropped, or its storage is
+/// marked as dead.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MaybeInitializedLocals;
+
+impl Analysis for MaybeInitializedLocals {
+    type Domain = LocalSet;
+
+    const DIRECTION: Direction = Direction::Forward;
+
+    fn bottom_value(&self, body: &Body) -> LocalSet {
+        LocalSet::new_empty(body.locals().len())
+    }
+
+    fn initialize_start_block(&self, body: &Body, state: &mut LocalSet) {
+        for arg in 1..=body.arg_locals().len() {
+            state.insert(arg);
+        }
+    }
+
+    fn apply_statement_effect(
+        &mut self,
+        state: &mut LocalSet,
+        statement: &Statement,
+        _point: ProgramPoint,
+    ) {
+        InitTransfer(state).visit_statement(statement, Location(statement.span));
+    }
+
+    fn apply_terminator_effect(
+        &mut self,
+        state: &mut LocalSet,
+        terminator: &Terminator,
+        _point: ProgramPoint,
+    ) {
+        InitTransfer(state).visit_terminator(terminator, Location(terminator.span));
+    }
+
+    fn apply_call_return_effect(
+        &mut self,
+        state: &mut LocalSet,
+        _block: BasicBlockIdx,
+        destination: &Place,
+    ) {
+        if let Some(local) = local_without_deref(destination) {
+            state.insert(local);
+        }
+    }
+}
+
+struct InitTransfer<'a>(&'a mut LocalSet);
+
+impl MirVisitor for InitTransfer<'_> {
+    fn visit_statement(&mut self, stmt: &Statement, location: Location) {
+        // Operands are moved out of before the assignment happens.
+        self.super_statement(stmt, location);
+        match &stmt.kind {
+            StatementKind::Assign(place, _) => {
+                if let Some(local) = local_without_deref(place) {
+                    self.0.insert(local);
+                }
+            }
+            StatementKind::Deinit(place) if place.projection.is_empty() => {
+                self.0.remove(place.local);
+            }
+            StatementKind::StorageLive(local) | StatementKind::StorageDead(local) => {
+                self.0.remove(*local);
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_terminator(&mut self, term: &Terminator, location: Location) {
+        self.super_terminator(term, location);
+        match &term.kind {
+            TerminatorKind::Drop { place, target: _, unwind: _ } if place.projection.is_empty() => {
+                self.0.remove(place.local);
+            }
+            TerminatorKind::InlineAsm { operands, .. } => {
+                for InlineAsmOperand { out_place, .. } in operands {
+                    if let Some(local) = out_place.as_ref().and_then(local_without_deref) {
+                        self.0.insert(local);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_operand(&mut self, operand: &Operand, _location: Location) {
+        match operand {
+            Operand::Move(place) if place.projection.is_empty() => {
+                self.0.remove(place.local);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A forward analysis that computes the locals that may be borrowed, i.e.: the locals that may be
+/// accessed through a reference or a raw pointer.
+///
+/// A local stops being borrowed only once its storage is marked as dead.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MaybeBorrowedLocals;
+
+impl Analysis for MaybeBorrowedLocals {
+    type Domain = LocalSet;
+
+    const DIRECTION: Direction = Direction::Forward;
+
+    fn bottom_value(&self, body: &Body) -> LocalSet {
+        LocalSet::new_empty(body.locals().len())
+    }
+
+    fn apply_statement_effect(
+        &mut self,
+        state: &mut LocalSet,
+        statement: &Statement,
+        _point: ProgramPoint,
+    ) {
+        BorrowTransfer(state).visit_statement(statement, Location(statement.span));
+    }
+
+    fn apply_terminator_effect(
+        &mut self,
+        state: &mut LocalSet,
+        terminator: &Terminator,
+        _point: ProgramPoint,
+    ) {
+        BorrowTransfer(state).visit_terminator(terminator, Location(terminator.span));
+    }
+}
+
+struct BorrowTransfer<'a>(&'a mut LocalSet);
+
+impl MirVisitor for BorrowTransfer<'_> {
+    fn visit_statement(&mut self, stmt: &Statement, location: Location) {
+        self.super_statement(stmt, location);
+        if let StatementKind::StorageDead(local) = &stmt.kind {
+            self.0.remove(*local);
+        }
+    }
+
+    fn visit_terminator(&mut self, term: &Terminator, location: Location) {
+        self.super_terminator(term, location);
+        // Drop glue takes a mutable reference to the dropped place.
+        if let TerminatorKind::Drop { place, target: _, unwind: _ } = &term.kind {
+            if let Some(local) = local_without_deref(place) {
+                self.0.insert(local);
+            }
+        }
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &Rvalue, location: Location) {
+        self.super_rvalue(rvalue, location);
+        match rvalue {
+            Rvalue::Ref(_, _, place) | Rvalue::AddressOf(_, place) => {
+                if let Some(local) = local_without_deref(place) {
+                    self.0.insert(local);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Lattices used as the domain of dataflow analyses.
+
+use std::fmt::{self, Debug};
+
+use crate::mir::Local;
+
+/// A partially ordered set that has a least upper bound for any pair of elements in the set.
+pub trait JoinSemiLattice {
+    /// Compute the least upper bound of `self` and `other` and store it in `self`.
+    ///
+    /// Return `true` if `self` changed.
+    fn join(&mut self, other: &Self) -> bool;
+}
+
+impl JoinSemiLattice for bool {
+    fn join(&mut self, other: &Self) -> bool {
+        let changed = !*self && *other;
+        *self |= *other;
+        changed
+    }
+}
+
+/// A set of locals, where the join of two sets is their union.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct LocalSet {
+    /// The number of locals that can be stored in this set.
+    domain_size: usize,
+    words: Vec<u64>,
+}
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+impl LocalSet {
+    /// Create an empty set that can hold the locals `0..domain_size`.
+    pub fn new_empty(domain_size: usize) -> Self {
+        LocalSet { domain_size, words: vec![0; domain_size.div_ceil(WORD_BITS)] }
+    }
+
+    /// The number of locals that can be stored in this set.
+    pub fn domain_size(&self) -> usize {
+        self.domain_size
+    }
+
+    /// Whether `local` is in the set.
+    pub fn contains(&self, local: Local) -> bool {
+        assert!(local < self.domain_size, "local `{local}` out of bounds");
+        self.words[local / WORD_BITS] & (1 << (local % WORD_BITS)) != 0
+    }
+
+    /// Insert `local` in the set. Return `true` if the set changed.
+    pub fn insert(&mut self, local: Local) -> bool {
+        assert!(local < self.domain_size, "local `{local}` out of bounds");
+        let word = &mut self.words[local / WORD_BITS];
+        let old = *word;
+        *word |= 1 << (local % WORD_BITS);
+        *word != old
+    }
+
+    /// Remove `local` from the set. Return `true` if the set changed.
+    pub fn remove(&mut self, local: Local) -> bool {
+        assert!(local < self.domain_size, "local `{local}` out of bounds");
+        let word = &mut self.words[local / WORD_BITS];
+        let old = *word;
+        *word &= !(1 << (local % WORD_BITS));
+        *word != old
+    }
+
+    /// Add all locals of `other` to `self`. Return `true` if `self` changed.
+    pub fn union(&mut self, other: &LocalSet) -> bool {
+        assert_eq!(self.domain_size, other.domain_size);
+        let mut changed = false;
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            let old = *word;
+            *word |= other;
+            changed |= *word != old;
+        }
+        changed
+    }
+
+    /// Whether the set contains no locals.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    /// Iterate over the locals in the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = Local> + '_ {
+        (0..self.domain_size).filter(|local| self.contains(*local))
+    }
+}
+
+impl JoinSemiLattice for LocalSet {
+    fn join(&mut self, other: &Self) -> bool {
+        self.union(other)
+    }
+}
+
+impl Debug for LocalSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("{")?;
+        for (idx, local) in self.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "_{local}")?;
+        }
+        f.write_str("}")
+    }
+}
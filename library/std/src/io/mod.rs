@@ -315,7 +315,9 @@
 pub use self::stdio::{_eprint, _print};
 #[unstable(feature = "internal_output_capture", issue = "none")]
 #[doc(no_inline, hidden)]
-pub use self::stdio::{set_output_capture, try_set_output_capture};
+pub use self::stdio::{
+    set_error_capture, set_output_capture, try_set_error_capture, try_set_output_capture,
+};
 #[stable(feature = "rust1", since = "1.0.0")]
 pub use self::{
     buffered::{BufReader, BufWriter, IntoInnerError, LineWriter},
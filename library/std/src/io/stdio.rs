@@ -14,7 +14,7 @@
 use crate::sync::atomic::{AtomicBool, Ordering};
 use crate::sync::{Arc, Mutex, MutexGuard, OnceLock, ReentrantLock, ReentrantLockGuard};
 use crate::sys::stdio;
-use crate::thread::AccessError;
+use crate::thread::{AccessError, LocalKey};
 
 type LocalStream = Arc<Mutex<Vec<u8>>>;
 
@@ -22,10 +22,16 @@
     /// Used by the test crate to capture the output of the print macros and panics.
     static OUTPUT_CAPTURE: Cell<Option<LocalStream>> = {
         Cell::new(None)
+    };
+
+    /// Used by the test crate to capture the output of `eprint!` and panics
+    /// separately. If it isn't set, that output goes to `OUTPUT_CAPTURE`.
+    static ERROR_CAPTURE: Cell<Option<LocalStream>> = {
+        Cell::new(None)
     }
 }
 
-/// Flag to indicate OUTPUT_CAPTURE is used.
+/// Flag to indicate OUTPUT_CAPTURE or ERROR_CAPTURE is used.
 ///
 /// If it is None and was never set on any thread, this flag is set to false,
 /// and OUTPUT_CAPTURE can be safely ignored on all threads, saving some time
@@ -1091,8 +1097,47 @@ pub fn try_set_output_capture(
     OUTPUT_CAPTURE.try_with(move |slot| slot.replace(sink))
 }
 
-/// Writes `args` to the capture buffer if enabled and possible, or `global_s`
-/// otherwise. `label` identifies the stream in a panic message.
+/// Sets the thread-local capture buffer for the standard error stream and
+/// returns the old one. While it is set, output to stderr no longer goes to
+/// the buffer set by `set_output_capture`.
+#[unstable(
+    feature = "internal_output_capture",
+    reason = "this function is meant for use in the test crate \
+        and may disappear in the future",
+    issue = "none"
+)]
+#[doc(hidden)]
+pub fn set_error_capture(sink: Option<LocalStream>) -> Option<LocalStream> {
+    try_set_error_capture(sink).expect(
+        "cannot access a Thread Local Storage value \
+         during or after destruction",
+    )
+}
+
+/// Tries to set the thread-local capture buffer for the standard error stream
+/// and returns the old one. Like `try_set_output_capture`, it's used in panic
+/// handling.
+#[unstable(
+    feature = "internal_output_capture",
+    reason = "this function is meant for use in the test crate \
+    and may disappear in the future",
+    issue = "none"
+)]
+#[doc(hidden)]
+pub fn try_set_error_capture(
+    sink: Option<LocalStream>,
+) -> Result<Option<LocalStream>, AccessError> {
+    if sink.is_none() && !OUTPUT_CAPTURE_USED.load(Ordering::Relaxed) {
+        // ERROR_CAPTURE is definitely None since OUTPUT_CAPTURE_USED is false.
+        return Ok(None);
+    }
+    OUTPUT_CAPTURE_USED.store(true, Ordering::Relaxed);
+    ERROR_CAPTURE.try_with(move |slot| slot.replace(sink))
+}
+
+/// Writes `args` to the first of the `captures` buffers that is enabled and
+/// possible to use, or `global_s` otherwise. `label` identifies the stream in a
+/// panic message.
 ///
 /// This function is used to print error messages, so it takes extra
 /// care to avoid causing a panic when `OUTPUT_CAPTURE` is unusable.
@@ -1104,11 +1149,15 @@ pub fn try_set_output_capture(
 ///
 /// Writing to non-blocking stdout/stderr can cause an error, which will lead
 /// this function to panic.
-fn print_to<T>(args: fmt::Arguments<'_>, global_s: fn() -> T, label: &str)
-where
+fn print_to<T>(
+    args: fmt::Arguments<'_>,
+    captures: &[&'static Capture],
+    global_s: fn() -> T,
+    label: &str,
+) where
     T: Write,
 {
-    if print_to_buffer_if_capture_used(args) {
+    if print_to_buffer_if_capture_used(args, captures) {
         // Successfully wrote to capture buffer.
         return;
     }
@@ -1118,24 +1167,34 @@ fn print_to<T>(args: fmt::Arguments<'_>, global_s: fn() -> T, label: &str)
     }
 }
 
-fn print_to_buffer_if_capture_used(args: fmt::Arguments<'_>) -> bool {
+type Capture = LocalKey<Cell<Option<LocalStream>>>;
+
+/// The capture buffers used for stderr, in order of preference.
+static STDERR_CAPTURES: [&Capture; 2] = [&ERROR_CAPTURE, &OUTPUT_CAPTURE];
+
+fn print_to_buffer_if_capture_used(
+    args: fmt::Arguments<'_>,
+    captures: &[&'static Capture],
+) -> bool {
     OUTPUT_CAPTURE_USED.load(Ordering::Relaxed)
-        && OUTPUT_CAPTURE.try_with(|s| {
-            // Note that we completely remove a local sink to write to in case
-            // our printing recursively panics/prints, so the recursive
-            // panic/print goes to the global sink instead of our local sink.
-            s.take().map(|w| {
-                let _ = w.lock().unwrap_or_else(|e| e.into_inner()).write_fmt(args);
-                s.set(Some(w));
-            })
-        }) == Ok(Some(()))
+        && captures.iter().any(|capture| {
+            capture.try_with(|s| {
+                // Note that we completely remove a local sink to write to in case
+                // our printing recursively panics/prints, so the recursive
+                // panic/print goes to the global sink instead of our local sink.
+                s.take().map(|w| {
+                    let _ = w.lock().unwrap_or_else(|e| e.into_inner()).write_fmt(args);
+                    s.set(Some(w));
+                })
+            }) == Ok(Some(()))
+        })
 }
 
 /// Used by impl Termination for Result to print error after `main` or a test
 /// has returned. Should avoid panicking, although we can't help it if one of
 /// the Display impls inside args decides to.
 pub(crate) fn attempt_print_to_stderr(args: fmt::Arguments<'_>) {
-    if print_to_buffer_if_capture_used(args) {
+    if print_to_buffer_if_capture_used(args, &STDERR_CAPTURES) {
         return;
     }
 
@@ -1223,7 +1282,7 @@ fn is_terminal(&self) -> bool {
 #[doc(hidden)]
 #[cfg(not(test))]
 pub fn _print(args: fmt::Arguments<'_>) {
-    print_to(args, stdout, "stdout");
+    print_to(args, &[&OUTPUT_CAPTURE], stdout, "stdout");
 }
 
 #[unstable(
@@ -1234,7 +1293,7 @@ pub fn _print(args: fmt::Arguments<'_>) {
 #[doc(hidden)]
 #[cfg(not(test))]
 pub fn _eprint(args: fmt::Arguments<'_>) {
-    print_to(args, stderr, "stderr");
+    print_to(args, &STDERR_CAPTURES, stderr, "stderr");
 }
 
 #[cfg(test)]
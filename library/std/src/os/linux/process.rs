@@ -11,6 +11,7 @@
 #[cfg(not(doc))]
 use crate::sys::{fd::FileDesc, linux::pidfd::PidFd as InnerPidFd};
 use crate::sys_common::{AsInner, AsInnerMut, FromInner, IntoInner};
+use crate::time::Duration;
 
 #[cfg(doc)]
 struct InnerPidFd;
@@ -84,6 +85,41 @@ pub fn wait(&self) -> Result<ExitStatus> {
     pub fn try_wait(&self) -> Result<Option<ExitStatus>> {
         Ok(self.inner.try_wait()?.map(FromInner::from_inner))
     }
+
+    /// Waits for the child to exit for at most `timeout`, returning the status
+    /// that it exited with, or `None` if it is still running after `timeout`.
+    ///
+    /// The wait is done on the pidfd rather than on the pid, so a concurrent
+    /// `waitpid` for another child can't reap this one, and the pid of this
+    /// child can't be recycled while waiting. Like [`PidFd::wait`], it returns
+    /// an error if the child has already been reaped.
+    ///
+    /// ```no_run
+    /// #![feature(linux_pidfd)]
+    /// use std::os::linux::process::{ChildExt, CommandExt};
+    /// use std::process::Command;
+    /// use std::time::Duration;
+    ///
+    /// let child = Command::new("sleep").arg("10").create_pidfd(true).spawn()?;
+    /// let pidfd = child.pidfd()?;
+    /// if pidfd.wait_timeout(Duration::from_secs(1))?.is_none() {
+    ///     pidfd.kill()?;
+    ///     pidfd.wait()?;
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<Option<ExitStatus>> {
+        Ok(self.inner.wait_timeout(timeout)?.map(FromInner::from_inner))
+    }
+
+    /// Sends `signal` to the child.
+    ///
+    /// Unlike sending a signal to the pid of the child, this can't reach
+    /// another process that reused the pid after the child was reaped. An
+    /// error is returned instead.
+    pub fn send_signal(&self, signal: i32) -> Result<()> {
+        self.inner.send_signal(signal)
+    }
 }
 
 impl AsInner<InnerPidFd> for PidFd {
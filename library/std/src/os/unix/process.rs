@@ -200,6 +200,44 @@ fn arg0<S>(&mut self, arg: S) -> &mut process::Command
     /// ```
     #[stable(feature = "process_set_process_group", since = "1.64.0")]
     fn process_group(&mut self, pgroup: i32) -> &mut process::Command;
+
+    /// Sets whether the child process starts a new session, by calling
+    /// `setsid` in the child process. Failure in the `setsid` call will cause
+    /// the spawn to fail.
+    ///
+    /// The child becomes the leader of a new session and of a new process
+    /// group, both of which have the ID of the child process, and it has no
+    /// controlling terminal. Unlike with [`process_group`], the child is then
+    /// also detached from the terminal of the parent, so it doesn't receive the
+    /// signals sent by the terminal to the session of the parent, like `SIGHUP`
+    /// when the terminal is closed.
+    ///
+    /// The whole group can be signalled with
+    /// [`ChildExt::send_signal_to_process_group`].
+    ///
+    /// If [`process_group`] is also used, the child is moved to that process
+    /// group after creating the new session.
+    ///
+    /// Spawning the child fails with [`io::ErrorKind::Unsupported`] on
+    /// platforms that can't start a new session, like Fuchsia and VxWorks.
+    ///
+    /// [`process_group`]: CommandExt::process_group
+    ///
+    /// ```no_run
+    /// #![feature(process_setsid)]
+    /// use std::os::unix::process::CommandExt;
+    /// use std::process::Command;
+    ///
+    /// Command::new("sleep")
+    ///     .arg("10")
+    ///     .setsid(true)
+    ///     .spawn()?
+    ///     .wait()?;
+    /// #
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[unstable(feature = "process_setsid", issue = "105376")]
+    fn setsid(&mut self, setsid: bool) -> &mut process::Command;
 }
 
 #[stable(feature = "rust1", since = "1.0.0")]
@@ -245,6 +283,83 @@ fn process_group(&mut self, pgroup: i32) -> &mut process::Command {
         self.as_inner_mut().pgroup(pgroup);
         self
     }
+
+    fn setsid(&mut self, setsid: bool) -> &mut process::Command {
+        self.as_inner_mut().setsid(setsid);
+        self
+    }
+}
+
+/// Unix-specific extensions to [`process::Child`].
+///
+/// This trait is sealed: it cannot be implemented outside the standard library.
+/// This is so that future additional methods are not breaking changes.
+#[unstable(feature = "unix_send_signal", issue = "none")]
+pub trait ChildExt: Sealed {
+    /// Sends `signal` to the child process, like the `kill` system call.
+    ///
+    /// Like [`Child::kill`], this does nothing once the child has been waited
+    /// for, since its process ID may have been reused by another process.
+    ///
+    /// [`Child::kill`]: process::Child::kill
+    ///
+    /// ```no_run
+    /// #![feature(unix_send_signal)]
+    /// use std::os::unix::process::ChildExt;
+    /// use std::process::Command;
+    ///
+    /// const SIGTERM: i32 = 15;
+    ///
+    /// let mut child = Command::new("sleep").arg("10").spawn()?;
+    /// child.send_signal(SIGTERM)?;
+    /// child.wait()?;
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[unstable(feature = "unix_send_signal", issue = "none")]
+    fn send_signal(&self, signal: i32) -> io::Result<()>;
+
+    /// Sends `signal` to every process in the process group led by the child,
+    /// like the `killpg` system call.
+    ///
+    /// The child leads a process group if it was spawned with
+    /// [`CommandExt::process_group`] set to `0`, or with
+    /// [`CommandExt::setsid`]. Processes started by the child stay in its
+    /// group unless they move to another one, so this can stop a whole tree
+    /// of processes.
+    ///
+    /// The signal is sent even after the child has been waited for, since the
+    /// rest of the group may still be running. The ID of the group can't be
+    /// reused while the group has members, and an error is returned once the
+    /// group is gone.
+    ///
+    /// ```no_run
+    /// #![feature(unix_send_signal)]
+    /// use std::os::unix::process::{ChildExt, CommandExt};
+    /// use std::process::Command;
+    ///
+    /// const SIGKILL: i32 = 9;
+    ///
+    /// let mut child = Command::new("sh")
+    ///     .args(["-c", "sleep 10 & sleep 10"])
+    ///     .process_group(0)
+    ///     .spawn()?;
+    /// child.send_signal_to_process_group(SIGKILL)?;
+    /// child.wait()?;
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    #[unstable(feature = "unix_send_signal", issue = "none")]
+    fn send_signal_to_process_group(&self, signal: i32) -> io::Result<()>;
+}
+
+#[unstable(feature = "unix_send_signal", issue = "none")]
+impl ChildExt for process::Child {
+    fn send_signal(&self, signal: i32) -> io::Result<()> {
+        self.as_inner().send_signal(signal)
+    }
+
+    fn send_signal_to_process_group(&self, signal: i32) -> io::Result<()> {
+        self.as_inner().send_signal_to_process_group(signal)
+    }
 }
 
 /// Unix-specific extensions to [`process::ExitStatus`] and
@@ -14,11 +14,11 @@
 // make sure to use the stderr output configured
 // by libtest in the real copy of std
 #[cfg(test)]
-use realstd::io::try_set_output_capture;
+use realstd::io::{try_set_error_capture, try_set_output_capture};
 
 use crate::any::Any;
 #[cfg(not(test))]
-use crate::io::try_set_output_capture;
+use crate::io::{try_set_error_capture, try_set_output_capture};
 use crate::mem::{self, ManuallyDrop};
 use crate::panic::{BacktraceStyle, PanicHookInfo};
 use crate::sync::atomic::{AtomicBool, Ordering};
@@ -286,7 +286,10 @@ fn default_hook(info: &PanicHookInfo<'_>) {
         }
     };
 
-    if let Ok(Some(local)) = try_set_output_capture(None) {
+    if let Ok(Some(local)) = try_set_error_capture(None) {
+        write(&mut *local.lock().unwrap_or_else(|e| e.into_inner()));
+        try_set_error_capture(Some(local)).ok();
+    } else if let Ok(Some(local)) = try_set_output_capture(None) {
         write(&mut *local.lock().unwrap_or_else(|e| e.into_inner()));
         try_set_output_capture(Some(local)).ok();
     } else if let Some(mut out) = panic_output() {
@@ -162,6 +162,7 @@
 #[stable(feature = "command_access", since = "1.57.0")]
 pub use crate::sys_common::process::CommandEnvs;
 use crate::sys_common::{AsInner, AsInnerMut, FromInner, IntoInner};
+use crate::time::{Duration, Instant};
 use crate::{fmt, fs, str};
 
 /// Representation of a running or exited child process.
@@ -2216,6 +2217,84 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         Ok(self.handle.try_wait()?.map(ExitStatus))
     }
 
+    /// Waits for the child to exit for at most `timeout`, collecting its exit
+    /// status if it did.
+    ///
+    /// If the child exits within `timeout`, then `Ok(Some(status))` is
+    /// returned, and on Unix the process ID is reaped. If it is still running
+    /// once `timeout` has elapsed then `Ok(None)` is returned. If an error
+    /// occurs, then that error is returned. Like [`try_wait`], this function
+    /// keeps returning the exit status once the child has exited.
+    ///
+    /// The calling thread is blocked while waiting. On Linux this waits on a
+    /// pidfd, and on Windows on the process handle; on other Unix platforms
+    /// the child is polled at increasing intervals.
+    ///
+    /// Note that unlike `wait`, this function will not attempt to drop stdin,
+    /// so the child can still be written to after a timeout.
+    ///
+    /// [`try_wait`]: Child::try_wait
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(child_wait_timeout)]
+    /// use std::process::Command;
+    /// use std::time::Duration;
+    ///
+    /// let mut child = Command::new("sleep").arg("60").spawn().unwrap();
+    ///
+    /// match child.wait_timeout(Duration::from_secs(5)) {
+    ///     Ok(Some(status)) => println!("exited with: {status}"),
+    ///     Ok(None) => {
+    ///         println!("still running after 5 seconds, killing it");
+    ///         child.kill().unwrap();
+    ///         child.wait().unwrap();
+    ///     }
+    ///     Err(e) => println!("error attempting to wait: {e}"),
+    /// }
+    /// ```
+    #[unstable(feature = "child_wait_timeout", issue = "none")]
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        Ok(self.handle.wait_timeout(timeout)?.map(ExitStatus))
+    }
+
+    /// Waits for the child to exit until `deadline` at most, collecting its
+    /// exit status if it did.
+    ///
+    /// This behaves like [`wait_timeout`] with the time remaining until
+    /// `deadline`, which is convenient when waiting on several children with
+    /// a shared time limit. A deadline in the past checks the child once,
+    /// like [`try_wait`].
+    ///
+    /// [`wait_timeout`]: Child::wait_timeout
+    /// [`try_wait`]: Child::try_wait
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// #![feature(child_wait_timeout)]
+    /// use std::process::Command;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let mut children = vec![
+    ///     Command::new("sleep").arg("1").spawn().unwrap(),
+    ///     Command::new("sleep").arg("60").spawn().unwrap(),
+    /// ];
+    ///
+    /// let deadline = Instant::now() + Duration::from_secs(5);
+    /// for child in &mut children {
+    ///     if child.wait_deadline(deadline).unwrap().is_none() {
+    ///         child.kill().unwrap();
+    ///         child.wait().unwrap();
+    ///     }
+    /// }
+    /// ```
+    #[unstable(feature = "child_wait_timeout", issue = "none")]
+    pub fn wait_deadline(&mut self, deadline: Instant) -> io::Result<Option<ExitStatus>> {
+        self.wait_timeout(deadline.saturating_duration_since(Instant::now()))
+    }
+
     /// Simultaneously waits for the child to exit and collect all remaining
     /// output on the stdout/stderr handles, returning an `Output`
     /// instance.
@@ -229,6 +229,28 @@ fn test_finish_twice() {
     assert!(prog.wait().unwrap().code() == Some(1));
 }
 
+#[test]
+#[cfg_attr(any(target_os = "vxworks"), ignore)]
+fn test_wait_timeout() {
+    use crate::time::{Duration, Instant};
+
+    let mut prog = if cfg!(target_os = "windows") {
+        Command::new("cmd").args(&["/C", "ping -n 60 127.0.0.1 > nul"]).spawn().unwrap()
+    } else {
+        shell_cmd().arg("-c").arg("sleep 60").spawn().unwrap()
+    };
+    let start = Instant::now();
+    assert!(prog.wait_timeout(Duration::from_millis(100)).unwrap().is_none());
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert!(prog.wait_deadline(Instant::now()).unwrap().is_none());
+
+    prog.kill().unwrap();
+    let status = prog.wait_timeout(Duration::from_secs(60)).unwrap().unwrap();
+    assert!(!status.success());
+    assert_eq!(prog.wait_timeout(Duration::ZERO).unwrap(), Some(status));
+    assert_eq!(prog.wait().unwrap(), status);
+}
+
 #[test]
 #[cfg_attr(any(target_os = "vxworks"), ignore)]
 fn test_wait_with_output_once() {
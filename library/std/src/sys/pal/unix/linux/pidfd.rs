@@ -4,6 +4,7 @@
 use crate::sys::pal::unix::fd::FileDesc;
 use crate::sys::process::ExitStatus;
 use crate::sys_common::{AsInner, FromInner, IntoInner};
+use crate::time::{Duration, Instant};
 
 #[cfg(test)]
 mod tests;
@@ -12,12 +13,23 @@
 pub(crate) struct PidFd(FileDesc);
 
 impl PidFd {
+    /// Opens a pidfd referring to the process `pid`.
+    pub fn open(pid: libc::pid_t) -> io::Result<PidFd> {
+        // pidfd_open sets CLOEXEC by default
+        let fd = cvt(unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) })?;
+        Ok(unsafe { PidFd::from_raw_fd(fd as RawFd) })
+    }
+
     pub fn kill(&self) -> io::Result<()> {
+        self.send_signal(libc::SIGKILL)
+    }
+
+    pub fn send_signal(&self, signal: i32) -> io::Result<()> {
         return cvt(unsafe {
             libc::syscall(
                 libc::SYS_pidfd_send_signal,
                 self.0.as_raw_fd(),
-                libc::SIGKILL,
+                signal,
                 crate::ptr::null::<()>(),
                 0,
             )
@@ -49,6 +61,34 @@ pub fn try_wait(&self) -> io::Result<Option<ExitStatus>> {
         }
         return Ok(Some(ExitStatus::from_waitid_siginfo(siginfo)));
     }
+
+    pub fn wait_timeout(&self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        // A pidfd becomes readable once the process has exited.
+        let deadline = Instant::now().checked_add(timeout);
+        let mut pollfd = libc::pollfd { fd: self.0.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        loop {
+            if let Some(status) = self.try_wait()? {
+                return Ok(Some(status));
+            }
+            // No deadline means waiting forever, which `-1` asks for.
+            let timeout_ms = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Ok(None);
+                    }
+                    remaining.as_nanos().div_ceil(1_000_000).min(libc::c_int::MAX as u128)
+                        as libc::c_int
+                }
+                None => -1,
+            };
+            match cvt(unsafe { libc::poll(&mut pollfd, 1, timeout_ms) }) {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
 }
 
 impl AsInner<FileDesc> for PidFd {
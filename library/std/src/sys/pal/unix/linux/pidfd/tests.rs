@@ -3,6 +3,7 @@
 use crate::os::linux::process::{ChildExt, CommandExt as _};
 use crate::os::unix::process::{CommandExt as _, ExitStatusExt};
 use crate::process::Command;
+use crate::time::{Duration, Instant};
 
 #[test]
 fn test_command_pidfd() {
@@ -86,6 +87,32 @@ fn test_pidfd() {
     assert_matches!(res, Err(e) if e.raw_os_error() == Some(libc::ESRCH));
 }
 
+#[test]
+fn test_pidfd_wait_timeout() {
+    if !probe_pidfd_support() {
+        return;
+    }
+
+    let child = Command::new("sleep")
+        .arg("1000")
+        .create_pidfd(true)
+        .spawn()
+        .expect("executing 'sleep' failed");
+    let fd = child.into_pidfd().unwrap();
+
+    let start = Instant::now();
+    assert_matches!(fd.wait_timeout(Duration::from_millis(100)), Ok(None));
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    fd.send_signal(libc::SIGTERM).expect("sending SIGTERM failed");
+    let status = fd.wait_timeout(Duration::from_secs(60)).expect("wait failed");
+    assert_eq!(status.and_then(|status| status.signal()), Some(libc::SIGTERM));
+
+    // The child has been reaped, so there's nothing left to wait for.
+    let res = fd.wait_timeout(Duration::ZERO);
+    assert_matches!(res, Err(e) if e.raw_os_error() == Some(libc::ECHILD));
+}
+
 fn probe_pidfd_support() -> bool {
     // pidfds require the pidfd_open syscall
     let our_pid = crate::process::id();
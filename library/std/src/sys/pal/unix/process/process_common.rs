@@ -14,6 +14,13 @@
 use crate::sys::pipe::{self, AnonPipe};
 use crate::sys_common::process::{CommandEnv, CommandEnvs};
 use crate::sys_common::{FromInner, IntoInner};
+#[cfg(not(any(
+    target_os = "fuchsia",
+    target_os = "espidf",
+    target_os = "horizon",
+    target_os = "vita"
+)))]
+use crate::time::{Duration, Instant};
 use crate::{fmt, io, ptr};
 
 cfg_if::cfg_if! {
@@ -104,6 +111,7 @@ pub struct Command {
     #[cfg(target_os = "linux")]
     create_pidfd: bool,
     pgroup: Option<pid_t>,
+    setsid: bool,
 }
 
 // Create a new type for argv, so that we can make it `Send` and `Sync`
@@ -196,6 +204,7 @@ pub fn new(program: &OsStr) -> Command {
             stdout: None,
             stderr: None,
             pgroup: None,
+            setsid: false,
         }
     }
 
@@ -221,6 +230,7 @@ pub fn new(program: &OsStr) -> Command {
             stderr: None,
             create_pidfd: false,
             pgroup: None,
+            setsid: false,
         }
     }
 
@@ -259,6 +269,9 @@ pub fn groups(&mut self, groups: &[gid_t]) {
     pub fn pgroup(&mut self, pgroup: pid_t) {
         self.pgroup = Some(pgroup);
     }
+    pub fn setsid(&mut self, setsid: bool) {
+        self.setsid = setsid;
+    }
 
     #[cfg(target_os = "linux")]
     pub fn create_pidfd(&mut self, val: bool) {
@@ -331,6 +344,10 @@ pub fn get_groups(&self) -> Option<&[gid_t]> {
     pub fn get_pgroup(&self) -> Option<pid_t> {
         self.pgroup
     }
+    #[cfg_attr(target_os = "vita", allow(dead_code))]
+    pub fn get_setsid(&self) -> bool {
+        self.setsid
+    }
 
     pub fn get_closures(&mut self) -> &mut Vec<Box<dyn FnMut() -> io::Result<()> + Send + Sync>> {
         &mut self.closures
@@ -390,6 +407,41 @@ pub fn setup_io(
     }
 }
 
+/// Waits up to `timeout` for a child to exit by calling `try_wait` repeatedly,
+/// for when there is no way to block on the child with a timeout.
+#[cfg(not(any(
+    target_os = "fuchsia",
+    target_os = "espidf",
+    target_os = "horizon",
+    target_os = "vita"
+)))]
+pub fn wait_timeout_by_polling<T>(
+    timeout: Duration,
+    mut try_wait: impl FnMut() -> io::Result<Option<T>>,
+) -> io::Result<Option<T>> {
+    let deadline = Instant::now().checked_add(timeout);
+    // Short-lived children are noticed quickly, while the back-off keeps long
+    // waits from spinning.
+    let mut interval = Duration::from_millis(1);
+    loop {
+        if let Some(status) = try_wait()? {
+            return Ok(Some(status));
+        }
+        let sleep = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Ok(None);
+                }
+                remaining.min(interval)
+            }
+            None => interval,
+        };
+        crate::thread::sleep(sleep);
+        interval = (interval * 2).min(Duration::from_millis(50));
+    }
+}
+
 fn os2c(s: &OsStr, saw_nul: &mut bool) -> CString {
     CString::new(s.as_bytes()).unwrap_or_else(|_e| {
         *saw_nul = true;
@@ -572,6 +624,9 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             if self.pgroup.is_some() {
                 debug_command.field("pgroup", &self.pgroup);
             }
+            if self.setsid {
+                debug_command.field("setsid", &self.setsid);
+            }
 
             #[cfg(target_os = "linux")]
             {
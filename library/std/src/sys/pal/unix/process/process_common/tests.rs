@@ -134,6 +134,37 @@ fn test_process_group_no_posix_spawn() {
     }
 }
 
+#[test]
+#[cfg_attr(
+    any(
+        // See test_process_mask
+        target_os = "macos",
+        target_arch = "arm",
+        target_arch = "aarch64",
+        target_arch = "riscv64",
+    ),
+    ignore
+)]
+fn test_setsid() {
+    unsafe {
+        let mut cmd = Command::new(OsStr::new("cat"));
+        cmd.setsid(true);
+        cmd.stdin(Stdio::MakePipe);
+        cmd.stdout(Stdio::MakePipe);
+        let (mut cat, _pipes) = t!(cmd.spawn(Stdio::Null, true));
+
+        // The child leads both a new session and a new process group.
+        let pid = cat.id() as libc::pid_t;
+        assert_eq!(t!(cvt(libc::getsid(pid))), pid);
+        assert_eq!(t!(cvt(libc::getpgid(pid))), pid);
+        assert_ne!(t!(cvt(libc::getsid(0))), pid);
+
+        t!(cat.send_signal_to_process_group(libc::SIGINT));
+        let status = t!(cat.wait());
+        assert_eq!(status.signal(), Some(libc::SIGINT));
+    }
+}
+
 #[test]
 fn test_program_kind() {
     let vectors = &[
@@ -3,6 +3,7 @@
 use crate::num::NonZero;
 use crate::sys::process::process_common::*;
 use crate::sys::process::zircon::{zx_handle_t, Handle};
+use crate::time::Duration;
 use crate::{fmt, io, mem, ptr};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -23,6 +24,12 @@ pub fn spawn(
                 "nul byte found in provided data",
             ));
         }
+        if self.get_setsid() {
+            return Err(io::const_io_error!(
+                io::ErrorKind::Unsupported,
+                "starting a new session is not supported on Fuchsia",
+            ));
+        }
 
         let (ours, theirs) = self.setup_io(default, needs_stdin)?;
 
@@ -161,6 +168,14 @@ pub fn kill(&mut self) -> io::Result<()> {
         Ok(())
     }
 
+    pub fn send_signal(&self, _signal: i32) -> io::Result<()> {
+        Err(io::const_io_error!(io::ErrorKind::Unsupported, "signals are not supported on Fuchsia"))
+    }
+
+    pub fn send_signal_to_process_group(&self, _signal: i32) -> io::Result<()> {
+        Err(io::const_io_error!(io::ErrorKind::Unsupported, "signals are not supported on Fuchsia"))
+    }
+
     pub fn wait(&mut self) -> io::Result<ExitStatus> {
         use crate::sys::process::zircon::*;
 
@@ -229,6 +244,24 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         }
         Ok(Some(ExitStatus(proc_info.return_code)))
     }
+
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        use crate::sys::process::zircon::*;
+
+        // `zx_object_wait_one` takes a deadline on the monotonic clock.
+        let timeout = zx_time_t::try_from(timeout.as_nanos()).unwrap_or(ZX_TIME_INFINITE);
+        let deadline = unsafe { zx_clock_get_monotonic() }.saturating_add(timeout);
+        let status = unsafe {
+            zx_object_wait_one(self.handle.raw(), ZX_TASK_TERMINATED, deadline, ptr::null_mut())
+        };
+        match status {
+            x if x == ERR_TIMED_OUT => Ok(None),
+            _ => {
+                zx_cvt(status)?;
+                self.try_wait()
+            }
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
@@ -16,6 +16,7 @@
 #[cfg(target_os = "linux")]
 use crate::sys::pal::unix::linux::pidfd::PidFd;
 use crate::sys::process::process_common::*;
+use crate::time::Duration;
 use crate::{fmt, mem, sys};
 
 cfg_if::cfg_if! {
@@ -336,6 +337,12 @@ unsafe fn do_exec(
             cvt(libc::chdir(cwd.as_ptr()))?;
         }
 
+        // A new session is also a new process group led by the child, so this
+        // goes first in case the child is then moved to another group.
+        if self.get_setsid() {
+            cvt(libc::setsid())?;
+        }
+
         if let Some(pgroup) = self.get_pgroup() {
             cvt(libc::setpgid(0, pgroup))?;
         }
@@ -453,6 +460,8 @@ fn posix_spawn(
             || (self.env_saw_path() && !self.program_is_path())
             || !self.get_closures().is_empty()
             || self.get_groups().is_some()
+            // `POSIX_SPAWN_SETSID` isn't available on all of these platforms.
+            || self.get_setsid()
         {
             return Ok(None);
         }
@@ -924,6 +933,25 @@ pub fn kill(&mut self) -> io::Result<()> {
         cvt(unsafe { libc::kill(self.pid, libc::SIGKILL) }).map(drop)
     }
 
+    pub fn send_signal(&self, signal: i32) -> io::Result<()> {
+        // Same as `kill`, the pid may have been recycled after the child was reaped.
+        if self.status.is_some() {
+            return Ok(());
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(pid_fd) = self.pidfd.as_ref() {
+            return pid_fd.send_signal(signal);
+        }
+        cvt(unsafe { libc::kill(self.pid, signal) }).map(drop)
+    }
+
+    pub fn send_signal_to_process_group(&self, signal: i32) -> io::Result<()> {
+        // The group can outlive the child, and its ID can't be reused as a pid
+        // while it has members, so the signal is sent even after the child was
+        // reaped.
+        cvt(unsafe { libc::killpg(self.pid, signal) }).map(drop)
+    }
+
     pub fn wait(&mut self) -> io::Result<ExitStatus> {
         use crate::sys::cvt_r;
         if let Some(status) = self.status {
@@ -962,6 +990,34 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
             Ok(Some(ExitStatus::new(status)))
         }
     }
+
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        if let Some(status) = self.status {
+            return Ok(Some(status));
+        }
+        #[cfg(target_os = "linux")]
+        {
+            // Without a pidfd of our own, a temporary one still lets us sleep in
+            // poll until the child exits. The pid can't have been recycled since
+            // we haven't reaped the child yet.
+            let temporary_pidfd;
+            let pid_fd = match self.pidfd.as_ref() {
+                Some(pid_fd) => Some(pid_fd),
+                None => {
+                    temporary_pidfd = PidFd::open(self.pid).ok();
+                    temporary_pidfd.as_ref()
+                }
+            };
+            if let Some(pid_fd) = pid_fd {
+                let status = pid_fd.wait_timeout(timeout)?;
+                if let Some(status) = status {
+                    self.status = Some(status);
+                }
+                return Ok(status);
+            }
+        }
+        wait_timeout_by_polling(timeout, || self.try_wait())
+    }
 }
 
 /// Unix exit statuses
@@ -6,6 +6,7 @@
 use crate::sys::cvt;
 use crate::sys::pal::unix::thread;
 use crate::sys::process::process_common::*;
+use crate::time::Duration;
 use crate::{fmt, sys};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -27,6 +28,12 @@ pub fn spawn(
                 "nul byte found in provided data",
             ));
         }
+        if self.get_setsid() {
+            return Err(io::const_io_error!(
+                ErrorKind::Unsupported,
+                "starting a new session is not supported on this platform",
+            ));
+        }
         let (ours, theirs) = self.setup_io(default, needs_stdin)?;
         let mut p = Process { pid: 0, status: None };
 
@@ -156,6 +163,21 @@ pub fn kill(&mut self) -> io::Result<()> {
         }
     }
 
+    pub fn send_signal(&self, signal: i32) -> io::Result<()> {
+        if self.status.is_some() {
+            Ok(())
+        } else {
+            cvt(unsafe { libc::kill(self.pid, signal) }).map(drop)
+        }
+    }
+
+    pub fn send_signal_to_process_group(&self, _signal: i32) -> io::Result<()> {
+        Err(io::const_io_error!(
+            io::ErrorKind::Unsupported,
+            "process groups are not supported on this platform"
+        ))
+    }
+
     pub fn wait(&mut self) -> io::Result<ExitStatus> {
         use crate::sys::cvt_r;
         if let Some(status) = self.status {
@@ -180,6 +202,10 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
             Ok(Some(ExitStatus::new(status)))
         }
     }
+
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        wait_timeout_by_polling(timeout, || self.try_wait())
+    }
 }
 
 /// Unix exit statuses
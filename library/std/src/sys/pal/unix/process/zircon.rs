@@ -88,6 +88,8 @@ pub fn zx_handle_duplicate(
         out: *const zx_handle_t,
     ) -> zx_handle_t;
 
+    pub fn zx_clock_get_monotonic() -> zx_time_t;
+
     pub fn zx_object_wait_one(
         handle: zx_handle_t,
         signals: zx_signals_t,
@@ -24,6 +24,7 @@
 use crate::sys::{cvt, path, stdio};
 use crate::sys_common::process::{CommandEnv, CommandEnvs};
 use crate::sys_common::IntoInner;
+use crate::time::{Duration, Instant};
 use crate::{cmp, env, fmt, mem, ptr};
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -696,6 +697,32 @@ pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
         }
     }
 
+    pub fn wait_timeout(&mut self, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            // `INFINITE` is reserved, so longer timeouts take several waits.
+            let timeout_ms = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    remaining.as_nanos().div_ceil(1_000_000).min((c::INFINITE - 1) as u128) as u32
+                }
+                None => c::INFINITE,
+            };
+            match unsafe { c::WaitForSingleObject(self.handle.as_raw_handle(), timeout_ms) } {
+                c::WAIT_OBJECT_0 => break,
+                c::WAIT_TIMEOUT => {
+                    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                        return Ok(None);
+                    }
+                }
+                _ => return Err(io::Error::last_os_error()),
+            }
+        }
+        let mut status = 0;
+        cvt(unsafe { c::GetExitCodeProcess(self.handle.as_raw_handle(), &mut status) })?;
+        Ok(Some(ExitStatus(status)))
+    }
+
     pub fn handle(&self) -> &Handle {
         &self.handle
     }
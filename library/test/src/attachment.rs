@@ -0,0 +1,172 @@
+//! Named artifacts that a test attaches to its result.
+//!
+//! Attachments are reported by the `json` and `junit` output formats. A test
+//! running in-process collects them in a thread-local list, so they have to be
+//! attached from the thread running the test. A test running in a subprocess
+//! appends them to a file as soon as they are attached, so that they are kept
+//! even if the subprocess aborts. That file is created in a new directory that
+//! only the current user can access.
+
+use std::cell::RefCell;
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::hash::{BuildHasher, RandomState};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::{env, process};
+
+use super::types::TestId;
+
+/// An artifact attached to the result of a test.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Attachment {
+    /// A file produced by the test, such as a log or a screenshot.
+    File { name: String, path: PathBuf },
+    /// A value measured by the test.
+    Metric { name: String, value: f64 },
+}
+
+impl Attachment {
+    pub fn name(&self) -> &str {
+        match self {
+            Attachment::File { name, .. } | Attachment::Metric { name, .. } => name,
+        }
+    }
+}
+
+thread_local! {
+    /// Attachments of the test running in-process on this thread.
+    static ATTACHMENTS: RefCell<Option<Vec<Attachment>>> = const { RefCell::new(None) };
+}
+
+/// Attachments file of the test running in this subprocess.
+static ATTACHMENTS_FILE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Attaches the file at `path` to the result of the current test.
+pub fn attach_file(name: impl Into<String>, path: impl Into<PathBuf>) {
+    attach(Attachment::File { name: name.into(), path: path.into() });
+}
+
+/// Attaches a measured value to the result of the current test.
+pub fn attach_metric(name: impl Into<String>, value: f64) {
+    attach(Attachment::Metric { name: name.into(), value });
+}
+
+// Attachments made outside of a test, or from another thread than the one
+// running the test in-process, are dropped.
+fn attach(attachment: Attachment) {
+    let attachment = ATTACHMENTS.with_borrow_mut(|attachments| match attachments {
+        Some(attachments) => {
+            attachments.push(attachment);
+            None
+        }
+        None => Some(attachment),
+    });
+    if let Some(attachment) = attachment {
+        if let Some(path) = &*ATTACHMENTS_FILE.lock().unwrap_or_else(|e| e.into_inner()) {
+            // The result of the test doesn't depend on its attachments, so a
+            // failure to write one isn't reported.
+            if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+                let _ = file.write_all(serialize(&attachment).as_bytes());
+            }
+        }
+    }
+}
+
+/// Starts collecting the attachments of a test running on this thread.
+pub(crate) fn start_collecting() {
+    ATTACHMENTS.set(Some(Vec::new()));
+}
+
+/// Returns the attachments collected since `start_collecting` was called.
+pub(crate) fn finish_collecting() -> Vec<Attachment> {
+    ATTACHMENTS.take().unwrap_or_default()
+}
+
+/// Makes attachments of the test running in this subprocess go to `path`.
+pub(crate) fn write_to_file(path: PathBuf) {
+    *ATTACHMENTS_FILE.lock().unwrap_or_else(|e| e.into_inner()) = Some(path);
+}
+
+/// Creates a private directory for the attachments of the test `id` running in
+/// a subprocess, and returns the path of the attachments file in it.
+///
+/// The directory must not exist yet, so that nobody else can have placed a file
+/// or a symlink where the subprocess writes.
+pub(crate) fn create_file(id: TestId) -> io::Result<PathBuf> {
+    let mut builder = DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    let mut attempts = 0;
+    loop {
+        // The random suffix makes the name hard to guess.
+        let suffix = RandomState::new().hash_one(id.0);
+        let name = format!("rust-test-attachments-{}-{}-{suffix:016x}", process::id(), id.0);
+        let dir = env::temp_dir().join(name);
+        match builder.create(&dir) {
+            Ok(()) => return Ok(dir.join("attachments")),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempts < 8 => attempts += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Reads the attachments file written by a subprocess, and removes it along
+/// with the directory created by `create_file`.
+pub(crate) fn read_file(path: &Path) -> Vec<Attachment> {
+    let contents = fs::read_to_string(path);
+    let _ = fs::remove_file(path);
+    if let Some(dir) = path.parent() {
+        let _ = fs::remove_dir(dir);
+    }
+    match contents {
+        Ok(contents) => contents.lines().filter_map(deserialize).collect(),
+        // The test didn't attach anything.
+        Err(_) => Vec::new(),
+    }
+}
+
+// Attachments are written one per line, as tab separated fields.
+fn serialize(attachment: &Attachment) -> String {
+    match attachment {
+        Attachment::File { name, path } => {
+            format!("file\t{}\t{}\n", escape(name), escape(&path.to_string_lossy()))
+        }
+        Attachment::Metric { name, value } => format!("metric\t{}\t{value}\n", escape(name)),
+    }
+}
+
+fn deserialize(line: &str) -> Option<Attachment> {
+    let mut fields = line.split('\t');
+    let (kind, name, value) = (fields.next()?, unescape(fields.next()?), fields.next()?);
+    match kind {
+        "file" => Some(Attachment::File { name, path: PathBuf::from(unescape(value)) }),
+        "metric" => Some(Attachment::Metric { name, value: value.parse().ok()? }),
+        _ => None,
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+    unescaped
+}
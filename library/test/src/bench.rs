@@ -1,16 +1,18 @@
 //! Benchmarking module.
 
+use std::collections::BTreeMap;
 use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use std::{cmp, io};
+use std::{cmp, fs, io};
 
 use super::event::CompletedTest;
 use super::options::BenchMode;
 use super::test_result::TestResult;
 use super::types::{TestDesc, TestId};
 use super::Sender;
-use crate::stats;
+use crate::stats::{self, Stats};
 
 /// An identity function that *__hints__* to the compiler to be maximally pessimistic about what
 /// `black_box` could do.
@@ -30,6 +32,7 @@ pub fn black_box<T>(dummy: T) -> T {
 pub struct Bencher {
     mode: BenchMode,
     summary: Option<stats::Summary>,
+    samples: Vec<f64>,
     pub bytes: u64,
 }
 
@@ -44,7 +47,9 @@ pub fn iter<T, F>(&mut self, mut inner: F)
             return;
         }
 
-        self.summary = Some(iter(&mut inner));
+        let (summary, samples) = iter_samples(&mut inner);
+        self.summary = Some(summary);
+        self.samples = samples;
     }
 
     pub fn bench<F>(&mut self, mut f: F) -> Result<Option<stats::Summary>, String>
@@ -60,6 +65,117 @@ pub fn bench<F>(&mut self, mut f: F) -> Result<Option<stats::Summary>, String>
 pub struct BenchSamples {
     pub ns_iter_summ: stats::Summary,
     pub mb_s: usize,
+    /// The ns/iter samples that `ns_iter_summ` summarizes.
+    pub samples: Vec<f64>,
+    /// How the benchmark compares to the baseline given with `--baseline`.
+    pub baseline: Option<BaselineComparison>,
+}
+
+/// Changes of the median smaller than this fraction are considered noise, even
+/// when they are statistically significant.
+const NOISE_THRESHOLD: f64 = 0.02;
+
+/// Significance level of the test comparing a benchmark to its baseline.
+const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// Comparison of the samples of a benchmark with those of an earlier run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BaselineComparison {
+    /// Median of the baseline, in ns/iter.
+    pub baseline_median: f64,
+    /// Change of the median relative to the baseline, e.g. `0.1` if the
+    /// benchmark got 10% slower.
+    pub change: f64,
+    /// p-value of Welch's t-test between the samples of the baseline and
+    /// those of this run.
+    pub p_value: f64,
+    pub verdict: BenchVerdict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BenchVerdict {
+    Regressed,
+    Improved,
+    Unchanged,
+}
+
+impl BenchVerdict {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BenchVerdict::Regressed => "regressed",
+            BenchVerdict::Improved => "improved",
+            BenchVerdict::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// Samples of benchmarks saved to disk, so that later runs can be compared
+/// against them.
+///
+/// It is saved as one line per benchmark, with the name of the benchmark and
+/// its samples separated by a tab, and the samples separated by spaces.
+#[derive(Debug, Default)]
+pub(crate) struct Baseline(BTreeMap<String, Vec<f64>>);
+
+impl Baseline {
+    pub(crate) fn load(path: &Path) -> io::Result<Baseline> {
+        let invalid = |line: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid line in baseline {}: {line:?}", path.display()),
+            )
+        };
+        let mut baseline = Baseline::default();
+        let contents = fs::read_to_string(path).map_err(|e| {
+            io::Error::new(e.kind(), format!("failed to read baseline {}: {e}", path.display()))
+        })?;
+        for line in contents.lines() {
+            let (name, samples) = line.rsplit_once('\t').ok_or_else(|| invalid(line))?;
+            let samples = samples
+                .split(' ')
+                .map(|sample| sample.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| invalid(line))?;
+            baseline.0.insert(name.to_string(), samples);
+        }
+        Ok(baseline)
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> io::Result<()> {
+        let mut contents = String::new();
+        for (name, samples) in &self.0 {
+            let samples: Vec<_> = samples.iter().map(|sample| sample.to_string()).collect();
+            contents.push_str(&format!("{name}\t{}\n", samples.join(" ")));
+        }
+        fs::write(path, contents)
+    }
+
+    pub(crate) fn insert(&mut self, name: &str, samples: &[f64]) {
+        self.0.insert(name.to_string(), samples.to_vec());
+    }
+
+    /// Compares `samples` to the baseline of the benchmark `name`, if there
+    /// is one with enough samples for a comparison.
+    pub(crate) fn compare(&self, name: &str, samples: &[f64]) -> Option<BaselineComparison> {
+        let baseline = self.0.get(name)?;
+        if baseline.len() < 2 || samples.len() < 2 {
+            return None;
+        }
+        let baseline_median = baseline.median();
+        if baseline_median <= 0.0 {
+            return None;
+        }
+        let change = samples.median() / baseline_median - 1.0;
+        let p_value = stats::welch_t_test(baseline, samples);
+        let verdict = if p_value >= SIGNIFICANCE_LEVEL || change.abs() < NOISE_THRESHOLD {
+            BenchVerdict::Unchanged
+        } else if change > 0.0 {
+            BenchVerdict::Regressed
+        } else {
+            BenchVerdict::Improved
+        };
+        Some(BaselineComparison { baseline_median, change, p_value, verdict })
+    }
 }
 
 pub fn fmt_bench_samples(bs: &BenchSamples) -> String {
@@ -79,6 +195,16 @@ pub fn fmt_bench_samples(bs: &BenchSamples) -> String {
     if bs.mb_s != 0 {
         write!(output, " = {} MB/s", bs.mb_s).unwrap();
     }
+    if let Some(comparison) = &bs.baseline {
+        write!(
+            output,
+            " ({}: {:+.2}% vs baseline, p = {:.3})",
+            comparison.verdict.as_str(),
+            comparison.change * 100.0,
+            comparison.p_value
+        )
+        .unwrap();
+    }
     output
 }
 
@@ -121,6 +247,14 @@ fn ns_iter_inner<T, F>(inner: &mut F, k: u64) -> u64
 }
 
 pub fn iter<T, F>(inner: &mut F) -> stats::Summary
+where
+    F: FnMut() -> T,
+{
+    iter_samples(inner).0
+}
+
+/// Like `iter`, but also returns the samples that were summarized.
+fn iter_samples<T, F>(inner: &mut F) -> (stats::Summary, Vec<f64>)
 where
     F: FnMut() -> T,
 {
@@ -167,13 +301,13 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
             && summ.median_abs_dev_pct < 1.0
             && summ.median - summ5.median < summ5.median_abs_dev
         {
-            return summ5;
+            return (summ5, samples.to_vec());
         }
 
         total_run += loop_run;
         // Longest we ever run for is 3s.
         if total_run > Duration::from_secs(3) {
-            return summ5;
+            return (summ5, samples.to_vec());
         }
 
         // If we overflow here just return the results so far. We check a
@@ -183,7 +317,7 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
         n = match n.checked_mul(10) {
             Some(_) => n * 2,
             None => {
-                return summ5;
+                return (summ5, samples.to_vec());
             }
         };
     }
@@ -198,7 +332,7 @@ pub fn benchmark<F>(
 ) where
     F: FnMut(&mut Bencher) -> Result<(), String>,
 {
-    let mut bs = Bencher { mode: BenchMode::Auto, summary: None, bytes: 0 };
+    let mut bs = Bencher { mode: BenchMode::Auto, summary: None, samples: Vec::new(), bytes: 0 };
 
     let data = Arc::new(Mutex::new(Vec::new()));
 
@@ -216,14 +350,20 @@ pub fn benchmark<F>(
             let ns_iter = cmp::max(ns_iter_summ.median as u64, 1);
             let mb_s = bs.bytes * 1000 / ns_iter;
 
-            let bs = BenchSamples { ns_iter_summ, mb_s: mb_s as usize };
+            let samples = std::mem::take(&mut bs.samples);
+            let bs = BenchSamples { ns_iter_summ, mb_s: mb_s as usize, samples, baseline: None };
             TestResult::TrBench(bs)
         }
         Ok(Ok(None)) => {
             // iter not called, so no data.
             // FIXME: error in this case?
             let samples: &mut [f64] = &mut [0.0_f64; 1];
-            let bs = BenchSamples { ns_iter_summ: stats::Summary::new(samples), mb_s: 0 };
+            let bs = BenchSamples {
+                ns_iter_summ: stats::Summary::new(samples),
+                mb_s: 0,
+                samples: samples.to_vec(),
+                baseline: None,
+            };
             TestResult::TrBench(bs)
         }
         Err(_) => TestResult::TrFailed,
@@ -239,6 +379,6 @@ pub fn run_once<F>(f: F) -> Result<(), String>
 where
     F: FnMut(&mut Bencher) -> Result<(), String>,
 {
-    let mut bs = Bencher { mode: BenchMode::Single, summary: None, bytes: 0 };
+    let mut bs = Bencher { mode: BenchMode::Single, summary: None, samples: Vec::new(), bytes: 0 };
     bs.bench(f).map(|_| ())
 }
@@ -3,8 +3,9 @@
 use std::env;
 use std::io::{self, IsTerminal};
 use std::path::PathBuf;
+use std::time::Duration;
 
-use super::options::{ColorConfig, Options, OutputFormat, RunIgnored};
+use super::options::{ColorConfig, Options, OutputFormat, RunIgnored, Shard};
 use super::time::TestTimeOptions;
 
 #[derive(Debug)]
@@ -23,9 +24,23 @@ pub struct TestOpts {
     pub format: OutputFormat,
     pub shuffle: bool,
     pub shuffle_seed: Option<u64>,
+    /// Run only the tests of this shard.
+    pub shard: Option<Shard>,
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
     pub time_options: Option<TestTimeOptions>,
+    /// Time limit for a single test, unless the test sets its own with `#[test_timeout]`.
+    pub test_timeout: Option<Duration>,
+    /// Time limit for the whole run. Tests still running when it expires
+    /// are reported as timed out and no further tests are started.
+    pub suite_timeout: Option<Duration>,
+    /// Number of times a failing test is run again before it is reported as
+    /// failed. A test that passes on one of these attempts is reported as flaky.
+    pub retries: usize,
+    /// Save the samples of the benchmarks to this file.
+    pub save_baseline: Option<PathBuf>,
+    /// Compare the benchmarks to the samples saved to this file by an earlier run.
+    pub baseline: Option<PathBuf>,
     /// Stop at first failing test.
     /// May run a few more tests due to threading, but will
     /// abort as soon as possible.
@@ -142,12 +157,68 @@ fn optgroups() -> getopts::Options {
             `CRITICAL_TIME` here means the limit that should not be exceeded by test.
             ",
         )
+        .optopt(
+            "",
+            "test-timeout",
+            "Fail tests that run for longer than DURATION.
+
+            DURATION is a number followed by `ms`, `s` or `m`; a plain number
+            means seconds. Tests running in-process cannot be interrupted and
+            are abandoned, tests running in a subprocess are killed.
+            The `#[test_timeout]` attribute overrides this for a single test.",
+            "DURATION",
+        )
+        .optopt(
+            "",
+            "suite-timeout",
+            "Fail all tests still running once the whole run has taken longer
+            than DURATION, and do not start any further tests.",
+            "DURATION",
+        )
+        .optopt(
+            "",
+            "retries",
+            "Run failing tests again up to N times. Tests that pass on a
+            later attempt are reported as flaky instead of failed. Dynamic
+            tests, such as doctests and the cases of a test generator, can
+            only be run once and are not retried.",
+            "N",
+        )
         .optflag("", "shuffle", "Run tests in random order")
         .optopt(
             "",
             "shuffle-seed",
             "Run tests in random order; seed the random number generator with SEED",
             "SEED",
+        )
+        .optopt(
+            "",
+            "shard-index",
+            "Run only the tests of shard INDEX, counting from 0. Requires
+            --shard-count.",
+            "INDEX",
+        )
+        .optopt(
+            "",
+            "shard-count",
+            "Split the tests into COUNT shards of which only one is run, as
+            selected by --shard-index. Each test is always put in the same shard.",
+            "COUNT",
+        )
+        .optopt(
+            "",
+            "save-baseline",
+            "Save the samples of the benchmarks to PATH, to compare later runs
+            against them with --baseline.",
+            "PATH",
+        )
+        .optopt(
+            "",
+            "baseline",
+            "Compare the benchmarks to the samples saved to PATH by
+            --save-baseline, and report significant regressions and
+            improvements.",
+            "PATH",
         );
     opts
 }
@@ -171,6 +242,15 @@ fn usage(binary: &str, options: &getopts::Options) {
 tests in the same order again. Note that --shuffle and --shuffle-seed do not
 affect whether the tests are run in parallel.
 
+To split the tests across several runs, for example on different CI machines,
+pass the same --shard-count to each run and a different --shard-index, from 0
+up to the count minus one. Together the shards run every test exactly once.
+
+To find out whether a change made benchmarks faster or slower, run them with
+--save-baseline before the change and with --baseline after it. A benchmark is
+reported as regressed or improved if Welch's t-test finds the difference
+significant and its median changed by at least 2%.
+
 All tests have their standard output and standard error captured by default.
 This can be overridden with the --nocapture flag or setting RUST_TEST_NOCAPTURE
 environment variable to a value other than "0". Logging is not captured by default.
@@ -188,7 +268,10 @@ fn usage(binary: &str, options: &getopts::Options) {
     `#[ignore]`       - When applied to a function which is already attributed as a
                         test, then the test runner will ignore these tests during
                         normal test runs. Running with --ignored or --include-ignored will run
-                        these tests."#,
+                        these tests.
+    `#[test_timeout = "30s"]` - Fails the test (also labeled with `#[test]`) if it is
+                        still running after the given duration, overriding --test-timeout.
+                        Requires `#![feature(test_timeout)]`."#,
         usage = options.usage(&message)
     );
 }
@@ -261,6 +344,10 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
     let time_options = get_time_options(&matches, allow_unstable)?;
     let shuffle = get_shuffle(&matches, allow_unstable)?;
     let shuffle_seed = get_shuffle_seed(&matches, allow_unstable)?;
+    let shard = get_shard(&matches, allow_unstable)?;
+    let (test_timeout, suite_timeout) = get_timeouts(&matches, allow_unstable)?;
+    let retries = get_retries(&matches, allow_unstable)?;
+    let (save_baseline, baseline) = get_baselines(&matches, allow_unstable)?;
 
     let include_ignored = matches.opt_present("include-ignored");
     let quiet = matches.opt_present("quiet");
@@ -296,9 +383,15 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         format,
         shuffle,
         shuffle_seed,
+        shard,
         test_threads,
         skip,
         time_options,
+        test_timeout,
+        suite_timeout,
+        retries,
+        save_baseline,
+        baseline,
         options,
         fail_fast: false,
     };
@@ -336,6 +429,82 @@ fn get_time_options(
     Ok(options)
 }
 
+// Gets the CLI options limiting the run time of tests.
+fn get_timeouts(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<(Option<Duration>, Option<Duration>)> {
+    let test_timeout = unstable_optopt!(matches, allow_unstable, "test-timeout");
+    let suite_timeout = unstable_optopt!(matches, allow_unstable, "suite-timeout");
+
+    let parse = |option_name: &str, value: Option<String>| match value {
+        Some(s) => match parse_duration(&s) {
+            Some(timeout) if !timeout.is_zero() => Ok(Some(timeout)),
+            Some(_) => Err(format!("argument for --{option_name} must not be 0")),
+            None => Err(format!(
+                "argument for --{option_name} must be a number optionally followed \
+                 by `ms`, `s` or `m` (was {s})"
+            )),
+        },
+        None => Ok(None),
+    };
+
+    Ok((parse("test-timeout", test_timeout)?, parse("suite-timeout", suite_timeout)?))
+}
+
+/// Parses durations such as `500ms`, `30s` or `5m`. A number without a unit is
+/// a number of seconds.
+///
+/// This is a `const fn` so that it can also check `#[test_timeout]` values at
+/// compile time, see [`crate::parse_test_timeout`].
+pub(crate) const fn parse_duration(s: &str) -> Option<Duration> {
+    let (digits, millis_per_unit): (&[u8], u64) = match s.as_bytes() {
+        [digits @ .., b'm', b's'] => (digits, 1),
+        [digits @ .., b's'] => (digits, 1_000),
+        [digits @ .., b'm'] => (digits, 60_000),
+        digits => (digits, 1_000),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    let mut number: u64 = 0;
+    let mut i = 0;
+    while i < digits.len() {
+        if !digits[i].is_ascii_digit() {
+            return None;
+        }
+        number = match number.checked_mul(10) {
+            Some(number) => match number.checked_add((digits[i] - b'0') as u64) {
+                Some(number) => number,
+                None => return None,
+            },
+            None => return None,
+        };
+        i += 1;
+    }
+    match number.checked_mul(millis_per_unit) {
+        Some(millis) => Some(Duration::from_millis(millis)),
+        None => None,
+    }
+}
+
+fn get_retries(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<usize> {
+    let retries = match unstable_optopt!(matches, allow_unstable, "retries") {
+        Some(n_str) => match n_str.parse::<usize>() {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(format!(
+                    "argument for --retries must be a number \
+                     (error: {e})"
+                ));
+            }
+        },
+        None => 0,
+    };
+
+    Ok(retries)
+}
+
 fn get_shuffle(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<bool> {
     let mut shuffle = unstable_optflag!(matches, allow_unstable, "shuffle");
     if !shuffle && allow_unstable {
@@ -375,6 +544,49 @@ fn get_shuffle_seed(matches: &getopts::Matches, allow_unstable: bool) -> OptPart
     Ok(shuffle_seed)
 }
 
+fn get_shard(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<Option<Shard>> {
+    let index = unstable_optopt!(matches, allow_unstable, "shard-index");
+    let count = unstable_optopt!(matches, allow_unstable, "shard-count");
+
+    let (index, count) = match (index, count) {
+        (Some(index), Some(count)) => (index, count),
+        (None, None) => return Ok(None),
+        _ => return Err("--shard-index and --shard-count must be used together".to_string()),
+    };
+    let index = match index.parse::<usize>() {
+        Ok(n) => n,
+        Err(e) => return Err(format!("argument for --shard-index must be a number (error: {e})")),
+    };
+    let count = match count.parse::<usize>() {
+        Ok(0) => return Err("argument for --shard-count must not be 0".to_string()),
+        Ok(n) => n,
+        Err(e) => {
+            return Err(format!("argument for --shard-count must be a number > 0 (error: {e})"));
+        }
+    };
+    if index >= count {
+        return Err(format!(
+            "argument for --shard-index must be less than --shard-count ({count}) (was {index})"
+        ));
+    }
+
+    Ok(Some(Shard { index, count }))
+}
+
+fn get_baselines(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<(Option<PathBuf>, Option<PathBuf>)> {
+    let save_baseline = unstable_optopt!(matches, allow_unstable, "save-baseline");
+    let baseline = unstable_optopt!(matches, allow_unstable, "baseline");
+
+    if (save_baseline.is_some() || baseline.is_some()) && !matches.opt_present("bench") {
+        return Err("--save-baseline and --baseline can only be used with --bench".to_string());
+    }
+
+    Ok((save_baseline.map(PathBuf::from), baseline.map(PathBuf::from)))
+}
+
 fn get_test_threads(matches: &getopts::Matches) -> OptPartRes<Option<usize>> {
     let test_threads = match matches.opt_str("test-threads") {
         Some(n_str) => match n_str.parse::<usize>() {
@@ -5,7 +5,7 @@
 use std::io::prelude::Write;
 use std::time::Instant;
 
-use super::bench::fmt_bench_samples;
+use super::bench::{fmt_bench_samples, Baseline};
 use super::cli::TestOpts;
 use super::event::{CompletedTest, TestEvent};
 use super::formatters::{
@@ -17,7 +17,7 @@
 use super::test_result::TestResult;
 use super::time::{TestExecTime, TestSuiteExecTime};
 use super::types::{NamePadding, TestDesc, TestDescAndFn};
-use super::{filter_tests, run_tests, term};
+use super::{filter_tests, generate_test_cases, run_tests, term};
 
 /// Generic wrapper over stdout.
 pub enum OutputLocation<T> {
@@ -79,6 +79,7 @@ pub struct ConsoleTestState {
     pub total: usize,
     pub passed: usize,
     pub failed: usize,
+    pub flaky: usize,
     pub ignored: usize,
     pub filtered_out: usize,
     pub measured: usize,
@@ -88,6 +89,7 @@ pub struct ConsoleTestState {
     pub not_failures: Vec<(TestDesc, Vec<u8>)>,
     pub ignores: Vec<(TestDesc, Vec<u8>)>,
     pub time_failures: Vec<(TestDesc, Vec<u8>)>,
+    pub flaky_tests: Vec<(TestDesc, Vec<u8>)>,
     pub options: Options,
 }
 
@@ -103,6 +105,7 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             total: 0,
             passed: 0,
             failed: 0,
+            flaky: 0,
             ignored: 0,
             filtered_out: 0,
             measured: 0,
@@ -112,6 +115,7 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             not_failures: Vec::new(),
             ignores: Vec::new(),
             time_failures: Vec::new(),
+            flaky_tests: Vec::new(),
             options: opts.options,
         })
     }
@@ -154,6 +158,10 @@ pub fn write_log_result(
                     }
                     TestResult::TrBench(ref bs) => fmt_bench_samples(bs),
                     TestResult::TrTimedFail => "failed (time limit exceeded)".to_owned(),
+                    TestResult::TrTimedOut => "failed (timed out)".to_owned(),
+                    TestResult::TrFlaky(failures) => {
+                        format!("flaky (passed on attempt {})", failures + 1)
+                    }
                 },
                 name,
             )
@@ -165,7 +173,7 @@ pub fn write_log_result(
     }
 
     fn current_test_count(&self) -> usize {
-        self.passed + self.failed + self.ignored + self.measured
+        self.passed + self.failed + self.flaky + self.ignored + self.measured
     }
 }
 
@@ -181,18 +189,23 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
             Box::new(PrettyFormatter::new(output, false, 0, false, None))
         }
         OutputFormat::Terse => Box::new(TerseFormatter::new(output, false, 0, false)),
-        OutputFormat::Json => Box::new(JsonFormatter::new(output)),
+        OutputFormat::Json => Box::new(JsonFormatter::new(output, opts.shard)),
     };
     let mut st = ConsoleTestDiscoveryState::new(opts)?;
 
     out.write_discovery_start()?;
-    for test in filter_tests(opts, tests).into_iter() {
+    for test in filter_tests(opts, generate_test_cases(tests)).into_iter() {
         use crate::TestFn::*;
 
         let TestDescAndFn { desc, testfn } = test;
 
         let fntype = match testfn {
-            StaticTestFn(..) | DynTestFn(..) | StaticBenchAsTestFn(..) | DynBenchAsTestFn(..) => {
+            StaticTestFn(..)
+            | DynTestFn(..)
+            | StaticBenchAsTestFn(..)
+            | DynBenchAsTestFn(..)
+            | StaticTestGenFn(..)
+            | DynTestCaseFn(..) => {
                 st.tests += 1;
                 "test"
             }
@@ -246,6 +259,16 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.failed += 1;
             st.time_failures.push((test, stdout));
         }
+        TestResult::TrTimedOut => {
+            st.failed += 1;
+            let mut stdout = stdout;
+            stdout.extend_from_slice(b"note: test did not finish before its timeout");
+            st.failures.push((test, stdout));
+        }
+        TestResult::TrFlaky(_) => {
+            st.flaky += 1;
+            st.flaky_tests.push((test, stdout));
+        }
     }
 }
 
@@ -271,9 +294,11 @@ fn on_test_event(
             let result = &completed_test.result;
             let exec_time = &completed_test.exec_time;
             let stdout = &completed_test.stdout;
+            let stderr = &completed_test.stderr;
+            let attachments = &completed_test.attachments;
 
             st.write_log_result(test, result, exec_time.as_ref())?;
-            out.write_result(test, result, exec_time.as_ref(), stdout, st)?;
+            out.write_result(test, result, exec_time.as_ref(), stdout, stderr, attachments, st)?;
             handle_test_result(st, completed_test);
         }
     }
@@ -289,6 +314,8 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         Some(t) => OutputLocation::Pretty(t),
     };
 
+    // The names of the generated cases have to be known to align the output.
+    let tests = generate_test_cases(tests);
     let max_name_len = tests
         .iter()
         .max_by_key(|t| len_if_padded(t))
@@ -308,10 +335,12 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         OutputFormat::Terse => {
             Box::new(TerseFormatter::new(output, opts.use_color(), max_name_len, is_multithreaded))
         }
-        OutputFormat::Json => Box::new(JsonFormatter::new(output)),
+        OutputFormat::Json => Box::new(JsonFormatter::new(output, opts.shard)),
         OutputFormat::Junit => Box::new(JunitFormatter::new(output)),
     };
     let mut st = ConsoleTestState::new(opts)?;
+    let baseline = opts.baseline.as_deref().map(Baseline::load).transpose()?;
+    let mut new_baseline = Baseline::default();
 
     // Prevent the usage of `Instant` in some cases:
     // - It's currently not supported for wasm targets.
@@ -319,8 +348,20 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         (cfg!(target_family = "wasm") && !cfg!(target_os = "wasi")) || cfg!(target_os = "zkvm");
 
     let start_time = (!is_instant_unsupported).then(Instant::now);
-    run_tests(opts, tests, |x| on_test_event(&x, &mut st, &mut *out))?;
+    run_tests(opts, tests, |mut x| {
+        if let TestEvent::TeResult(ref mut completed_test) = x {
+            if let TestResult::TrBench(ref mut bs) = completed_test.result {
+                let name = completed_test.desc.name.as_slice();
+                bs.baseline = baseline.as_ref().and_then(|b| b.compare(name, &bs.samples));
+                new_baseline.insert(name, &bs.samples);
+            }
+        }
+        on_test_event(&x, &mut st, &mut *out)
+    })?;
     st.exec_time = start_time.map(|t| TestSuiteExecTime(t.elapsed()));
+    if let Some(path) = &opts.save_baseline {
+        new_baseline.save(path)?;
+    }
 
     assert!(opts.fail_fast || st.current_test_count() == st.total);
 
@@ -0,0 +1,38 @@
+//! Minimal executor running `#[test] async fn` without an external runtime.
+//!
+//! The future is polled on the thread running the test, which parks until the
+//! future is woken. Tests relying on a specific runtime (for example to spawn
+//! tasks or use its timers or I/O) still need to set up that runtime themselves.
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+/// Wakes the thread blocked on a future by unparking it.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs `future` to completion on the current thread and returns its output.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            // A spurious wakeup only causes the future to be polled once more.
+            Poll::Pending => thread::park(),
+        }
+    }
+}
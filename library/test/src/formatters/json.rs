@@ -1,20 +1,79 @@
-use std::borrow::Cow;
 use std::io;
 use std::io::prelude::Write;
 
 use super::OutputFormatter;
+use crate::attachment::Attachment;
 use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation};
+use crate::options::{Shard, ShouldPanic};
 use crate::test_result::TestResult;
 use crate::time;
-use crate::types::TestDesc;
+use crate::types::{TestDesc, TestType};
 
 pub(crate) struct JsonFormatter<T> {
     out: OutputLocation<T>,
+    /// Recorded with every result, so that the results of all shards can be merged.
+    shard: Option<Shard>,
 }
 
 impl<T: Write> JsonFormatter<T> {
-    pub fn new(out: OutputLocation<T>) -> Self {
-        Self { out }
+    pub fn new(out: OutputLocation<T>, shard: Option<Shard>) -> Self {
+        Self { out, shard }
+    }
+
+    fn shard_json(&self) -> String {
+        if let Some(Shard { index, count }) = self.shard {
+            format!(r#", "shard_index": {index}, "shard_count": {count}"#)
+        } else {
+            String::from("")
+        }
+    }
+
+    /// Lists the modules containing a test, from the crate root down, so that the
+    /// tests can be shown as a tree. Doc tests aren't nested in modules.
+    fn module_path_json(desc: &TestDesc) -> String {
+        let mut segments: Vec<_> = match desc.test_type {
+            TestType::DocTest => Vec::new(),
+            _ => desc.name.as_slice().split("::").collect(),
+        };
+        segments.pop();
+        let segments: Vec<_> = segments
+            .into_iter()
+            .map(|segment| format!(r#""{}""#, EscapedString(segment)))
+            .collect();
+        format!("[{}]", segments.join(", "))
+    }
+
+    fn output_json(stdout: &[u8], stderr: &[u8], attachments: &[Attachment]) -> String {
+        let mut json = String::new();
+        if !stdout.is_empty() {
+            json += &format!(r#", "stdout": "{}""#, EscapedString(String::from_utf8_lossy(stdout)));
+        }
+        if !stderr.is_empty() {
+            json += &format!(r#", "stderr": "{}""#, EscapedString(String::from_utf8_lossy(stderr)));
+        }
+        if !attachments.is_empty() {
+            let attachments: Vec<_> = attachments
+                .iter()
+                .map(|attachment| {
+                    let name = EscapedString(attachment.name());
+                    match attachment {
+                        Attachment::File { path, .. } => {
+                            let path = EscapedString(path.to_string_lossy());
+                            format!(r#"{{ "kind": "file", "name": "{name}", "path": "{path}" }}"#)
+                        }
+                        // JSON has no representation for infinite and NaN values.
+                        Attachment::Metric { value, .. } if !value.is_finite() => {
+                            format!(r#"{{ "kind": "metric", "name": "{name}", "value": null }}"#)
+                        }
+                        Attachment::Metric { value, .. } => {
+                            format!(r#"{{ "kind": "metric", "name": "{name}", "value": {value} }}"#)
+                        }
+                    }
+                })
+                .collect();
+            json += &format!(r#", "attachments": [{}]"#, attachments.join(", "));
+        }
+        json
     }
 
     fn writeln_message(&mut self, s: &str) -> io::Result<()> {
@@ -32,7 +91,7 @@ fn write_event(
         name: &str,
         event: &str,
         exec_time: Option<&time::TestExecTime>,
-        stdout: Option<Cow<'_, str>>,
+        output_json: &str,
         extra: Option<&str>,
     ) -> io::Result<()> {
         // A doc test's name includes a filename which must be escaped for correct json.
@@ -42,17 +101,13 @@ fn write_event(
         } else {
             String::from("")
         };
-        let stdout_json = if let Some(stdout) = stdout {
-            format!(r#", "stdout": "{}""#, EscapedString(stdout))
-        } else {
-            String::from("")
-        };
         let extra_json =
             if let Some(extra) = extra { format!(r#", {extra}"#) } else { String::from("") };
+        let shard_json = self.shard_json();
         let newline = "\n";
 
         self.writeln_message(&format!(
-                r#"{{ "type": "{ty}", "name": "{name}", "event": "{event}"{exec_time_json}{stdout_json}{extra_json} }}{newline}"#))
+                r#"{{ "type": "{ty}", "name": "{name}", "event": "{event}"{exec_time_json}{output_json}{extra_json}{shard_json} }}{newline}"#))
     }
 }
 
@@ -71,16 +126,24 @@ fn write_test_discovered(&mut self, desc: &TestDesc, test_type: &str) -> io::Res
             start_col,
             end_line,
             end_col,
+            should_panic,
             ..
         } = desc;
 
+        let module_path = Self::module_path_json(desc);
         let name = EscapedString(name.as_slice());
-        let ignore_message = ignore_message.unwrap_or("");
+        let ignore_message = EscapedString(ignore_message.unwrap_or(""));
+        let (should_panic, should_panic_message) = match should_panic {
+            ShouldPanic::No => (false, ""),
+            ShouldPanic::Yes => (true, ""),
+            ShouldPanic::YesWithMessage(message) => (true, *message),
+        };
+        let should_panic_message = EscapedString(should_panic_message);
         let source_path = EscapedString(source_file);
         let newline = "\n";
 
         self.writeln_message(&format!(
-            r#"{{ "type": "{test_type}", "event": "discovered", "name": "{name}", "ignore": {ignore}, "ignore_message": "{ignore_message}", "source_path": "{source_path}", "start_line": {start_line}, "start_col": {start_col}, "end_line": {end_line}, "end_col": {end_col} }}{newline}"#
+            r#"{{ "type": "{test_type}", "event": "discovered", "name": "{name}", "module_path": {module_path}, "ignore": {ignore}, "ignore_message": "{ignore_message}", "should_panic": {should_panic}, "should_panic_message": "{should_panic_message}", "source_path": "{source_path}", "start_line": {start_line}, "start_col": {start_col}, "end_line": {end_line}, "end_col": {end_col} }}{newline}"#
         ))
     }
 
@@ -100,9 +163,10 @@ fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> i
         } else {
             String::new()
         };
+        let shard_json = self.shard_json();
         let newline = "\n";
         self.writeln_message(&format!(
-            r#"{{ "type": "suite", "event": "started", "test_count": {test_count}{shuffle_seed_json} }}{newline}"#
+            r#"{{ "type": "suite", "event": "started", "test_count": {test_count}{shuffle_seed_json}{shard_json} }}{newline}"#
             ))
     }
 
@@ -120,21 +184,24 @@ fn write_result(
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
         stdout: &[u8],
+        stderr: &[u8],
+        attachments: &[Attachment],
         state: &ConsoleTestState,
     ) -> io::Result<()> {
-        let display_stdout = state.options.display_output || *result != TestResult::TrOk;
-        let stdout = if display_stdout && !stdout.is_empty() {
-            Some(String::from_utf8_lossy(stdout))
+        // Attachments are always reported, the output only when it is displayed.
+        let display_output = state.options.display_output || *result != TestResult::TrOk;
+        let output = if display_output {
+            Self::output_json(stdout, stderr, attachments)
         } else {
-            None
+            Self::output_json(&[], &[], attachments)
         };
         match *result {
             TestResult::TrOk => {
-                self.write_event("test", desc.name.as_slice(), "ok", exec_time, stdout, None)
+                self.write_event("test", desc.name.as_slice(), "ok", exec_time, &output, None)
             }
 
             TestResult::TrFailed => {
-                self.write_event("test", desc.name.as_slice(), "failed", exec_time, stdout, None)
+                self.write_event("test", desc.name.as_slice(), "failed", exec_time, &output, None)
             }
 
             TestResult::TrTimedFail => self.write_event(
@@ -142,16 +209,34 @@ fn write_result(
                 desc.name.as_slice(),
                 "failed",
                 exec_time,
-                stdout,
+                &output,
                 Some(r#""reason": "time limit exceeded""#),
             ),
 
+            TestResult::TrTimedOut => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "failed",
+                exec_time,
+                &output,
+                Some(r#""reason": "timed out""#),
+            ),
+
+            TestResult::TrFlaky(failures) => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "flaky",
+                exec_time,
+                &output,
+                Some(&*format!(r#""attempts": {}"#, failures + 1)),
+            ),
+
             TestResult::TrFailedMsg(ref m) => self.write_event(
                 "test",
                 desc.name.as_slice(),
                 "failed",
                 exec_time,
-                stdout,
+                &output,
                 Some(&*format!(r#""message": "{}""#, EscapedString(m))),
             ),
 
@@ -160,7 +245,7 @@ fn write_result(
                 desc.name.as_slice(),
                 "ignored",
                 exec_time,
-                stdout,
+                &output,
                 desc.ignore_message
                     .map(|msg| format!(r#""message": "{}""#, EscapedString(msg)))
                     .as_deref(),
@@ -175,13 +260,25 @@ fn write_result(
                 } else {
                     format!(r#", "mib_per_second": {}"#, bs.mb_s)
                 };
+                let baseline = if let Some(comparison) = &bs.baseline {
+                    format!(
+                        r#", "baseline_median": {}, "change": {}, "p_value": {}, "verdict": "{}""#,
+                        comparison.baseline_median,
+                        comparison.change,
+                        comparison.p_value,
+                        comparison.verdict.as_str()
+                    )
+                } else {
+                    String::new()
+                };
                 let name = EscapedString(desc.name.as_slice());
+                let shard_json = self.shard_json();
 
                 self.writeln_message(&format!(
                     "{{ \"type\": \"bench\", \
                      \"name\": \"{name}\", \
                      \"median\": {median}, \
-                     \"deviation\": {deviation}{mbps} }}\n",
+                     \"deviation\": {deviation}{mbps}{baseline}{shard_json} }}\n",
                 ))
             }
         }
@@ -202,15 +299,21 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let ignored = state.ignored;
         let measured = state.measured;
         let filtered_out = state.filtered_out;
+        let flaky_json = if state.flaky > 0 {
+            format!(r#", "flaky": {}"#, state.flaky)
+        } else {
+            String::from("")
+        };
         let exec_time_json = if let Some(ref exec_time) = state.exec_time {
             format!(r#", "exec_time": {}"#, exec_time.0.as_secs_f64())
         } else {
             String::from("")
         };
+        let shard_json = self.shard_json();
         let newline = "\n";
 
         self.writeln_message(&format!(
-            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}, "failed": {failed}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{exec_time_json} }}{newline}"#
+            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}, "failed": {failed}{flaky_json}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{exec_time_json}{shard_json} }}{newline}"#
         ))?;
 
         Ok(state.failed == 0)
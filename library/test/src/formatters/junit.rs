@@ -3,6 +3,7 @@
 use std::time::Duration;
 
 use super::OutputFormatter;
+use crate::attachment::Attachment;
 use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation};
 use crate::test_result::TestResult;
 use crate::time;
@@ -10,7 +11,7 @@
 
 pub struct JunitFormatter<T> {
     out: OutputLocation<T>,
-    results: Vec<(TestDesc, TestResult, Duration, Vec<u8>)>,
+    results: Vec<(TestDesc, TestResult, Duration, Vec<u8>, Vec<u8>, Vec<Attachment>)>,
 }
 
 impl<T: Write> JunitFormatter<T> {
@@ -23,6 +24,61 @@ fn write_message(&mut self, s: &str) -> io::Result<()> {
 
         self.out.write_all(s.as_ref())
     }
+
+    fn write_properties(&mut self, attachments: &[Attachment]) -> io::Result<()> {
+        // Metrics are recorded as properties of the test case, as pytest does.
+        let properties: Vec<_> = attachments
+            .iter()
+            .filter_map(|attachment| match attachment {
+                Attachment::Metric { name, value } => {
+                    Some(format!("<property name=\"{}\" value=\"{value}\"/>", str_to_attr(name)))
+                }
+                Attachment::File { .. } => None,
+            })
+            .collect();
+        if properties.is_empty() {
+            return Ok(());
+        }
+        self.write_message("<properties>")?;
+        for property in properties {
+            self.write_message(&property)?;
+        }
+        self.write_message("</properties>")
+    }
+
+    fn write_output(
+        &mut self,
+        stdout: &[u8],
+        stderr: &[u8],
+        attachments: &[Attachment],
+    ) -> io::Result<()> {
+        // Attached files are referenced from the output in the format of the
+        // Jenkins JUnit Attachments plugin.
+        let mut stdout = String::from_utf8_lossy(stdout).into_owned();
+        for attachment in attachments {
+            if let Attachment::File { path, .. } = attachment {
+                if !stdout.is_empty() && !stdout.ends_with('\n') {
+                    stdout.push('\n');
+                }
+                stdout.push_str(&format!("[[ATTACHMENT|{}]]\n", path.display()));
+            }
+        }
+        if !stdout.is_empty() {
+            self.write_message("<system-out>")?;
+            self.write_message(&str_to_cdata(&stdout))?;
+            self.write_message("</system-out>")?;
+        }
+        if !stderr.is_empty() {
+            self.write_message("<system-err>")?;
+            self.write_message(&str_to_cdata(&String::from_utf8_lossy(stderr)))?;
+            self.write_message("</system-err>")?;
+        }
+        Ok(())
+    }
+}
+
+fn str_to_attr(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
 }
 
 fn str_to_cdata(s: &str) -> String {
@@ -75,13 +131,22 @@ fn write_result(
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
         stdout: &[u8],
+        stderr: &[u8],
+        attachments: &[Attachment],
         _state: &ConsoleTestState,
     ) -> io::Result<()> {
         // Because the testsuite node holds some of the information as attributes, we can't write it
         // until all of the tests have finished. Instead of writing every result as they come in, we add
         // them to a Vec and write them all at once when run is complete.
         let duration = exec_time.map(|t| t.0).unwrap_or_default();
-        self.results.push((desc.clone(), result.clone(), duration, stdout.to_vec()));
+        self.results.push((
+            desc.clone(),
+            result.clone(),
+            duration,
+            stdout.to_vec(),
+            stderr.to_vec(),
+            attachments.to_vec(),
+        ));
         Ok(())
     }
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
@@ -96,7 +161,9 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
              >",
             state.failed, state.total, state.ignored
         ))?;
-        for (desc, result, duration, stdout) in std::mem::take(&mut self.results) {
+        for (desc, result, duration, stdout, stderr, attachments) in
+            std::mem::take(&mut self.results)
+        {
             let (class_name, test_name) = parse_class_name(&desc);
             match result {
                 TestResult::TrIgnored => { /* no-op */ }
@@ -108,12 +175,9 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
+                    self.write_properties(&attachments)?;
                     self.write_message("<failure type=\"assert\"/>")?;
-                    if !stdout.is_empty() {
-                        self.write_message("<system-out>")?;
-                        self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
-                        self.write_message("</system-out>")?;
-                    }
+                    self.write_output(&stdout, &stderr, &attachments)?;
                     self.write_message("</testcase>")?;
                 }
 
@@ -125,12 +189,9 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
+                    self.write_properties(&attachments)?;
                     self.write_message(&format!("<failure message=\"{m}\" type=\"assert\"/>"))?;
-                    if !stdout.is_empty() {
-                        self.write_message("<system-out>")?;
-                        self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
-                        self.write_message("</system-out>")?;
-                    }
+                    self.write_output(&stdout, &stderr, &attachments)?;
                     self.write_message("</testcase>")?;
                 }
 
@@ -142,7 +203,44 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
+                    self.write_properties(&attachments)?;
                     self.write_message("<failure type=\"timeout\"/>")?;
+                    self.write_output(&[], &[], &attachments)?;
+                    self.write_message("</testcase>")?;
+                }
+
+                TestResult::TrTimedOut => {
+                    self.write_message(&format!(
+                        "<testcase classname=\"{}\" \
+                         name=\"{}\" time=\"{}\">",
+                        class_name,
+                        test_name,
+                        duration.as_secs_f64()
+                    ))?;
+                    self.write_properties(&attachments)?;
+                    self.write_message("<failure message=\"timed out\" type=\"timeout\"/>")?;
+                    self.write_output(&stdout, &stderr, &attachments)?;
+                    self.write_message("</testcase>")?;
+                }
+
+                TestResult::TrFlaky(failures) => {
+                    self.write_message(&format!(
+                        "<testcase classname=\"{}\" \
+                         name=\"{}\" time=\"{}\">",
+                        class_name,
+                        test_name,
+                        duration.as_secs_f64()
+                    ))?;
+                    self.write_properties(&attachments)?;
+                    // Same as the `flakyFailure` element of Maven Surefire reports, with the
+                    // output of the last failed attempt.
+                    self.write_message(&format!(
+                        "<flakyFailure message=\"passed on attempt {}\" type=\"assert\">",
+                        failures + 1
+                    ))?;
+                    self.write_output(&stdout, &stderr, &[])?;
+                    self.write_message("</flakyFailure>")?;
+                    self.write_output(&[], &[], &attachments)?;
                     self.write_message("</testcase>")?;
                 }
 
@@ -162,12 +260,18 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
-                    if stdout.is_empty() || !state.options.display_output {
+                    let display_output =
+                        state.options.display_output && !(stdout.is_empty() && stderr.is_empty());
+                    if !display_output && attachments.is_empty() {
                         self.write_message("/>")?;
                     } else {
-                        self.write_message("><system-out>")?;
-                        self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
-                        self.write_message("</system-out>")?;
+                        self.write_message(">")?;
+                        self.write_properties(&attachments)?;
+                        if display_output {
+                            self.write_output(&stdout, &stderr, &attachments)?;
+                        } else {
+                            self.write_output(&[], &[], &attachments)?;
+                        }
                         self.write_message("</testcase>")?;
                     }
                 }
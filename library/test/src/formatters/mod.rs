@@ -1,6 +1,7 @@
 use std::io;
 use std::io::prelude::Write;
 
+use crate::attachment::Attachment;
 use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState};
 use crate::test_result::TestResult;
 use crate::time;
@@ -30,6 +31,8 @@ fn write_result(
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
         stdout: &[u8],
+        stderr: &[u8],
+        attachments: &[Attachment],
         state: &ConsoleTestState,
     ) -> io::Result<()>;
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool>;
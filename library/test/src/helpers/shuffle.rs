@@ -2,6 +2,7 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::cli::TestOpts;
+use crate::options::Shard;
 use crate::types::{TestDescAndFn, TestId, TestName};
 
 pub fn get_shuffle_seed(opts: &TestOpts) -> Option<u64> {
@@ -26,6 +27,12 @@ pub fn shuffle_tests(shuffle_seed: u64, tests: &mut [(TestId, TestDescAndFn)]) {
     shuffle(&mut rng, tests);
 }
 
+/// Assigns each test to a shard based on the hash of its name, so that the shard
+/// of a test doesn't change when other tests are added or removed.
+pub fn is_in_shard(shard: Shard, test_name: &TestName) -> bool {
+    calculate_hash(&test_name.as_slice()) % shard.count as u64 == shard.index as u64
+}
+
 // `shuffle` is from `rust-analyzer/src/cli/analysis_stats.rs`.
 fn shuffle<T>(rng: &mut Rng, slice: &mut [T]) {
     for i in 0..slice.len() {
@@ -28,9 +28,11 @@
 
 pub use cli::TestOpts;
 
+pub use self::attachment::{attach_file, attach_metric, Attachment};
 pub use self::bench::{black_box, Bencher};
 pub use self::console::run_tests_console;
-pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, ShouldPanic};
+pub use self::executor::block_on;
+pub use self::options::{ColorConfig, Options, OutputFormat, RunIgnored, Shard, ShouldPanic};
 pub use self::types::TestName::*;
 pub use self::types::*;
 pub use self::ColorConfig::*;
@@ -40,30 +42,36 @@ pub mod test {
     pub use crate::bench::Bencher;
     pub use crate::cli::{parse_opts, TestOpts};
     pub use crate::helpers::metrics::{Metric, MetricMap};
-    pub use crate::options::{Options, RunIgnored, RunStrategy, ShouldPanic};
+    pub use crate::options::{Options, RunIgnored, RunStrategy, Shard, ShouldPanic};
     pub use crate::test_result::{TestResult, TrFailed, TrFailedMsg, TrIgnored, TrOk};
     pub use crate::time::{TestExecTime, TestTimeOptions};
     pub use crate::types::{
-        DynTestFn, DynTestName, StaticBenchFn, StaticTestFn, StaticTestName, TestDesc,
-        TestDescAndFn, TestId, TestName, TestType,
+        DynTestFn, DynTestName, StaticBenchFn, StaticTestFn, StaticTestGenFn, StaticTestName,
+        TestCase, TestDesc, TestDescAndFn, TestId, TestName, TestType,
+    };
+    pub use crate::{
+        assert_test_result, block_on, collect_test_cases, filter_tests, generate_test_cases,
+        parse_test_timeout, run_test, test_main, test_main_static,
     };
-    pub use crate::{assert_test_result, filter_tests, run_test, test_main, test_main_static};
 }
 
 use std::collections::VecDeque;
 use std::io::prelude::Write;
 use std::mem::ManuallyDrop;
+use std::path::PathBuf;
 use std::panic::{self, catch_unwind, AssertUnwindSafe, PanicHookInfo};
 use std::process::{self, Command, Termination};
-use std::sync::mpsc::{channel, Sender};
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use std::{env, io, thread};
 
+mod attachment;
 pub mod bench;
 mod cli;
 mod console;
 mod event;
+mod executor;
 mod formatters;
 mod helpers;
 mod options;
@@ -80,7 +88,7 @@ pub mod test {
 
 use event::{CompletedTest, TestEvent};
 use helpers::concurrency::get_concurrency;
-use helpers::shuffle::{get_shuffle_seed, shuffle_tests};
+use helpers::shuffle::{get_shuffle_seed, is_in_shard, shuffle_tests};
 use options::RunStrategy;
 use test_result::*;
 use time::TestExecTime;
@@ -90,6 +98,7 @@ pub mod test {
 
 const SECONDARY_TEST_INVOKER_VAR: &str = "__RUST_TEST_INVOKE";
 const SECONDARY_TEST_BENCH_BENCHMARKS_VAR: &str = "__RUST_TEST_BENCH_BENCHMARKS";
+const SECONDARY_TEST_ATTACHMENTS_VAR: &str = "__RUST_TEST_ATTACHMENTS";
 
 // The default console test runner. It accepts the command line
 // arguments and a vector of test_descs.
@@ -120,7 +129,7 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Option<Opt
             let hook = Box::new({
                 move |info: &'_ PanicHookInfo<'_>| {
                     if !info.can_unwind() {
-                        std::mem::forget(std::io::stderr().lock());
+                        let mut stderr = ManuallyDrop::new(std::io::stderr().lock());
                         let mut stdout = ManuallyDrop::new(std::io::stdout().lock());
                         if let Some(captured) = io::set_output_capture(None) {
                             if let Ok(data) = captured.lock() {
@@ -128,6 +137,12 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Option<Opt
                                 let _ = stdout.flush();
                             }
                         }
+                        if let Some(captured) = io::set_error_capture(None) {
+                            if let Ok(data) = captured.lock() {
+                                let _ = stderr.write_all(&data);
+                                let _ = stderr.flush();
+                            }
+                        }
                     }
                     builtin_panic_hook(info);
                 }
@@ -172,9 +187,13 @@ pub fn test_main_static_abort(tests: &[&TestDescAndFn]) {
     // will then exit the process.
     if let Ok(name) = env::var(SECONDARY_TEST_INVOKER_VAR) {
         env::remove_var(SECONDARY_TEST_INVOKER_VAR);
+        if let Some(path) = env::var_os(SECONDARY_TEST_ATTACHMENTS_VAR) {
+            env::remove_var(SECONDARY_TEST_ATTACHMENTS_VAR);
+            attachment::write_to_file(PathBuf::from(path));
+        }
 
         // Convert benchmarks to tests if we're not benchmarking.
-        let mut tests = tests.iter().map(make_owned_test).collect::<Vec<_>>();
+        let mut tests = generate_test_cases(tests.iter().map(make_owned_test).collect());
         if env::var(SECONDARY_TEST_BENCH_BENCHMARKS_VAR).is_ok() {
             env::remove_var(SECONDARY_TEST_BENCH_BENCHMARKS_VAR);
         } else {
@@ -212,10 +231,58 @@ fn make_owned_test(test: &&TestDescAndFn) -> TestDescAndFn {
     match test.testfn {
         StaticTestFn(f) => TestDescAndFn { testfn: StaticTestFn(f), desc: test.desc.clone() },
         StaticBenchFn(f) => TestDescAndFn { testfn: StaticBenchFn(f), desc: test.desc.clone() },
+        StaticTestGenFn(f) => TestDescAndFn { testfn: StaticTestGenFn(f), desc: test.desc.clone() },
         _ => panic!("non-static tests passed to test::test_main_static"),
     }
 }
 
+/// Invoked by `#[test_generator]` functions to collect the cases they return.
+pub fn collect_test_cases<I: IntoIterator<Item = TestCase>>(cases: I) -> Vec<TestCase> {
+    cases.into_iter().collect()
+}
+
+/// Replaces the tests generating cases with one test per generated case. The
+/// other tests are returned unchanged.
+///
+/// A generator that panics is replaced with a single failing test.
+pub fn generate_test_cases(tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
+    let mut generated = Vec::with_capacity(tests.len());
+    for test in tests {
+        let StaticTestGenFn(generate) = test.testfn else {
+            generated.push(test);
+            continue;
+        };
+        let cases = match catch_unwind(AssertUnwindSafe(|| __rust_begin_short_backtrace(generate)))
+        {
+            Ok(cases) => cases,
+            Err(payload) => {
+                // The generating test is reported as a single failing test.
+                let panic_str = payload
+                    .downcast_ref::<String>()
+                    .map(|e| &**e)
+                    .or_else(|| payload.downcast_ref::<&'static str>().copied())
+                    .unwrap_or("Box<dyn Any>");
+                let msg = format!("the test generator panicked: {panic_str}");
+                let mut desc = test.desc;
+                desc.should_panic = ShouldPanic::No;
+                let testfn = DynTestFn(Box::new(move || panic!("{msg}")));
+                generated.push(TestDescAndFn { desc, testfn });
+                continue;
+            }
+        };
+        for case in cases {
+            let mut desc = test.desc.clone();
+            desc.name = DynTestName(format!("{}::{}", test.desc.name, case.name));
+            if case.ignore {
+                desc.ignore = true;
+                desc.ignore_message = case.ignore_message;
+            }
+            generated.push(TestDescAndFn { desc, testfn: DynTestCaseFn(case.testfn) });
+        }
+    }
+    generated
+}
+
 /// Invoked when unit tests terminate. Returns `Result::Err` if the test is
 /// considered a failure. By default, invokes `report()` and checks for a `0`
 /// result.
@@ -231,6 +298,18 @@ pub fn assert_test_result<T: Termination>(result: T) -> Result<(), String> {
     }
 }
 
+/// Invoked by tests with a `#[test_timeout]` attribute to parse its value. The
+/// test description is a constant, so an invalid value is an error at compile time.
+pub const fn parse_test_timeout(value: &str) -> Duration {
+    match cli::parse_duration(value) {
+        Some(timeout) if !timeout.is_zero() => timeout,
+        _ => panic!(
+            "invalid `#[test_timeout]` value, expected a number of seconds, \
+             or a number followed by `ms`, `s` or `m`, like `30s`"
+        ),
+    }
+}
+
 struct FilteredTests {
     tests: Vec<(TestId, TestDescAndFn)>,
     benches: Vec<(TestId, TestDescAndFn)>,
@@ -291,6 +370,19 @@ struct TimeoutEntry {
         timeout: Instant,
     }
 
+    // A copy of a test function kept around to run the test again if it fails.
+    struct RetryEntry {
+        testfn: TestFn,
+        failures: usize,
+        // The result and output of the last failed attempt.
+        result: Option<TestResult>,
+        stdout: Vec<u8>,
+        stderr: Vec<u8>,
+    }
+
+    type RetryMap = HashMap<TestId, RetryEntry, BuildHasherDefault<DefaultHasher>>;
+
+    let tests = generate_test_cases(tests);
     let tests_len = tests.len();
 
     let mut filtered = FilteredTests { tests: Vec::new(), benches: Vec::new(), next_id: 0 };
@@ -333,6 +425,8 @@ struct TimeoutEntry {
     // tests in the order they were passed (unless shuffled).
     let mut remaining = VecDeque::from(remaining);
     let mut pending = 0;
+    let mut retries: RetryMap = HashMap::default();
+    let mut next_id = filtered.next_id;
 
     let (tx, rx) = channel::<CompletedTest>();
     let run_strategy = if opts.options.panic_abort && !opts.force_run_in_process {
@@ -343,6 +437,12 @@ struct TimeoutEntry {
 
     let mut running_tests: TestMap = HashMap::default();
     let mut timeout_queue: VecDeque<TimeoutEntry> = VecDeque::new();
+    // Deadlines of tests running in-process. Tests running in a subprocess
+    // enforce their deadline themselves by killing the child process.
+    let mut deadline_queue: Vec<TimeoutEntry> = Vec::new();
+    let suite_deadline = opts.suite_timeout.map(|timeout| Instant::now() + timeout);
+    let suite_timed_out =
+        || suite_deadline.is_some_and(|suite_deadline| Instant::now() >= suite_deadline);
 
     fn get_timed_out_tests(
         running_tests: &TestMap,
@@ -362,26 +462,170 @@ fn get_timed_out_tests(
         timed_out
     }
 
-    fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
-        timeout_queue.front().map(|&TimeoutEntry { timeout: next_timeout, .. }| {
+    // A test running in-process can't be interrupted. Once its deadline has
+    // passed, it is detached and a timed out result is sent on its behalf.
+    // Whichever result is received first is reported, the other one is dropped.
+    fn expire_in_process_tests(
+        running_tests: &mut TestMap,
+        deadline_queue: &mut Vec<TimeoutEntry>,
+        tx: &Sender<CompletedTest>,
+    ) {
+        let now = Instant::now();
+        let (expired, pending): (Vec<_>, Vec<_>) =
+            std::mem::take(deadline_queue).into_iter().partition(|entry| now >= entry.timeout);
+        *deadline_queue = pending;
+        for TimeoutEntry { id, desc, .. } in expired {
+            if let Some(running_test) = running_tests.get_mut(&id) {
+                running_test.join_handle = None;
+                tx.send(CompletedTest::new(id, desc, TrTimedOut, None, Vec::new())).unwrap();
+            }
+        }
+    }
+
+    fn calc_timeout(
+        timeout_queue: &VecDeque<TimeoutEntry>,
+        deadline_queue: &[TimeoutEntry],
+    ) -> Option<Duration> {
+        let next_deadline = deadline_queue.iter().map(|entry| entry.timeout).min();
+        let next_timeout = timeout_queue.front().map(|entry| entry.timeout);
+        next_timeout.into_iter().chain(next_deadline).min().map(|next_timeout| {
             let now = Instant::now();
             if next_timeout >= now { next_timeout - now } else { Duration::new(0, 0) }
         })
     }
 
+    // Waits for the result of the test `id`, or until `deadline` has passed.
+    // Late results of tests that timed out earlier are dropped.
+    fn recv_result(
+        rx: &Receiver<CompletedTest>,
+        id: TestId,
+        deadline: Option<Instant>,
+    ) -> Option<CompletedTest> {
+        loop {
+            let res = match deadline {
+                Some(deadline) => {
+                    rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match res {
+                Ok(completed_test) if completed_test.id == id => return Some(completed_test),
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) => return None,
+                Err(RecvTimeoutError::Disconnected) => panic!("test result channel disconnected"),
+            }
+        }
+    }
+
+    // Keeps a copy of the test function if the test may have to be retried.
+    // Returns whether this is a retry, which isn't announced to the formatter again.
+    fn prepare_retry(opts: &TestOpts, retries: &mut RetryMap, id: TestId, test: &TestFn) -> bool {
+        if retries.contains_key(&id) {
+            return true;
+        }
+        if opts.retries > 0 {
+            if let Some(testfn) = test.try_clone() {
+                let entry = RetryEntry {
+                    testfn,
+                    failures: 0,
+                    result: None,
+                    stdout: Vec::new(),
+                    stderr: Vec::new(),
+                };
+                retries.insert(id, entry);
+            }
+        }
+        false
+    }
+
+    // Queues a failed test to run again if it has retries left. Otherwise returns
+    // the result to report, which is `TrFlaky` if an earlier attempt failed.
+    fn retry_or_report(
+        opts: &TestOpts,
+        retries: &mut RetryMap,
+        remaining: &mut VecDeque<(TestId, TestDescAndFn)>,
+        next_id: &mut usize,
+        mut completed_test: CompletedTest,
+    ) -> Option<CompletedTest> {
+        let Some(mut entry) = retries.remove(&completed_test.id) else {
+            // `prepare_retry` only skips the tests which can't be run again.
+            if opts.retries > 0
+                && matches!(
+                    completed_test.result,
+                    TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut
+                )
+            {
+                completed_test.stdout.extend_from_slice(
+                    b"note: dynamic tests can only be run once, so this test was not retried\n",
+                );
+            }
+            return Some(completed_test);
+        };
+        match completed_test.result {
+            TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut
+                if entry.failures < opts.retries =>
+            {
+                entry.failures += 1;
+                entry.result = Some(completed_test.result);
+                entry.stdout = completed_test.stdout;
+                entry.stderr = completed_test.stderr;
+                // The retry runs under a new id, so that a late result of an
+                // abandoned attempt can't be mistaken for the result of the retry.
+                let id = TestId(*next_id);
+                *next_id += 1;
+                let testfn = entry.testfn.try_clone().unwrap();
+                remaining.push_front((id, TestDescAndFn { desc: completed_test.desc, testfn }));
+                retries.insert(id, entry);
+                None
+            }
+            TrOk if entry.failures > 0 => {
+                // The output of the failed attempt is the interesting one.
+                completed_test.result = TrFlaky(entry.failures);
+                completed_test.stdout = entry.stdout;
+                completed_test.stderr = entry.stderr;
+                Some(completed_test)
+            }
+            _ => Some(completed_test),
+        }
+    }
+
     if concurrency == 1 {
-        while !remaining.is_empty() {
+        while !remaining.is_empty() && !suite_timed_out() {
             let (id, test) = remaining.pop_front().unwrap();
-            let event = TestEvent::TeWait(test.desc.clone());
-            notify_about_test_event(event)?;
-            let join_handle = run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
-            // Wait for the test to complete.
-            let mut completed_test = rx.recv().unwrap();
-            RunningTest { join_handle }.join(&mut completed_test);
+            let desc = test.desc.clone();
+            if !prepare_retry(opts, &mut retries, id, &test.testfn) {
+                let event = TestEvent::TeWait(desc.clone());
+                notify_about_test_event(event)?;
+            }
+            let deadline = test_deadline(opts, &desc, suite_deadline);
+            let join_handle = run_test_with_deadline(
+                opts,
+                !opts.run_tests,
+                id,
+                test,
+                run_strategy,
+                tx.clone(),
+                deadline,
+            );
+            // Wait for the test to complete. Only tests running in-process need to be
+            // timed out here, subprocesses report their own timeout.
+            let deadline = deadline.filter(|_| matches!(run_strategy, RunStrategy::InProcess));
+            let completed_test = match recv_result(&rx, id, deadline) {
+                Some(mut completed_test) => {
+                    RunningTest { join_handle }.join(&mut completed_test);
+                    completed_test
+                }
+                None => CompletedTest::new(id, desc, TrTimedOut, None, Vec::new()),
+            };
+            let Some(completed_test) =
+                retry_or_report(opts, &mut retries, &mut remaining, &mut next_id, completed_test)
+            else {
+                continue;
+            };
 
             let fail_fast = match completed_test.result {
-                TrIgnored | TrOk | TrBench(_) => false,
-                TrFailed | TrFailedMsg(_) | TrTimedFail => opts.fail_fast,
+                TrIgnored | TrOk | TrBench(_) | TrFlaky(_) => false,
+                TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut => opts.fail_fast,
             };
 
             let event = TestEvent::TeResult(completed_test);
@@ -393,28 +637,48 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
         }
     } else {
         while pending > 0 || !remaining.is_empty() {
-            while pending < concurrency && !remaining.is_empty() {
+            while pending < concurrency && !remaining.is_empty() && !suite_timed_out() {
                 let (id, test) = remaining.pop_front().unwrap();
                 let timeout = time::get_default_test_timeout();
                 let desc = test.desc.clone();
 
-                let event = TestEvent::TeWait(desc.clone());
-                notify_about_test_event(event)?; //here no pad
-                let join_handle =
-                    run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
+                if !prepare_retry(opts, &mut retries, id, &test.testfn) {
+                    let event = TestEvent::TeWait(desc.clone());
+                    notify_about_test_event(event)?; //here no pad
+                }
+                let deadline = test_deadline(opts, &desc, suite_deadline);
+                let join_handle = run_test_with_deadline(
+                    opts,
+                    !opts.run_tests,
+                    id,
+                    test,
+                    run_strategy,
+                    tx.clone(),
+                    deadline,
+                );
                 running_tests.insert(id, RunningTest { join_handle });
+                if let (RunStrategy::InProcess, Some(deadline)) = (run_strategy, deadline) {
+                    deadline_queue.push(TimeoutEntry { id, desc: desc.clone(), timeout: deadline });
+                }
                 timeout_queue.push_back(TimeoutEntry { id, desc, timeout });
                 pending += 1;
             }
 
+            if pending == 0 {
+                // The suite timed out before all tests could be started, they are
+                // reported below.
+                break;
+            }
+
             let mut res;
             loop {
-                if let Some(timeout) = calc_timeout(&timeout_queue) {
+                if let Some(timeout) = calc_timeout(&timeout_queue, &deadline_queue) {
                     res = rx.recv_timeout(timeout);
                     for test in get_timed_out_tests(&running_tests, &mut timeout_queue) {
                         let event = TestEvent::TeTimeout(test);
                         notify_about_test_event(event)?;
                     }
+                    expire_in_process_tests(&mut running_tests, &mut deadline_queue, &tx);
 
                     match res {
                         Err(RecvTimeoutError::Timeout) => {
@@ -432,17 +696,26 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
             }
 
             let mut completed_test = res.unwrap();
-            let running_test = running_tests.remove(&completed_test.id).unwrap();
+            let Some(running_test) = running_tests.remove(&completed_test.id) else {
+                // A result for this test has already been reported.
+                continue;
+            };
+            deadline_queue.retain(|entry| entry.id != completed_test.id);
             running_test.join(&mut completed_test);
+            pending -= 1;
+            let Some(completed_test) =
+                retry_or_report(opts, &mut retries, &mut remaining, &mut next_id, completed_test)
+            else {
+                continue;
+            };
 
             let fail_fast = match completed_test.result {
-                TrIgnored | TrOk | TrBench(_) => false,
-                TrFailed | TrFailedMsg(_) | TrTimedFail => opts.fail_fast,
+                TrIgnored | TrOk | TrBench(_) | TrFlaky(_) => false,
+                TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut => opts.fail_fast,
             };
 
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
-            pending -= 1;
 
             if fail_fast {
                 // Prevent remaining test threads from panicking
@@ -452,14 +725,41 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
         }
     }
 
+    // The suite timed out before these tests could be started. A test that was
+    // waiting to be retried is reported with the result of its last attempt.
+    for (id, test) in remaining {
+        let completed_test = match retries.remove(&id) {
+            Some(RetryEntry { result: Some(result), stdout, stderr, .. }) => {
+                let mut completed_test = CompletedTest::new(id, test.desc, result, None, stdout);
+                completed_test.stderr = stderr;
+                completed_test
+            }
+            _ => {
+                let event = TestEvent::TeWait(test.desc.clone());
+                notify_about_test_event(event)?;
+                CompletedTest::new(id, test.desc, TrTimedOut, None, Vec::new())
+            }
+        };
+        let event = TestEvent::TeResult(completed_test);
+        notify_about_test_event(event)?;
+    }
+
     if opts.bench_benchmarks {
         // All benchmarks run at the end, in serial.
         for (id, b) in filtered.benches {
+            if suite_timed_out() {
+                let event = TestEvent::TeWait(b.desc.clone());
+                notify_about_test_event(event)?;
+                let completed_test = CompletedTest::new(id, b.desc, TrTimedOut, None, Vec::new());
+                let event = TestEvent::TeResult(completed_test);
+                notify_about_test_event(event)?;
+                continue;
+            }
             let event = TestEvent::TeWait(b.desc.clone());
             notify_about_test_event(event)?;
             let join_handle = run_test(opts, false, id, b, run_strategy, tx.clone());
             // Wait for the test to complete.
-            let mut completed_test = rx.recv().unwrap();
+            let mut completed_test = recv_result(&rx, id, None).unwrap();
             RunningTest { join_handle }.join(&mut completed_test);
 
             let event = TestEvent::TeResult(completed_test);
@@ -495,6 +795,11 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         filtered.retain(|test| test.desc.should_panic == ShouldPanic::No);
     }
 
+    // Keep only the tests of this shard
+    if let Some(shard) = opts.shard {
+        filtered.retain(|test| is_in_shard(shard, &test.desc.name));
+    }
+
     // maybe unignore tests
     match opts.run_ignored {
         RunIgnored::Yes => {
@@ -532,6 +837,44 @@ pub fn run_test(
     test: TestDescAndFn,
     strategy: RunStrategy,
     monitor_ch: Sender<CompletedTest>,
+) -> Option<thread::JoinHandle<()>> {
+    let deadline = test_deadline(opts, &test.desc, None);
+    run_test_with_deadline(opts, force_ignore, id, test, strategy, monitor_ch, deadline)
+}
+
+/// Returns the point in time after which a test started now has timed out.
+fn test_deadline(
+    opts: &TestOpts,
+    desc: &TestDesc,
+    suite_deadline: Option<Instant>,
+) -> Option<Instant> {
+    let test_deadline = desc_timeout(desc).or(opts.test_timeout).map(|t| Instant::now() + t);
+    match (test_deadline, suite_deadline) {
+        (Some(test_deadline), Some(suite_deadline)) => Some(test_deadline.min(suite_deadline)),
+        (test_deadline, suite_deadline) => test_deadline.or(suite_deadline),
+    }
+}
+
+#[cfg(not(bootstrap))]
+fn desc_timeout(desc: &TestDesc) -> Option<Duration> {
+    desc.timeout
+}
+
+#[cfg(bootstrap)]
+fn desc_timeout(_: &TestDesc) -> Option<Duration> {
+    None
+}
+
+// Only tests running in a subprocess are timed out here, the runner is
+// responsible for abandoning tests that run in-process.
+fn run_test_with_deadline(
+    opts: &TestOpts,
+    force_ignore: bool,
+    id: TestId,
+    test: TestDescAndFn,
+    strategy: RunStrategy,
+    monitor_ch: Sender<CompletedTest>,
+    deadline: Option<Instant>,
 ) -> Option<thread::JoinHandle<()>> {
     let TestDescAndFn { desc, testfn } = test;
 
@@ -559,12 +902,15 @@ pub fn run_test(
             let nocapture = opts.nocapture;
             let time_options = opts.time_options;
             let bench_benchmarks = opts.bench_benchmarks;
+            // Only the machine-readable formats report stderr apart from stdout.
+            let split_output = matches!(opts.format, OutputFormat::Json | OutputFormat::Junit);
 
             let runtest = move || match strategy {
                 RunStrategy::InProcess => run_test_in_process(
                     id,
                     desc,
                     nocapture,
+                    split_output,
                     time_options.is_some(),
                     runnable_test,
                     monitor_ch,
@@ -574,10 +920,12 @@ pub fn run_test(
                     id,
                     desc,
                     nocapture,
+                    split_output,
                     time_options.is_some(),
                     monitor_ch,
                     time_options,
                     bench_benchmarks,
+                    deadline,
                 ),
             };
 
@@ -627,17 +975,23 @@ fn run_test_in_process(
     id: TestId,
     desc: TestDesc,
     nocapture: bool,
+    split_output: bool,
     report_time: bool,
     runnable_test: RunnableTest,
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
 ) {
-    // Buffer for capturing standard I/O
+    // Buffers for capturing standard I/O
     let data = Arc::new(Mutex::new(Vec::new()));
+    let error_data = Arc::new(Mutex::new(Vec::new()));
 
     if !nocapture {
         io::set_output_capture(Some(data.clone()));
+        if split_output {
+            io::set_error_capture(Some(error_data.clone()));
+        }
     }
+    attachment::start_collecting();
 
     let start = report_time.then(Instant::now);
     let result = fold_err(catch_unwind(AssertUnwindSafe(|| runnable_test.run())));
@@ -647,13 +1001,18 @@ fn run_test_in_process(
     });
 
     io::set_output_capture(None);
+    io::set_error_capture(None);
+    let attachments = attachment::finish_collecting();
 
     let test_result = match result {
         Ok(()) => calc_result(&desc, Ok(()), &time_opts, &exec_time),
         Err(e) => calc_result(&desc, Err(e.as_ref()), &time_opts, &exec_time),
     };
     let stdout = data.lock().unwrap_or_else(|e| e.into_inner()).to_vec();
-    let message = CompletedTest::new(id, desc, test_result, exec_time, stdout);
+    let stderr = error_data.lock().unwrap_or_else(|e| e.into_inner()).to_vec();
+    let mut message = CompletedTest::new(id, desc, test_result, exec_time, stdout);
+    message.stderr = stderr;
+    message.attachments = attachments;
     monitor_ch.send(message).unwrap();
 }
 
@@ -674,31 +1033,45 @@ fn spawn_test_subprocess(
     id: TestId,
     desc: TestDesc,
     nocapture: bool,
+    split_output: bool,
     report_time: bool,
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
     bench_benchmarks: bool,
+    deadline: Option<Instant>,
 ) {
-    let (result, test_output, exec_time) = (|| {
+    // The attachments of the test are dropped if their file can't be created.
+    let attachments_path = attachment::create_file(id).ok();
+    let (result, test_output, test_error_output, exec_time) = (|| {
         let args = env::args().collect::<Vec<_>>();
         let current_exe = &args[0];
 
         let mut command = Command::new(current_exe);
         command.env(SECONDARY_TEST_INVOKER_VAR, desc.name.as_slice());
+        if let Some(attachments_path) = &attachments_path {
+            command.env(SECONDARY_TEST_ATTACHMENTS_VAR, attachments_path);
+        }
         if bench_benchmarks {
             command.env(SECONDARY_TEST_BENCH_BENCHMARKS_VAR, "1");
         }
         if nocapture {
             command.stdout(process::Stdio::inherit());
             command.stderr(process::Stdio::inherit());
+        } else {
+            command.stdout(process::Stdio::piped());
+            command.stderr(process::Stdio::piped());
         }
 
         let start = report_time.then(Instant::now);
-        let output = match command.output() {
+        let output = match deadline {
+            Some(deadline) => output_with_deadline(&mut command, deadline),
+            None => command.output().map(|output| (output, false)),
+        };
+        let (output, timed_out) = match output {
             Ok(out) => out,
             Err(e) => {
                 let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
-                return (TrFailed, err.into_bytes(), None);
+                return (TrFailed, err.into_bytes(), Vec::new(), None);
             }
         };
         let exec_time = start.map(|start| {
@@ -706,19 +1079,89 @@ fn spawn_test_subprocess(
             TestExecTime(duration)
         });
 
-        let std::process::Output { stdout, stderr, status } = output;
+        let std::process::Output { stdout, mut stderr, status } = output;
         let mut test_output = stdout;
-        formatters::write_stderr_delimiter(&mut test_output, &desc.name);
-        test_output.extend_from_slice(&stderr);
+        if !split_output {
+            formatters::write_stderr_delimiter(&mut test_output, &desc.name);
+            test_output.append(&mut stderr);
+        }
 
-        let result = get_result_from_exit_code(&desc, status, &time_opts, &exec_time);
-        (result, test_output, exec_time)
+        let result = if timed_out {
+            TrTimedOut
+        } else {
+            get_result_from_exit_code(&desc, status, &time_opts, &exec_time)
+        };
+        (result, test_output, stderr, exec_time)
     })();
 
-    let message = CompletedTest::new(id, desc, result, exec_time, test_output);
+    let mut message = CompletedTest::new(id, desc, result, exec_time, test_output);
+    message.stderr = test_error_output;
+    message.attachments =
+        attachments_path.as_deref().map(attachment::read_file).unwrap_or_default();
     monitor_ch.send(message).unwrap();
 }
 
+/// Like `Command::output`, but kills the child process if it is still running
+/// once `deadline` has passed. Also returns whether the child was killed.
+fn output_with_deadline(
+    command: &mut Command,
+    deadline: Instant,
+) -> io::Result<(process::Output, bool)> {
+    use std::io::Read;
+
+    // Collects the output of the child as it comes in, so that whatever was
+    // written before a timeout can still be reported.
+    fn read_pipe<R: Read + Send + 'static>(
+        pipe: Option<R>,
+    ) -> (Arc<Mutex<Vec<u8>>>, Option<thread::JoinHandle<()>>) {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let reader = pipe.map(|mut pipe| {
+            let buf = buf.clone();
+            thread::spawn(move || {
+                let mut chunk = [0; 4096];
+                loop {
+                    match pipe.read(&mut chunk) {
+                        Ok(0) => break,
+                        Ok(n) => buf.lock().unwrap().extend_from_slice(&chunk[..n]),
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                        Err(_) => break,
+                    }
+                }
+            })
+        });
+        (buf, reader)
+    }
+
+    command.stdin(process::Stdio::null());
+    let mut child = command.spawn()?;
+    let (stdout, stdout_reader) = read_pipe(child.stdout.take());
+    let (stderr, stderr_reader) = read_pipe(child.stderr.take());
+
+    let (status, timed_out) = loop {
+        if let Some(status) = child.try_wait()? {
+            break (status, false);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            // The child may have exited just now, so a failure to kill it is fine.
+            let _ = child.kill();
+            break (child.wait()?, true);
+        }
+        thread::sleep((deadline - now).min(Duration::from_millis(10)));
+    };
+
+    // Processes spawned by the test may keep the pipes open, so don't wait for
+    // the readers of a child that had to be killed.
+    if !timed_out {
+        for reader in [stdout_reader, stderr_reader].into_iter().flatten() {
+            let _ = reader.join();
+        }
+    }
+
+    let take = |buf: Arc<Mutex<Vec<u8>>>| std::mem::take(&mut *buf.lock().unwrap());
+    Ok((process::Output { status, stdout: take(stdout), stderr: take(stderr) }, timed_out))
+}
+
 fn run_test_in_spawned_subprocess(desc: TestDesc, runnable_test: RunnableTest) -> ! {
     let builtin_panic_hook = panic::take_hook();
     let record_result = Arc::new(move |panic_info: Option<&'_ PanicHookInfo<'_>>| {
@@ -47,6 +47,16 @@ pub enum RunIgnored {
     Only,
 }
 
+/// The part of the tests to run when they are split across several test runs,
+/// for example on different machines.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Shard {
+    /// Index of this shard, starting at 0.
+    pub index: usize,
+    /// Total number of shards.
+    pub count: usize,
+}
+
 #[derive(Clone, Copy)]
 pub enum RunStrategy {
     /// Runs the test in the current process, and sends the result back over the
@@ -300,3 +300,94 @@ pub fn winsorize(samples: &mut [f64], pct: f64) {
         }
     }
 }
+
+/// Welch's t-test of the hypothesis that two sets of samples have the same
+/// mean, without assuming that they have the same variance. Returns the
+/// two-sided p-value, the probability of a difference of means at least as
+/// large as the observed one if the hypothesis is true.
+///
+/// Both sets need at least two samples.
+///
+/// See: <https://en.wikipedia.org/wiki/Welch%27s_t-test>
+pub fn welch_t_test(a: &[f64], b: &[f64]) -> f64 {
+    assert!(a.len() >= 2 && b.len() >= 2);
+    let (len_a, len_b) = (a.len() as f64, b.len() as f64);
+    let (var_a, var_b) = (a.var() / len_a, b.var() / len_b);
+    let var = var_a + var_b;
+    if var == 0.0 {
+        // Both sets are constant, so their means are either certainly equal or not.
+        return if a.mean() == b.mean() { 1.0 } else { 0.0 };
+    }
+    let t = (a.mean() - b.mean()) / var.sqrt();
+    // Welch-Satterthwaite approximation of the degrees of freedom.
+    let df = var * var / (var_a * var_a / (len_a - 1.0) + var_b * var_b / (len_b - 1.0));
+    // Two-sided tail of Student's t-distribution with `df` degrees of freedom.
+    incomplete_beta(df / 2.0, 0.5, df / (df + t * t))
+}
+
+// Regularized incomplete beta function I_x(a, b), evaluated with the continued
+// fraction from "Numerical Recipes", section 6.4.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The continued fraction converges quickly below this point, so above it
+    // the symmetry I_x(a, b) = 1 - I_(1-x)(b, a) is used instead.
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+// Evaluates the continued fraction of the incomplete beta function with the
+// modified Lentz's method.
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let not_tiny = |v: f64| if v.abs() < TINY { TINY } else { v };
+
+    let mut c = 1.0;
+    let mut d = 1.0 / not_tiny(1.0 - (a + b) * x / (a + 1.0));
+    let mut h = d;
+    for m in 1..300 {
+        let m = m as f64;
+        let (even, odd) = (
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        );
+        for coefficient in [even, odd] {
+            d = 1.0 / not_tiny(1.0 + coefficient * d);
+            c = not_tiny(1.0 + coefficient / c);
+            h *= d * c;
+        }
+        if (d * c - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+    h
+}
+
+// Natural logarithm of the gamma function for `x > 0`, using the Lanczos
+// approximation from "Numerical Recipes", section 6.1.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000000000190015, |series, (i, c)| series + c / (x + 1.0 + i as f64));
+    (2.5066282746310005 * series / x).ln() - tmp
+}
@@ -35,6 +35,11 @@ pub enum TestResult {
     TrIgnored,
     TrBench(BenchSamples),
     TrTimedFail,
+    /// The test was still running when its timeout expired.
+    TrTimedOut,
+    /// The test failed, but passed when it was run again. Holds the number of
+    /// failed attempts.
+    TrFlaky(usize),
 }
 
 /// Creates a `TestResult` depending on the raw result of test execution
@@ -1,5 +1,8 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use super::*;
 use crate::{
+    bench::{Baseline, BenchVerdict},
     console::OutputLocation,
     formatters::PrettyFormatter,
     test::{
@@ -29,9 +32,15 @@ fn new() -> TestOpts {
             format: OutputFormat::Pretty,
             shuffle: false,
             shuffle_seed: None,
+            shard: None,
             test_threads: None,
             skip: vec![],
             time_options: None,
+            test_timeout: None,
+            suite_timeout: None,
+            retries: 0,
+            save_baseline: None,
+            baseline: None,
             options: Options::new(),
             fail_fast: false,
         }
@@ -51,6 +60,8 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 end_line: 0,
                 end_col: 0,
                 should_panic: ShouldPanic::No,
+                #[cfg(not(bootstrap))]
+                timeout: None,
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
@@ -68,6 +79,8 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 end_line: 0,
                 end_col: 0,
                 should_panic: ShouldPanic::No,
+                #[cfg(not(bootstrap))]
+                timeout: None,
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
@@ -93,6 +106,8 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::No,
+            #[cfg(not(bootstrap))]
+            timeout: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -121,6 +136,8 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::No,
+            #[cfg(not(bootstrap))]
+            timeout: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -152,6 +169,8 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::Yes,
+            #[cfg(not(bootstrap))]
+            timeout: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -183,6 +202,8 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::YesWithMessage("error message"),
+            #[cfg(not(bootstrap))]
+            timeout: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -219,6 +240,8 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::YesWithMessage(expected),
+            #[cfg(not(bootstrap))]
+            timeout: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -260,6 +283,8 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::YesWithMessage(expected),
+            #[cfg(not(bootstrap))]
+            timeout: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -294,6 +319,8 @@ fn f() -> Result<(), String> {
                 end_line: 0,
                 end_col: 0,
                 should_panic,
+                #[cfg(not(bootstrap))]
+                timeout: None,
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
@@ -327,6 +354,8 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::No,
+            #[cfg(not(bootstrap))]
+            timeout: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -369,6 +398,8 @@ fn f() -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::No,
+            #[cfg(not(bootstrap))]
+            timeout: None,
             compile_fail: false,
             no_run: false,
             test_type,
@@ -413,6 +444,8 @@ fn typed_test_desc(test_type: TestType) -> TestDesc {
         end_line: 0,
         end_col: 0,
         should_panic: ShouldPanic::No,
+        #[cfg(not(bootstrap))]
+        timeout: None,
         compile_fail: false,
         no_run: false,
         test_type,
@@ -478,6 +511,65 @@ fn parse_include_ignored_flag() {
     assert_eq!(opts.run_ignored, RunIgnored::Yes);
 }
 
+#[test]
+fn parse_timeout_options() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--test-timeout".to_string(),
+        "500ms".to_string(),
+        "--suite-timeout".to_string(),
+        "5m".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.test_timeout, Some(Duration::from_millis(500)));
+    assert_eq!(opts.suite_timeout, Some(Duration::from_secs(300)));
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--test-timeout=30".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.test_timeout, Some(Duration::from_secs(30)));
+
+    for bad in ["0s", "1h", "ms", "-1s"] {
+        let args = vec![
+            "progname".to_string(),
+            "-Zunstable-options".to_string(),
+            format!("--test-timeout={bad}"),
+        ];
+        assert!(parse_opts(&args).unwrap().is_err(), "--test-timeout={bad} was accepted");
+    }
+}
+
+#[test]
+fn parse_test_timeout_attribute() {
+    // `#[test_timeout]` values are parsed while evaluating the test's constant.
+    const TIMEOUT: Duration = parse_test_timeout("2m");
+    assert_eq!(TIMEOUT, Duration::from_secs(120));
+    assert_eq!(parse_test_timeout("250ms"), Duration::from_millis(250));
+}
+
+#[test]
+fn parse_retries_option() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--retries".to_string(),
+        "3".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.retries, 3);
+
+    let args = vec!["progname".to_string(), "--retries=3".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+
+    let args =
+        vec!["progname".to_string(), "-Zunstable-options".to_string(), "--retries=x".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
 #[test]
 pub fn filter_for_ignored_option() {
     // When we run ignored tests the test filter should filter out all the
@@ -530,6 +622,8 @@ pub fn exclude_should_panic_option() {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::Yes,
+            #[cfg(not(bootstrap))]
+            timeout: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -559,6 +653,8 @@ fn tests() -> Vec<TestDescAndFn> {
                     end_line: 0,
                     end_col: 0,
                     should_panic: ShouldPanic::No,
+                    #[cfg(not(bootstrap))]
+                    timeout: None,
                     compile_fail: false,
                     no_run: false,
                     test_type: TestType::Unknown,
@@ -655,6 +751,8 @@ fn testfn() -> Result<(), String> {
                 end_line: 0,
                 end_col: 0,
                 should_panic: ShouldPanic::No,
+                #[cfg(not(bootstrap))]
+                timeout: None,
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
@@ -731,6 +829,55 @@ pub fn order_depends_on_more_than_seed() {
     assert!(left.iter().zip(right).any(|(a, b)| a.0 != b.0));
 }
 
+#[test]
+pub fn shards_partition_tests() {
+    let all_names = sample_tests().into_iter().map(|t| t.desc.name.to_string()).collect::<Vec<_>>();
+
+    let mut sharded_names = Vec::new();
+    for index in 0..3 {
+        let opts = TestOpts { shard: Some(Shard { index, count: 3 }), ..TestOpts::new() };
+        let names = filter_tests(&opts, sample_tests())
+            .into_iter()
+            .map(|t| t.desc.name.to_string())
+            .collect::<Vec<_>>();
+        // The same shard always contains the same tests.
+        let names_again = filter_tests(&opts, sample_tests())
+            .into_iter()
+            .map(|t| t.desc.name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, names_again);
+        sharded_names.extend(names);
+    }
+
+    sharded_names.sort();
+    let mut all_names = all_names;
+    all_names.sort();
+    assert_eq!(sharded_names, all_names);
+}
+
+#[test]
+fn parse_shard_options() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--shard-index=1".to_string(),
+        "--shard-count=4".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.shard, Some(Shard { index: 1, count: 4 }));
+
+    for bad in [
+        &["--shard-index=1"][..],
+        &["--shard-count=4"],
+        &["--shard-index=4", "--shard-count=4"],
+        &["--shard-index=0", "--shard-count=0"],
+    ] {
+        let mut args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        args.extend(bad.iter().map(|arg| arg.to_string()));
+        assert!(parse_opts(&args).unwrap().is_err(), "{bad:?} was accepted");
+    }
+}
+
 #[test]
 pub fn test_metricmap_compare() {
     let mut m1 = MetricMap::new();
@@ -789,6 +936,8 @@ fn f(_: &mut Bencher) -> Result<(), String> {
         end_line: 0,
         end_col: 0,
         should_panic: ShouldPanic::No,
+        #[cfg(not(bootstrap))]
+        timeout: None,
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
@@ -817,6 +966,8 @@ fn f(b: &mut Bencher) -> Result<(), String> {
         end_line: 0,
         end_col: 0,
         should_panic: ShouldPanic::No,
+        #[cfg(not(bootstrap))]
+        timeout: None,
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
@@ -826,6 +977,62 @@ fn f(b: &mut Bencher) -> Result<(), String> {
     rx.recv().unwrap();
 }
 
+#[test]
+fn test_baseline_comparison() {
+    let samples = |median: f64| -> Vec<f64> { (0..50).map(|i| median + (i % 5) as f64).collect() };
+    let mut baseline = Baseline::default();
+    baseline.insert("bench", &samples(100.0));
+    let verdict = |median| baseline.compare("bench", &samples(median)).unwrap().verdict;
+
+    assert_eq!(verdict(120.0), BenchVerdict::Regressed);
+    assert_eq!(verdict(80.0), BenchVerdict::Improved);
+    assert_eq!(verdict(100.0), BenchVerdict::Unchanged);
+    // Significant, but within the noise threshold.
+    assert_eq!(verdict(101.0), BenchVerdict::Unchanged);
+
+    let comparison = baseline.compare("bench", &samples(120.0)).unwrap();
+    assert_eq!(comparison.baseline_median, 102.0);
+    assert!((comparison.change - 0.2 / 1.02).abs() < 1e-9);
+    assert!(comparison.p_value < 0.05);
+    assert_eq!(baseline.compare("other", &samples(100.0)), None);
+}
+
+#[test]
+fn test_baseline_save_and_load() {
+    let path = env::temp_dir().join(format!("rust-test-baseline-{}", process::id()));
+    let mut baseline = Baseline::default();
+    baseline.insert("bench::a", &[1.5, 2.0, 2.5]);
+    baseline.insert("bench::b", &[10.0, 11.0]);
+    baseline.save(&path).unwrap();
+    let loaded = Baseline::load(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    let loaded = loaded.unwrap();
+    assert_eq!(loaded.compare("bench::a", &[1.5, 2.0, 2.5]).unwrap().change, 0.0);
+    assert_eq!(loaded.compare("bench::b", &[10.0, 11.0]).unwrap().baseline_median, 10.5);
+}
+
+#[test]
+fn parse_baseline_options() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--bench".to_string(),
+        "--save-baseline=new".to_string(),
+        "--baseline=old".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.save_baseline, Some(PathBuf::from("new")));
+    assert_eq!(opts.baseline, Some(PathBuf::from("old")));
+
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--baseline=old".to_string(),
+    ];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
 #[test]
 fn should_sort_failures_before_printing_them() {
     let test_a = TestDesc {
@@ -838,6 +1045,8 @@ fn should_sort_failures_before_printing_them() {
         end_line: 0,
         end_col: 0,
         should_panic: ShouldPanic::No,
+        #[cfg(not(bootstrap))]
+        timeout: None,
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
@@ -853,6 +1062,8 @@ fn should_sort_failures_before_printing_them() {
         end_line: 0,
         end_col: 0,
         should_panic: ShouldPanic::No,
+        #[cfg(not(bootstrap))]
+        timeout: None,
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
@@ -865,6 +1076,7 @@ fn should_sort_failures_before_printing_them() {
         total: 0,
         passed: 0,
         failed: 0,
+        flaky: 0,
         ignored: 0,
         filtered_out: 0,
         measured: 0,
@@ -875,6 +1087,7 @@ fn should_sort_failures_before_printing_them() {
         not_failures: Vec::new(),
         ignores: Vec::new(),
         time_failures: Vec::new(),
+        flaky_tests: Vec::new(),
     };
 
     out.write_failures(&st).unwrap();
@@ -905,6 +1118,8 @@ fn f(_: &mut Bencher) -> Result<(), String> {
             end_line: 0,
             end_col: 0,
             should_panic: ShouldPanic::No,
+            #[cfg(not(bootstrap))]
+            timeout: None,
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
@@ -922,3 +1137,381 @@ fn f(_: &mut Bencher) -> Result<(), String> {
     let result = rx.recv().unwrap().result;
     assert_eq!(result, TrFailed);
 }
+
+fn hanging_test(name: &'static str) -> TestDescAndFn {
+    TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName(name),
+            ignore: false,
+            ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            start_col: 0,
+            end_line: 0,
+            end_col: 0,
+            should_panic: ShouldPanic::No,
+            #[cfg(not(bootstrap))]
+            timeout: None,
+            compile_fail: false,
+            no_run: false,
+            test_type: TestType::Unknown,
+        },
+        testfn: DynTestFn(Box::new(|| {
+            loop {
+                thread::park();
+            }
+        })),
+    }
+}
+
+fn run_tests_collecting_results(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<CompletedTest> {
+    let mut results = Vec::new();
+    let notify = |event: TestEvent| {
+        if let TestEvent::TeResult(result) = event {
+            results.push(result);
+        }
+        Ok(())
+    };
+    run_tests(opts, tests, notify).unwrap();
+    results
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_timeout_fails_hanging_test() {
+    for test_threads in [1, 2] {
+        let mut tests = one_ignored_one_unignored_test();
+        tests.push(hanging_test("hangs"));
+        let opts = TestOpts {
+            run_tests: true,
+            test_threads: Some(test_threads),
+            test_timeout: Some(Duration::from_millis(100)),
+            ..TestOpts::new()
+        };
+        let results = run_tests_collecting_results(&opts, tests);
+
+        let result_of = |name: &str| {
+            results.iter().find(|test| test.desc.name.as_slice() == name).unwrap().result.clone()
+        };
+        assert_eq!(results.len(), 3);
+        assert_eq!(result_of("1"), TrIgnored);
+        assert_eq!(result_of("2"), TrOk);
+        assert_eq!(result_of("hangs"), TrTimedOut);
+    }
+}
+
+#[test]
+#[cfg(not(any(target_os = "emscripten", bootstrap)))]
+fn test_own_timeout_overrides_test_timeout() {
+    let mut test = hanging_test("hangs");
+    test.desc.timeout = Some(Duration::from_millis(100));
+    let opts = TestOpts {
+        run_tests: true,
+        test_timeout: Some(Duration::from_secs(600)),
+        ..TestOpts::new()
+    };
+    let start = Instant::now();
+    let results = run_tests_collecting_results(&opts, vec![test]);
+    assert_eq!(results[0].result, TrTimedOut);
+    assert!(start.elapsed() < Duration::from_secs(600));
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_suite_timeout_stops_run() {
+    for test_threads in [1, 2] {
+        let opts = TestOpts {
+            run_tests: true,
+            test_threads: Some(test_threads),
+            suite_timeout: Some(Duration::from_millis(100)),
+            ..TestOpts::new()
+        };
+        let tests = vec![hanging_test("a"), hanging_test("b"), hanging_test("c")];
+        let results = run_tests_collecting_results(&opts, tests);
+
+        // The running tests time out with the suite, and the tests that were never started
+        // are reported as timed out too.
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|test| test.result == TrTimedOut));
+    }
+}
+
+fn test_with_fn(name: &'static str, testfn: TestFn) -> TestDescAndFn {
+    TestDescAndFn {
+        desc: TestDesc {
+            name: StaticTestName(name),
+            ignore: false,
+            ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            start_col: 0,
+            end_line: 0,
+            end_col: 0,
+            should_panic: ShouldPanic::No,
+            #[cfg(not(bootstrap))]
+            timeout: None,
+            compile_fail: false,
+            no_run: false,
+            test_type: TestType::Unknown,
+        },
+        testfn,
+    }
+}
+
+static FLAKY_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+// Fails on the first two runs.
+fn flaky_test() -> Result<(), String> {
+    if FLAKY_RUNS.fetch_add(1, Ordering::SeqCst) < 2 { Err("flaked".to_string()) } else { Ok(()) }
+}
+
+#[test]
+fn test_retries_report_flaky_test() {
+    for test_threads in [1, 2] {
+        FLAKY_RUNS.store(0, Ordering::SeqCst);
+        let tests = vec![
+            test_with_fn("flaky", StaticTestFn(flaky_test)),
+            test_with_fn("fails", StaticTestFn(|| Err("failed".to_string()))),
+            test_with_fn("passes", StaticTestFn(|| Ok(()))),
+        ];
+        let opts = TestOpts {
+            run_tests: true,
+            test_threads: Some(test_threads),
+            retries: 2,
+            ..TestOpts::new()
+        };
+        let results = run_tests_collecting_results(&opts, tests);
+
+        let result_of = |name: &str| {
+            results.iter().find(|test| test.desc.name.as_slice() == name).unwrap().result.clone()
+        };
+        assert_eq!(results.len(), 3);
+        assert_eq!(result_of("flaky"), TrFlaky(2));
+        assert_eq!(result_of("fails"), TrFailed);
+        assert_eq!(result_of("passes"), TrOk);
+        assert_eq!(FLAKY_RUNS.load(Ordering::SeqCst), 3);
+    }
+}
+
+static FAILING_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+fn failing_test() -> Result<(), String> {
+    FAILING_RUNS.fetch_add(1, Ordering::SeqCst);
+    Err("failed".to_string())
+}
+
+#[test]
+fn test_retries_exhausted_reports_failure() {
+    let opts = TestOpts { run_tests: true, retries: 2, ..TestOpts::new() };
+    let tests = vec![test_with_fn("fails", StaticTestFn(failing_test))];
+    let results = run_tests_collecting_results(&opts, tests);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].result, TrFailed);
+    assert_eq!(FAILING_RUNS.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_retries_with_suite_timeout_report_last_failure() {
+    fn hangs() -> Result<(), String> {
+        loop {
+            thread::park();
+        }
+    }
+
+    for test_threads in [1, 2] {
+        let opts = TestOpts {
+            run_tests: true,
+            test_threads: Some(test_threads),
+            retries: 3,
+            suite_timeout: Some(Duration::from_millis(100)),
+            ..TestOpts::new()
+        };
+        let tests = vec![test_with_fn("hangs", StaticTestFn(hangs))];
+        let results = run_tests_collecting_results(&opts, tests);
+
+        // The suite times out before the test can be retried, so the result of the first
+        // attempt is reported.
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].desc.name.as_slice(), "hangs");
+        assert_eq!(results[0].result, TrTimedOut);
+    }
+}
+
+#[test]
+fn test_retries_skip_dynamic_tests() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let runs2 = runs.clone();
+    let test = test_with_fn(
+        "dynamic",
+        DynTestFn(Box::new(move || {
+            runs2.fetch_add(1, Ordering::SeqCst);
+            Err("failed".to_string())
+        })),
+    );
+    let opts = TestOpts { run_tests: true, retries: 3, ..TestOpts::new() };
+    let results = run_tests_collecting_results(&opts, vec![test]);
+    assert_eq!(results[0].result, TrFailed);
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    let stdout = String::from_utf8_lossy(&results[0].stdout);
+    assert!(stdout.contains("this test was not retried"), "{stdout}");
+}
+
+#[test]
+fn test_attachments_are_collected() {
+    let test = test_with_fn(
+        "attaches",
+        DynTestFn(Box::new(|| {
+            attach_file("log", "target/test.log");
+            attach_metric("requests per second", 12.5);
+            Ok(())
+        })),
+    );
+    let opts = TestOpts { run_tests: true, ..TestOpts::new() };
+    let results = run_tests_collecting_results(&opts, vec![test]);
+    assert_eq!(
+        results[0].attachments,
+        vec![
+            Attachment::File { name: "log".to_string(), path: "target/test.log".into() },
+            Attachment::Metric { name: "requests per second".to_string(), value: 12.5 },
+        ]
+    );
+}
+
+#[test]
+fn test_stderr_is_captured_separately_for_json() {
+    let run_with_format = |format| {
+        let test = test_with_fn(
+            "prints",
+            DynTestFn(Box::new(|| {
+                print!("out");
+                eprint!("err");
+                Ok(())
+            })),
+        );
+        let opts = TestOpts { run_tests: true, format, ..TestOpts::new() };
+        run_tests_collecting_results(&opts, vec![test]).remove(0)
+    };
+
+    let pretty = run_with_format(OutputFormat::Pretty);
+    assert_eq!(pretty.stdout, b"outerr");
+    assert!(pretty.stderr.is_empty());
+
+    let json = run_with_format(OutputFormat::Json);
+    assert_eq!(json.stdout, b"out");
+    assert_eq!(json.stderr, b"err");
+}
+
+// Returns `Pending` the first time it's polled, after arranging for the task to
+// be woken from another thread.
+struct WokenFromThread(bool);
+
+impl std::future::Future for WokenFromThread {
+    type Output = u32;
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<u32> {
+        if self.0 {
+            return std::task::Poll::Ready(42);
+        }
+        self.0 = true;
+        let waker = cx.waker().clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            waker.wake();
+        });
+        std::task::Poll::Pending
+    }
+}
+
+#[test]
+fn test_block_on() {
+    assert_eq!(block_on(async { 1 + 1 }), 2);
+    assert_eq!(block_on(WokenFromThread(false)), 42);
+}
+
+#[test]
+fn test_block_on_should_panic() {
+    let mut test = test_with_fn(
+        "panics",
+        DynTestFn(Box::new(|| {
+            block_on(async {
+                WokenFromThread(false).await;
+                panic!("an error message")
+            })
+        })),
+    );
+    test.desc.should_panic = ShouldPanic::YesWithMessage("error message");
+    let opts = TestOpts { run_tests: true, ..TestOpts::new() };
+    let results = run_tests_collecting_results(&opts, vec![test]);
+    assert_eq!(results[0].result, TrOk);
+}
+
+fn generate_cases() -> Vec<TestCase> {
+    vec![
+        TestCase::new("passes", || {}),
+        TestCase::new("fails", || -> Result<(), String> { Err("failed".to_string()) }),
+        TestCase::new("ignored", || {}).ignore_with_message("not yet implemented"),
+    ]
+}
+
+#[test]
+fn test_generated_cases_are_reported_separately() {
+    let test = test_with_fn("parametrized", StaticTestGenFn(generate_cases));
+    let opts = TestOpts { run_tests: true, ..TestOpts::new() };
+    let mut results: Vec<_> = run_tests_collecting_results(&opts, vec![test])
+        .into_iter()
+        .map(|result| (result.desc.name.to_string(), result.desc.ignore_message, result.result))
+        .collect();
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        results,
+        vec![
+            ("parametrized::fails".to_string(), None, TrFailed),
+            ("parametrized::ignored".to_string(), Some("not yet implemented"), TrIgnored),
+            ("parametrized::passes".to_string(), None, TrOk),
+        ]
+    );
+}
+
+#[test]
+fn test_generated_cases_can_be_filtered() {
+    let test = test_with_fn("parametrized", StaticTestGenFn(generate_cases));
+    let opts = TestOpts {
+        run_tests: true,
+        filters: vec!["parametrized::passes".to_string()],
+        filter_exact: true,
+        ..TestOpts::new()
+    };
+    let mut filtered_out = 0;
+    let mut results = Vec::new();
+    run_tests(&opts, vec![test], |event| {
+        match event {
+            TestEvent::TeFilteredOut(n) => filtered_out = n,
+            TestEvent::TeResult(result) => results.push(result.desc.name.to_string()),
+            _ => {}
+        }
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(results, ["parametrized::passes"]);
+    assert_eq!(filtered_out, 2);
+}
+
+#[test]
+fn test_panicking_generator_is_reported_as_failure() {
+    fn generate_panics() -> Vec<TestCase> {
+        panic!("no cases");
+    }
+
+    let test = test_with_fn("parametrized", StaticTestGenFn(generate_panics));
+    let opts = TestOpts { run_tests: true, ..TestOpts::new() };
+    let results = run_tests_collecting_results(&opts, vec![test]);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].desc.name.as_slice(), "parametrized");
+    assert_eq!(results[0].result, TrFailed);
+    let stdout = String::from_utf8_lossy(&results[0].stdout);
+    assert!(stdout.contains("the test generator panicked: no cases"), "{stdout}");
+}
@@ -2,6 +2,7 @@
 
 use std::borrow::Cow;
 use std::fmt;
+use std::process::Termination;
 use std::sync::mpsc::Sender;
 
 pub use NamePadding::*;
@@ -10,7 +11,7 @@
 
 use super::bench::Bencher;
 use super::event::CompletedTest;
-use super::{__rust_begin_short_backtrace, options};
+use super::{__rust_begin_short_backtrace, assert_test_result, options};
 
 /// Type of the test according to the [Rust book](https://doc.rust-lang.org/cargo/guide/tests.html)
 /// conventions.
@@ -88,6 +89,12 @@ pub enum TestFn {
     DynTestFn(Box<dyn FnOnce() -> Result<(), String> + Send>),
     DynBenchFn(Box<dyn Fn(&mut Bencher) -> Result<(), String> + Send>),
     DynBenchAsTestFn(Box<dyn Fn(&mut Bencher) -> Result<(), String> + Send>),
+    /// Generates the cases of a parametrized test, which are run as separate
+    /// tests named after it.
+    StaticTestGenFn(fn() -> Vec<TestCase>),
+    /// A case generated by a `StaticTestGenFn`. Unlike other dynamic tests, it
+    /// can run in a subprocess, which generates the cases again to find it.
+    DynTestCaseFn(Box<dyn FnOnce() -> Result<(), String> + Send>),
 }
 
 impl TestFn {
@@ -99,6 +106,20 @@ pub fn padding(&self) -> NamePadding {
             DynTestFn(..) => PadNone,
             DynBenchFn(..) => PadOnRight,
             DynBenchAsTestFn(..) => PadNone,
+            StaticTestGenFn(..) => PadNone,
+            DynTestCaseFn(..) => PadNone,
+        }
+    }
+
+    /// Returns a copy of the test function, so that the test can be run again.
+    /// Dynamic test functions, including the generated cases, can only be run once.
+    pub(crate) fn try_clone(&self) -> Option<TestFn> {
+        match *self {
+            StaticTestFn(f) => Some(StaticTestFn(f)),
+            StaticBenchFn(f) => Some(StaticBenchFn(f)),
+            StaticBenchAsTestFn(f) => Some(StaticBenchAsTestFn(f)),
+            StaticTestGenFn(f) => Some(StaticTestGenFn(f)),
+            DynTestFn(..) | DynBenchFn(..) | DynBenchAsTestFn(..) | DynTestCaseFn(..) => None,
         }
     }
 
@@ -110,6 +131,8 @@ pub(crate) fn into_runnable(self) -> Runnable {
             DynTestFn(f) => Runnable::Test(RunnableTest::Dynamic(f)),
             DynBenchFn(f) => Runnable::Bench(RunnableBench::Dynamic(f)),
             DynBenchAsTestFn(f) => Runnable::Test(RunnableTest::DynamicBenchAsTest(f)),
+            StaticTestGenFn(..) => panic!("test cases must be generated before running tests"),
+            DynTestCaseFn(f) => Runnable::Test(RunnableTest::GeneratedCase(f)),
         }
     }
 }
@@ -123,6 +146,8 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             DynTestFn(..) => "DynTestFn(..)",
             DynBenchFn(..) => "DynBenchFn(..)",
             DynBenchAsTestFn(..) => "DynBenchAsTestFn(..)",
+            StaticTestGenFn(..) => "StaticTestGenFn(..)",
+            DynTestCaseFn(..) => "DynTestCaseFn(..)",
         })
     }
 }
@@ -137,13 +162,16 @@ pub(crate) enum RunnableTest {
     Dynamic(Box<dyn FnOnce() -> Result<(), String> + Send>),
     StaticBenchAsTest(fn(&mut Bencher) -> Result<(), String>),
     DynamicBenchAsTest(Box<dyn Fn(&mut Bencher) -> Result<(), String> + Send>),
+    GeneratedCase(Box<dyn FnOnce() -> Result<(), String> + Send>),
 }
 
 impl RunnableTest {
     pub(crate) fn run(self) -> Result<(), String> {
         match self {
             RunnableTest::Static(f) => __rust_begin_short_backtrace(f),
-            RunnableTest::Dynamic(f) => __rust_begin_short_backtrace(f),
+            RunnableTest::Dynamic(f) | RunnableTest::GeneratedCase(f) => {
+                __rust_begin_short_backtrace(f)
+            }
             RunnableTest::StaticBenchAsTest(f) => {
                 crate::bench::run_once(|b| __rust_begin_short_backtrace(|| f(b)))
             }
@@ -159,6 +187,8 @@ pub(crate) fn is_dynamic(&self) -> bool {
             RunnableTest::StaticBenchAsTest(_) => false,
             RunnableTest::Dynamic(_) => true,
             RunnableTest::DynamicBenchAsTest(_) => true,
+            // The case can be found again by generating the cases in a subprocess.
+            RunnableTest::GeneratedCase(_) => false,
         }
     }
 }
@@ -187,6 +217,48 @@ pub(crate) fn run(
     }
 }
 
+/// A case of a parametrized test, returned by a `#[test]` function marked with
+/// `#[test_generator]`.
+///
+/// Each case is reported as a separate test, named after the generating test
+/// followed by the name of the case, like `parse::empty_input`.
+pub struct TestCase {
+    pub(crate) name: String,
+    pub(crate) ignore: bool,
+    pub(crate) ignore_message: Option<&'static str>,
+    pub(crate) testfn: Box<dyn FnOnce() -> Result<(), String> + Send>,
+}
+
+impl TestCase {
+    /// Creates a case that runs `f`, which fails the case by panicking or by
+    /// returning a failing [`Termination`], like a `#[test]` function.
+    pub fn new<F, T>(name: impl Into<String>, f: F) -> TestCase
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Termination,
+    {
+        TestCase {
+            name: name.into(),
+            ignore: false,
+            ignore_message: None,
+            testfn: Box::new(move || assert_test_result(f())),
+        }
+    }
+
+    /// Ignores the case, as `#[ignore]` would.
+    pub fn ignore(mut self) -> TestCase {
+        self.ignore = true;
+        self
+    }
+
+    /// Ignores the case with a reason, as `#[ignore = "reason"]` would.
+    pub fn ignore_with_message(mut self, message: &'static str) -> TestCase {
+        self.ignore = true;
+        self.ignore_message = Some(message);
+        self
+    }
+}
+
 // A unique integer associated with each test.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct TestId(pub usize);
@@ -204,6 +276,9 @@ pub struct TestDesc {
     pub end_line: usize,
     pub end_col: usize,
     pub should_panic: options::ShouldPanic,
+    /// Time limit set through `#[test_timeout]`, overriding `--test-timeout`.
+    #[cfg(not(bootstrap))]
+    pub timeout: Option<std::time::Duration>,
     pub compile_fail: bool,
     pub no_run: bool,
     pub test_type: TestType,
@@ -278,6 +353,8 @@ pub const fn new_doctest(
                 } else {
                     options::ShouldPanic::No
                 },
+                #[cfg(not(bootstrap))]
+                timeout: None,
                 test_type: TestType::DocTest,
             },
             testfn,
@@ -1944,6 +1944,9 @@ fn run(self, builder: &Builder<'_>) -> Compiler {
         dist::maybe_install_llvm_runtime(builder, target_compiler.host, &sysroot);
         dist::maybe_install_llvm_target(builder, target_compiler.host, &sysroot);
 
+        // Make the translations of the diagnostics available to `-Z translate-lang`.
+        dist::install_locales(builder, &sysroot);
+
         // Link the compiler binary itself into place
         let out_dir = builder.cargo_out(build_compiler, Mode::Rustc, host);
         let rustc = out_dir.join(exe("rustc-main", host));
@@ -495,6 +495,9 @@ fn prepare_image(builder: &Builder<'_>, compiler: Compiler, image: &Path) {
                 t!(fs::copy(&page_src, &page_dst));
             }
 
+            // Translations of the diagnostics
+            install_locales(builder, image);
+
             // Debugger scripts
             builder.ensure(DebuggerScripts { sysroot: image.to_owned(), host });
 
@@ -2095,6 +2098,22 @@ pub fn maybe_install_llvm_runtime(builder: &Builder<'_>, target: TargetSelection
     }
 }
 
+/// Add the Fluent bundles of `compiler/rustc_error_messages/locales` to `share/locale` in the
+/// sysroot, where `-Z translate-lang` looks for them.
+pub fn install_locales(builder: &Builder<'_>, sysroot: &Path) {
+    let src = builder.src.join("compiler/rustc_error_messages/locales");
+    if !src.is_dir() {
+        return;
+    }
+    let dst = sysroot.join("share/locale");
+    t!(fs::create_dir_all(&dst));
+    // Only the locale directories and the `.ftl` files in them, not the README.
+    builder.cp_link_filtered(&src, &dst, &|path| match path.extension() {
+        Some(extension) => path.components().count() == 2 && extension == "ftl",
+        None => path.components().count() == 1,
+    });
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct LlvmTools {
     pub target: TargetSelection,
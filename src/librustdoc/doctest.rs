@@ -891,6 +891,8 @@ fn generate_test_desc_and_fn(
             end_col: 0,
             // compiler failures are test failures
             should_panic: test::ShouldPanic::No,
+            #[cfg(not(bootstrap))]
+            timeout: None,
             compile_fail: scraped_test.langstr.compile_fail,
             no_run: scraped_test.no_run(&rustdoc_options),
             test_type: test::TestType::DocTest,
@@ -122,6 +122,7 @@ pub enum CompareMode {
         NextSolverCoherence => "next-solver-coherence",
         SplitDwarf => "split-dwarf",
         SplitDwarfSingle => "split-dwarf-single",
+        AnnotateSnippets => "annotate-snippets",
     }
 }
 
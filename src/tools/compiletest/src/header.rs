@@ -1436,6 +1436,8 @@ macro_rules! decision {
         end_line: 0,
         end_col: 0,
         should_panic,
+        #[cfg(not(bootstrap))]
+        timeout: None,
         compile_fail: false,
         no_run: false,
         test_type: test::TestType::Unknown,
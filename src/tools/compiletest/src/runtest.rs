@@ -2290,6 +2290,15 @@ fn make_compile_args(
             Some(CompareMode::SplitDwarfSingle) => {
                 rustc.args(&["-Csplit-debuginfo=packed"]);
             }
+            Some(CompareMode::AnnotateSnippets) => {
+                // UI tests compare the `rendered` field of JSON diagnostics, so render it with
+                // the `annotate-snippets` emitter. Tests setting `--error-format` are unaffected.
+                if self.config.mode == Ui
+                    && !self.props.compile_flags.iter().any(|s| s.starts_with("--error-format"))
+                {
+                    rustc.args(&["--json", "diagnostic-annotate-rs", "-Zunstable-options"]);
+                }
+            }
             None => {}
         }
 
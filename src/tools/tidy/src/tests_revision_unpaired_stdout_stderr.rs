@@ -16,6 +16,7 @@
     "split-dwarf-single",
     "next-solver-coherence",
     "next-solver",
+    "annotate-snippets",
     "run",
 ];
 const EXTENSIONS: &[&str] = &["stdout", "stderr"];
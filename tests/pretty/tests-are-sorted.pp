@@ -26,6 +26,7 @@ pub const m_test: test::TestDescAndFn =
             compile_fail: false,
             no_run: false,
             should_panic: test::ShouldPanic::No,
+            timeout: ::core::option::Option::None,
             test_type: test::TestType::Unknown,
         },
         testfn: test::StaticTestFn(#[coverage(off)] ||
@@ -50,6 +51,7 @@ pub const z_test: test::TestDescAndFn =
             compile_fail: false,
             no_run: false,
             should_panic: test::ShouldPanic::No,
+            timeout: ::core::option::Option::None,
             test_type: test::TestType::Unknown,
         },
         testfn: test::StaticTestFn(#[coverage(off)] ||
@@ -75,6 +77,7 @@ pub const a_test: test::TestDescAndFn =
             compile_fail: false,
             no_run: false,
             should_panic: test::ShouldPanic::No,
+            timeout: ::core::option::Option::None,
             test_type: test::TestType::Unknown,
         },
         testfn: test::StaticTestFn(#[coverage(off)] ||
@@ -0,0 +1,39 @@
+// `--apply-suggestions` applies the machine-applicable suggestions of the chosen lints to the
+// sources, or writes a diff of them, and only reports the diagnostics left afterwards.
+
+use run_make_support::{rfs, rustc};
+
+const SOURCE: &str = "use std::fmt;\n\npub fn f() -> i32 {\n    let mut x = ((1));\n    x\n}\n";
+
+fn main() {
+    rfs::write("lib.rs", SOURCE);
+
+    let output = rustc()
+        .input("lib.rs")
+        .crate_type("lib")
+        .arg("-Zunstable-options")
+        .arg("--apply-suggestions=unused-parens")
+        .arg("--apply-suggestions-diff=-")
+        .run();
+    let diff = output.stdout_utf8();
+    assert!(diff.starts_with("--- lib.rs\n+++ lib.rs\n"), "{diff}");
+    assert!(diff.contains("\n-    let mut x = ((1));\n+    let mut x = 1;\n"), "{diff}");
+    let stderr = output.stderr_utf8();
+    assert!(!stderr.contains("unnecessary parentheses"), "{stderr}");
+    assert!(stderr.contains("unused import: `std::fmt`"), "{stderr}");
+    assert!(stderr.contains("variable does not need to be mutable"), "{stderr}");
+    assert_eq!(rfs::read_to_string("lib.rs"), SOURCE);
+
+    let stderr = rustc()
+        .input("lib.rs")
+        .crate_type("lib")
+        .arg("-Zunstable-options")
+        .arg("--apply-suggestions=unused_parens,unused_mut")
+        .arg("--apply-suggestions=unused_imports")
+        .run()
+        .stderr_utf8();
+    assert_eq!(stderr, "");
+    let fixed = rfs::read_to_string("lib.rs");
+    assert!(!fixed.contains("use std::fmt;"), "{fixed}");
+    assert!(fixed.contains("    let x = 1;\n"), "{fixed}");
+}
@@ -0,0 +1,54 @@
+#![allow(dead_code)]
+
+use std::hint::black_box;
+
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+pub enum Shape {
+    Circle(f32),
+    Rect { w: u32, h: u32 },
+}
+
+pub enum Void {}
+
+// `Dead` is uninhabited, so the layout of `OnlyLive` only has the `Live` variant.
+pub enum OnlyLive {
+    Live(u32),
+    Dead(Void),
+}
+
+pub trait Area {
+    fn area(&self) -> i32;
+}
+
+impl Area for Point {
+    fn area(&self) -> i32 {
+        self.x * self.y
+    }
+}
+
+static POINT: Point = Point { x: 2, y: 3 };
+static SHAPE: Shape = Shape::Rect { w: 2, h: 3 };
+static ONLY_LIVE: OnlyLive = OnlyLive::Live(1);
+static SLICE: &[u8] = &[1, 2, 3];
+static NAME: &str = "name";
+static AREA: &(dyn Area + Sync) = &POINT;
+
+#[inline(never)]
+fn make_closure(offset: i32) -> impl Fn(i32) -> i32 {
+    move |x| x + offset
+}
+
+#[inline(never)]
+fn boxed_slice() -> Box<[u16]> {
+    Box::new([1, 2, 3])
+}
+
+fn main() {
+    black_box((&POINT, &SHAPE, &ONLY_LIVE, SLICE, NAME, AREA));
+    black_box(make_closure(1)(2));
+    black_box(boxed_slice());
+}
@@ -0,0 +1,149 @@
+//@ ignore-windows
+// Checks how ADTs, closures, slices, trait objects and fat pointers are described in DWARF. The
+// types are looked up through the statics and functions of `main.rs` using them.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use gimli::{DebuggingInformationEntry, Dwarf, EndianRcSlice, Reader, RunTimeEndian, Unit};
+use object::{Object, ObjectSection};
+use run_make_support::{gimli, object, rfs, rustc};
+
+type R = EndianRcSlice<RunTimeEndian>;
+
+/// A type as described by its DWARF entry.
+#[derive(Debug)]
+struct Type {
+    tag: gimli::DwTag,
+    name: Option<String>,
+    /// The names of the direct `DW_TAG_member` children.
+    members: Vec<String>,
+    /// The names of the members of each `DW_TAG_variant` of the `DW_TAG_variant_part`, if any.
+    variants: Vec<String>,
+    /// Whether the unit describing the type was produced by cg_clif.
+    is_cranelift: bool,
+}
+
+fn main() {
+    let output = PathBuf::from("main");
+    rustc().input("main.rs").output(&output).arg("-Cdebuginfo=2").run();
+    // Mach-O uses packed debug info
+    let dsym_location =
+        output.with_extension("dSYM").join("Contents").join("Resources").join("DWARF").join("main");
+    let output =
+        rfs::read(if dsym_location.try_exists().unwrap() { dsym_location } else { output });
+    let obj = object::File::parse(output.as_slice()).unwrap();
+    let endian = if obj.is_little_endian() { RunTimeEndian::Little } else { RunTimeEndian::Big };
+    let dwarf = gimli::Dwarf::load(|section| -> Result<_, ()> {
+        let data = obj.section_by_name(section.name()).map(|s| s.uncompressed_data().unwrap());
+        Ok(EndianRcSlice::new(Rc::from(data.unwrap_or_default().as_ref()), endian))
+    })
+    .unwrap();
+
+    let items =
+        ["POINT", "SHAPE", "ONLY_LIVE", "SLICE", "NAME", "AREA", "make_closure", "boxed_slice"];
+    let mut types = HashMap::new();
+    let mut iter = dwarf.units();
+    while let Some(header) = iter.next().unwrap() {
+        let unit = dwarf.unit(header).unwrap();
+        let mut is_cranelift = false;
+        let mut cursor = unit.entries();
+        while let Some((_, entry)) = cursor.next_dfs().unwrap() {
+            if entry.tag() == gimli::DW_TAG_compile_unit {
+                let producer = entry.attr_value(gimli::DW_AT_producer).unwrap().unwrap();
+                let producer = dwarf.attr_string(&unit, producer).unwrap();
+                is_cranelift = producer.to_string_lossy().unwrap().contains("cranelift");
+                continue;
+            }
+            if entry.tag() != gimli::DW_TAG_variable && entry.tag() != gimli::DW_TAG_subprogram {
+                continue;
+            }
+            let Some(name) = entry_name(&dwarf, &unit, entry) else { continue };
+            if !items.contains(&name.as_str()) {
+                continue;
+            }
+            if let Some(gimli::AttributeValue::UnitRef(offset)) =
+                entry.attr_value(gimli::DW_AT_type).unwrap()
+            {
+                let mut ty = describe_type(&dwarf, &unit, offset);
+                ty.is_cranelift = is_cranelift;
+                types.insert(name, ty);
+            }
+        }
+    }
+    let ty = |name: &str| types.get(name).unwrap_or_else(|| panic!("no type found for {name}"));
+
+    let point = ty("POINT");
+    assert_eq!(point.tag, gimli::DW_TAG_structure_type, "{point:?}");
+    assert_eq!(point.name.as_deref(), Some("Point"), "{point:?}");
+    assert_eq!(point.members, ["x", "y"], "{point:?}");
+
+    let shape = ty("SHAPE");
+    assert_eq!(shape.tag, gimli::DW_TAG_structure_type, "{shape:?}");
+    assert_eq!(shape.name.as_deref(), Some("Shape"), "{shape:?}");
+    assert_eq!(shape.variants, ["Circle", "Rect"], "{shape:?}");
+
+    let only_live = ty("ONLY_LIVE");
+    assert_eq!(only_live.name.as_deref(), Some("OnlyLive"), "{only_live:?}");
+    assert!(only_live.variants.iter().any(|variant| variant == "Live"), "{only_live:?}");
+    // cg_clif only describes the variant which is present in the layout.
+    if only_live.is_cranelift {
+        assert_eq!(only_live.variants, ["Live"], "{only_live:?}");
+    }
+
+    for slice in [ty("SLICE"), ty("NAME"), ty("boxed_slice")] {
+        assert_eq!(slice.tag, gimli::DW_TAG_structure_type, "{slice:?}");
+        assert_eq!(slice.members, ["data_ptr", "length"], "{slice:?}");
+    }
+
+    let area = ty("AREA");
+    assert_eq!(area.tag, gimli::DW_TAG_structure_type, "{area:?}");
+    assert_eq!(area.members, ["pointer", "vtable"], "{area:?}");
+
+    let closure = ty("make_closure");
+    assert_eq!(closure.tag, gimli::DW_TAG_structure_type, "{closure:?}");
+    assert_eq!(closure.name.as_deref(), Some("{closure_env#0}"), "{closure:?}");
+    assert_eq!(closure.members, ["offset"], "{closure:?}");
+}
+
+fn entry_name(
+    dwarf: &Dwarf<R>,
+    unit: &Unit<R>,
+    entry: &DebuggingInformationEntry<'_, '_, R>,
+) -> Option<String> {
+    let value = entry.attr_value(gimli::DW_AT_name).unwrap()?;
+    Some(dwarf.attr_string(unit, value).unwrap().to_string_lossy().unwrap().into_owned())
+}
+
+fn describe_type(dwarf: &Dwarf<R>, unit: &Unit<R>, offset: gimli::UnitOffset) -> Type {
+    let mut tree = unit.entries_tree(Some(offset)).unwrap();
+    let root = tree.root().unwrap();
+    let mut ty = Type {
+        tag: root.entry().tag(),
+        name: entry_name(dwarf, unit, root.entry()),
+        members: vec![],
+        variants: vec![],
+        is_cranelift: false,
+    };
+    let mut children = root.children();
+    while let Some(child) = children.next().unwrap() {
+        match child.entry().tag() {
+            gimli::DW_TAG_member => ty.members.extend(entry_name(dwarf, unit, child.entry())),
+            gimli::DW_TAG_variant_part => {
+                let mut variants = child.children();
+                while let Some(variant) = variants.next().unwrap() {
+                    if variant.entry().tag() != gimli::DW_TAG_variant {
+                        continue;
+                    }
+                    let mut members = variant.children();
+                    while let Some(member) = members.next().unwrap() {
+                        ty.variants.extend(entry_name(dwarf, unit, member.entry()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    ty
+}
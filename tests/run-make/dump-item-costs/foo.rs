@@ -0,0 +1,7 @@
+pub fn generic<T: Default>() -> T {
+    T::default()
+}
+
+pub fn bar() -> (u32, u64) {
+    (generic(), generic())
+}
@@ -0,0 +1,39 @@
+// `-Z dump-item-costs` reports the code size and codegen time of each item, with the
+// instantiations of generics attributed to the local items requesting them. The copies in codegen
+// units reused from the incremental cache are counted separately, since they have no costs.
+
+use run_make_support::serde_json::Value;
+use run_make_support::{cwd, rfs, rustc, serde_json};
+
+fn dump_item_costs() -> Vec<Value> {
+    rustc()
+        .crate_type("lib")
+        .input("foo.rs")
+        .incremental("incr")
+        .arg(format!("-Zdump-item-costs={}", cwd().display()))
+        .arg("-Zdump-item-costs-format=json")
+        .run();
+    let items: Value = serde_json::from_str(&rfs::read_to_string("foo.item_costs.json")).unwrap();
+    items.as_array().unwrap().clone()
+}
+
+fn main() {
+    let items = dump_item_costs();
+
+    let generic = items.iter().find(|item| item["name"] == "generic").unwrap();
+    assert_eq!(generic["crate"], "foo");
+    assert_eq!(generic["instantiations"], 2);
+    assert_eq!(generic["reused_copies"], 0);
+    assert_eq!(generic["requested_by"], serde_json::json!(["bar"]));
+    assert!(generic["llvm_ir_size"].as_u64().unwrap() > 0);
+    assert!(generic["codegen_time"].as_f64().is_some());
+    assert_eq!(generic["instances"].as_array().unwrap().len(), 2);
+
+    assert!(items.iter().any(|item| item["name"] == "bar"));
+
+    // Nothing changed, so all codegen units are reused.
+    let items = dump_item_costs();
+    let generic = items.iter().find(|item| item["name"] == "generic").unwrap();
+    assert_eq!(generic["reused_copies"], generic["copies"]);
+    assert!(generic["llvm_ir_size"].is_null());
+}
@@ -0,0 +1,52 @@
+// `--lint-baseline` records the lint warnings of a crate when the baseline file doesn't exist
+// yet, and afterwards only reports warnings that aren't in it, even if the code moved around.
+
+use run_make_support::{rfs, rustc};
+
+fn compile(deny_warnings: bool) -> String {
+    let mut rustc = rustc();
+    rustc
+        .input("lib.rs")
+        .crate_type("lib")
+        .arg("-Zunstable-options")
+        .arg("--lint-baseline=baseline.txt");
+    if deny_warnings {
+        rustc.arg("-Dwarnings");
+    }
+    rustc.run().stderr_utf8()
+}
+
+fn main() {
+    rfs::write("lib.rs", "pub fn f() {\n    let unused = 1;\n}\n");
+    let stderr = compile(false);
+    assert!(stderr.contains("unused variable: `unused`"), "{stderr}");
+    let baseline = rfs::read_to_string("baseline.txt");
+    assert!(baseline.contains("unused_variables\tlib.rs\t"), "{baseline}");
+
+    // The recorded warning is suppressed, also when it would be an error.
+    assert_eq!(compile(false), "");
+    assert_eq!(compile(true), "");
+
+    // Moving or reformatting the code keeps the warning known. Only new occurrences are reported,
+    // including further ones of the same code.
+    rfs::write(
+        "lib.rs",
+        "// Moved down a line.\n\
+         pub fn f() {\n    let   unused = 1;\n}\n\
+         pub fn g() {\n    let unused = 1;\n    let other = 2;\n}\n",
+    );
+    let stderr = compile(false);
+    assert!(stderr.contains("unused variable: `other`"), "{stderr}");
+    assert_eq!(stderr.matches("unused variable: `unused`").count(), 1, "{stderr}");
+    assert!(stderr.contains("lib.rs:6:9"), "{stderr}");
+
+    // Warnings replayed from the incremental cache would bypass the baseline.
+    rustc()
+        .input("lib.rs")
+        .crate_type("lib")
+        .arg("-Zunstable-options")
+        .arg("--lint-baseline=baseline.txt")
+        .incremental("incr")
+        .run_fail()
+        .assert_stderr_contains("`--lint-baseline` is incompatible with incremental compilation");
+}
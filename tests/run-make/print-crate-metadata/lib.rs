@@ -0,0 +1,16 @@
+#![feature(rustc_attrs)]
+
+extern crate dep;
+
+mod inner {
+    pub struct Thing;
+
+    pub fn hidden() {}
+}
+
+pub use inner::Thing;
+
+#[cfg(feature = "std")]
+pub fn with_std() {
+    dep::dep();
+}
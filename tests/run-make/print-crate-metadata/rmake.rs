@@ -0,0 +1,49 @@
+// The `crate-items-json`, `crate-deps-json` and `crate-cfg-json` print requests describe the
+// crate being compiled, after expanding it, without compiling it any further.
+
+use run_make_support::serde_json::{self, Value};
+use run_make_support::{path, rust_lib_name, rustc};
+
+fn print(kind: &str) -> Value {
+    let output = rustc()
+        .input("lib.rs")
+        .crate_type("lib")
+        .arg(format!("--print={kind}"))
+        .arg("-Zunstable-options")
+        .extern_("dep", rust_lib_name("dep"))
+        .cfg(r#"feature="std""#)
+        .run();
+    serde_json::from_str(&output.stdout_utf8()).unwrap()
+}
+
+fn main() {
+    rustc().input("dep.rs").crate_type("rlib").run();
+
+    let items = print("crate-items-json");
+    assert_eq!(items["name"], "lib");
+    let items = items["items"].as_array().unwrap();
+    let thing = items.iter().find(|item| item["path"] == "inner::Thing").unwrap();
+    assert_eq!(thing["kind"], "struct");
+    assert_eq!(thing["directly_public"], false);
+    assert_eq!(thing["file"], "lib.rs");
+    assert_eq!(thing["line"], 6);
+    assert!(items.iter().any(|item| item["path"] == "with_std" && item["kind"] == "function"));
+    assert!(!items.iter().any(|item| item["path"] == "inner::hidden"));
+
+    let deps = print("crate-deps-json");
+    let crates = deps["crates"].as_array().unwrap();
+    let dep = crates.iter().find(|krate| krate["name"] == "dep").unwrap();
+    assert_eq!(dep["kind"], "explicit");
+    assert!(path(dep["rlib"].as_str().unwrap()).ends_with(rust_lib_name("dep")));
+    assert!(deps["dependencies"].as_array().unwrap().contains(&dep["id"]));
+    let std = crates.iter().find(|krate| krate["name"] == "std").unwrap();
+    assert!(dep["dependencies"].as_array().unwrap().contains(&std["id"]));
+
+    let cfg = print("crate-cfg-json");
+    assert_eq!(cfg["features"], serde_json::json!(["std"]));
+    assert!(cfg["cfg"].as_array().unwrap().iter().any(|cfg| cfg["name"] == "target_os"));
+    assert_eq!(cfg["unstable_features"], serde_json::json!(["rustc_attrs"]));
+
+    // Nothing was compiled.
+    assert!(!path(rust_lib_name("lib")).exists());
+}
@@ -0,0 +1,4 @@
+fn main() {
+    let X = 1;
+    let y: u8 = "one";
+}
@@ -0,0 +1,43 @@
+// `--error-format=sarif` writes all diagnostics as one SARIF log on stderr,
+// with lints and error codes as rules and suggestions as fixes.
+
+use run_make_support::{rustc, serde_json};
+
+fn main() {
+    let output =
+        rustc().input("main.rs").arg("-Zunstable-options").error_format("sarif").run_fail();
+    let log: serde_json::Value =
+        serde_json::from_str(&output.stderr_utf8()).expect("stderr is not a single SARIF log");
+    assert_eq!(log["version"], "2.1.0");
+
+    let run = &log["runs"][0];
+    let results = run["results"].as_array().unwrap();
+    let result_for = |rule: &str| {
+        results.iter().find(|result| result["ruleId"] == rule).unwrap_or_else(|| {
+            panic!("no result for `{rule}` in {results:#?}");
+        })
+    };
+
+    let lint = result_for("non_snake_case");
+    assert_eq!(lint["level"], "warning");
+    let region = &lint["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(region["startLine"], 2);
+    assert_eq!(region["startColumn"], 9);
+    let replacement = &lint["fixes"][0]["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["insertedContent"]["text"], "x");
+
+    let error = result_for("E0308");
+    assert_eq!(error["level"], "error");
+    assert_eq!(error["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], "main.rs");
+    let rule = &run["tool"]["driver"]["rules"][error["ruleIndex"].as_u64().unwrap() as usize];
+    assert_eq!(rule["id"], "E0308");
+    let explanation = rule["fullDescription"]["text"].as_str().unwrap();
+    assert!(explanation.starts_with("Expected type did not match"));
+
+    // Without `-Zunstable-options`, the format is rejected.
+    rustc()
+        .input("main.rs")
+        .error_format("sarif")
+        .run_fail()
+        .assert_stderr_contains("`--error-format=sarif` is unstable");
+}
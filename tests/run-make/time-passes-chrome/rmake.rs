@@ -0,0 +1,37 @@
+// `-Z time-passes-format=chrome` writes the passes, queries and codegen activities to a
+// Chrome trace event file in the current directory instead of printing them, without needing
+// `-Z self-profile`. No `measureme` data is written then.
+
+use run_make_support::serde_json::Value;
+use run_make_support::{has_suffix, rfs, rustc, serde_json, shallow_find_files};
+
+fn main() {
+    let output = rustc()
+        .input("main.rs")
+        .crate_name("main")
+        .arg("-Ztime-passes")
+        .arg("-Ztime-passes-format=chrome")
+        .run();
+    assert!(!output.stderr_utf8().contains("time:"));
+
+    let traces = shallow_find_files(".", |path| has_suffix(path, ".trace.json"));
+    let [trace] = &traces[..] else { panic!("expected a single trace, found {traces:?}") };
+    let profiles = shallow_find_files(".", |path| has_suffix(path, ".mm_profdata"));
+    assert!(profiles.is_empty(), "unexpected `measureme` data: {profiles:?}");
+    let trace: Value = serde_json::from_str(&rfs::read_to_string(trace)).unwrap();
+    let events = trace["traceEvents"].as_array().unwrap();
+
+    let has_event = |name: &str, cat: &str| {
+        events
+            .iter()
+            .any(|event| event["name"] == name && event["cat"] == cat && event["ph"] == "X")
+    };
+    assert!(has_event("typeck", "Query"));
+    assert!(has_event("codegen_crate", "GenericActivity"));
+    assert!(has_event("LLVM_module_codegen", "GenericActivity"));
+    assert!(
+        events
+            .iter()
+            .any(|event| event["name"] == "process_name" && event["args"]["name"] == "main")
+    );
+}
@@ -0,0 +1,158 @@
+//@ run-pass
+//! Test that users are able to instrument a body using the mutable visitor and the body builder.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+//@ edition: 2021
+
+#![feature(rustc_private)]
+#![feature(assert_matches)]
+#![feature(control_flow_enum)]
+
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::mir::visit::{Location, PlaceContext};
+use stable_mir::mir::{
+    BasicBlock, Body, BodyBuilder, Local, MirVisitor, MutMirVisitor, Mutability, Operand, Place,
+    Rvalue, Statement, StatementKind, Terminator, TerminatorKind,
+};
+use stable_mir::{CrateDef, CrateItems, ItemKind};
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+fn test_builder() -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+    let add = get_item(&items, (ItemKind::Fn, "add")).unwrap();
+    let body = add.body();
+    let orig_uses = LocalUses::count(&body, 1);
+    assert!(orig_uses > 1);
+
+    // Redirect every use of the first argument to a new local, which is initialized with a copy
+    // of the argument at the start of the function.
+    let mut builder = BodyBuilder::from_body(body.clone());
+    let arg_ty = builder.locals()[1].ty;
+    let copy = builder.new_local(arg_ty, body.span, Mutability::Not);
+    for bb in 0..builder.blocks().len() {
+        ReplaceLocal { from: 1, to: copy }.visit_basic_block(builder.block_mut(bb));
+    }
+    let copy_stmt = Statement {
+        kind: StatementKind::Assign(Place::from(copy), Rvalue::Use(Operand::Copy(Place::from(1)))),
+        span: body.span,
+    };
+    builder.insert_statement(0, 0, copy_stmt);
+
+    // Move everything but the copy into a new block.
+    let rest = builder.split_block(0, 1);
+    let new_body = builder.build().unwrap();
+
+    assert_eq!(new_body.locals().len(), body.locals().len() + 1);
+    assert_eq!(new_body.blocks.len(), body.blocks.len() + 1);
+    assert_eq!(new_body.blocks[0].statements.len(), 1);
+    assert_eq!(new_body.blocks[0].terminator.kind, TerminatorKind::Goto { target: rest });
+    assert_eq!(
+        new_body.blocks[rest].terminator.successors(),
+        body.blocks[0].terminator.successors()
+    );
+    assert_eq!(LocalUses::count(&new_body, 1), 1);
+    assert_eq!(LocalUses::count(&new_body, copy), orig_uses + 1);
+
+    let mut dump = vec![];
+    new_body.dump(&mut dump, "add").unwrap();
+    let dump = String::from_utf8(dump).unwrap();
+    assert!(dump.contains(&format!(" _{copy}: u64;")), "{dump}");
+
+    // Edges to blocks or locals that don't exist are rejected.
+    let mut builder = BodyBuilder::from_body(body.clone());
+    let unreachable = Terminator { kind: TerminatorKind::Goto { target: 100 }, span: body.span };
+    builder.new_block(BasicBlock { statements: vec![], terminator: unreachable });
+    assert!(builder.build().is_err());
+
+    let mut builder = BodyBuilder::from_body(body.clone());
+    builder.push_statement(0, Statement { kind: StatementKind::StorageLive(100), span: body.span });
+    assert!(builder.build().is_err());
+
+    ControlFlow::Continue(())
+}
+
+struct ReplaceLocal {
+    from: Local,
+    to: Local,
+}
+
+impl MutMirVisitor for ReplaceLocal {
+    fn visit_local(&mut self, local: &mut Local, _ptx: PlaceContext, _location: Location) {
+        if *local == self.from {
+            *local = self.to;
+        }
+    }
+}
+
+struct LocalUses {
+    local: Local,
+    count: usize,
+}
+
+impl LocalUses {
+    /// Count the uses of a local in the basic blocks of a body.
+    fn count(body: &Body, local: Local) -> usize {
+        let mut visitor = LocalUses { local, count: 0 };
+        for bb in &body.blocks {
+            visitor.visit_basic_block(bb);
+        }
+        visitor.count
+    }
+}
+
+impl MirVisitor for LocalUses {
+    fn visit_local(&mut self, local: &Local, _ptx: PlaceContext, _location: Location) {
+        if *local == self.local {
+            self.count += 1;
+        }
+    }
+}
+
+fn get_item<'a>(
+    items: &'a CrateItems,
+    item: (ItemKind, &str),
+) -> Option<&'a stable_mir::CrateItem> {
+    items.iter().find(|crate_item| (item.0 == crate_item.kind()) && crate_item.name() == item.1)
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "body_builder_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    run!(args, test_builder).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        pub fn add(x: u64, y: u64) -> u64 {{
+            if x > y {{ x - y }} else {{ x + y }}
+        }}
+        "#
+    )?;
+    Ok(())
+}
@@ -0,0 +1,245 @@
+//@ run-pass
+//! Test the dataflow framework and the analyses provided by StableMIR.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+//@ edition: 2021
+
+#![feature(rustc_private)]
+#![feature(assert_matches)]
+#![feature(control_flow_enum)]
+
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::mir::dataflow::{
+    Analysis, MaybeBorrowedLocals, MaybeInitializedLocals, MaybeLiveLocals, ProgramPoint,
+};
+use stable_mir::mir::{Body, Local, Rvalue, StatementKind, TerminatorKind, RETURN_LOCAL};
+use stable_mir::{CrateDef, CrateItems, ItemKind};
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+fn test_dataflow() -> ControlFlow<()> {
+    let items = stable_mir::all_local_items();
+    check_read_through_ref(&get_item(&items, (ItemKind::Fn, "read_through_ref")).unwrap().body());
+    check_loop(&get_item(&items, (ItemKind::Fn, "sum_to")).unwrap().body());
+    check_call(&get_item(&items, (ItemKind::Fn, "call_then_use")).unwrap().body());
+    ControlFlow::Continue(())
+}
+
+fn check_read_through_ref(body: &Body) {
+    let start = ProgramPoint { block: 0, statement_index: 0 };
+    let ret = return_point(body);
+    let arg = 1;
+
+    let mut live = MaybeLiveLocals.iterate_to_fixpoint(body).into_cursor(body);
+    live.seek_before(start);
+    assert!(live.get().contains(arg));
+    assert!(!live.get().contains(RETURN_LOCAL));
+    live.seek_before(ret);
+    assert_eq!(live.get().iter().collect::<Vec<_>>(), vec![RETURN_LOCAL]);
+
+    let mut init = MaybeInitializedLocals.iterate_to_fixpoint(body).into_cursor(body);
+    init.seek_before(start);
+    assert_eq!(init.get().iter().collect::<Vec<_>>(), vec![arg]);
+    init.seek_before(ret);
+    assert!(init.get().contains(RETURN_LOCAL));
+
+    // The local that is borrowed is marked as such right after the borrow, and stops being
+    // borrowed once its storage is dead.
+    let (borrow, borrowed) = body
+        .blocks
+        .iter()
+        .enumerate()
+        .find_map(|(block, bb)| {
+            bb.statements.iter().enumerate().find_map(|(statement_index, stmt)| {
+                match &stmt.kind {
+                    StatementKind::Assign(_, Rvalue::Ref(_, _, place)) => {
+                        Some((ProgramPoint { block, statement_index }, place.local))
+                    }
+                    _ => None,
+                }
+            })
+        })
+        .expect("Expected a borrow");
+    let mut borrowed_locals = MaybeBorrowedLocals.iterate_to_fixpoint(body).into_cursor(body);
+    borrowed_locals.seek_before(borrow);
+    assert!(!borrowed_locals.get().contains(borrowed));
+    borrowed_locals.seek_after(borrow);
+    assert!(borrowed_locals.get().contains(borrowed));
+    borrowed_locals.seek_before(ret);
+    assert!(!borrowed_locals.get().contains(borrowed));
+}
+
+/// The states at the loop header depend on the back edge, so they are only correct once the
+/// analyses have iterated to a fixpoint.
+fn check_loop(body: &Body) {
+    let (n, total, i, last) = (
+        local_named(body, "n"),
+        local_named(body, "total"),
+        local_named(body, "i"),
+        local_named(body, "last"),
+    );
+    let (back_edge, header) = body
+        .blocks
+        .iter()
+        .enumerate()
+        .find_map(|(block, bb)| match bb.terminator.kind {
+            TerminatorKind::Goto { target } if target <= block => {
+                Some((ProgramPoint { block, statement_index: bb.statements.len() }, target))
+            }
+            _ => None,
+        })
+        .expect("Expected a back edge");
+    let header_start = ProgramPoint { block: header, statement_index: 0 };
+
+    // `n` is only read by the loop condition and the loop body, so it's live at the end of the
+    // loop body because of the back edge. `last` is overwritten before every read of it.
+    let mut live = MaybeLiveLocals.iterate_to_fixpoint(body).into_cursor(body);
+    live.seek_before(back_edge);
+    for local in [n, total, i] {
+        assert!(live.get().contains(local), "{local} isn't live at the back edge");
+    }
+    assert!(!live.get().contains(last));
+    live.seek_before(header_start);
+    for local in [n, total, i] {
+        assert!(live.get().contains(local), "{local} isn't live at the loop header");
+    }
+    assert!(!live.get().contains(last));
+
+    // `last` is only initialized in the loop body, so it may be initialized at the loop header
+    // through the back edge only.
+    let mut init = MaybeInitializedLocals.iterate_to_fixpoint(body).into_cursor(body);
+    init.seek_before(ProgramPoint { block: 0, statement_index: 0 });
+    assert!(!init.get().contains(last));
+    init.seek_before(back_edge);
+    assert!(init.get().contains(last));
+    init.seek_before(header_start);
+    for local in [n, total, i, last] {
+        assert!(init.get().contains(local), "{local} isn't initialized at the loop header");
+    }
+}
+
+/// The destination of a call is only assigned on the edge to its return target.
+fn check_call(body: &Body) {
+    let (x, y) = (local_named(body, "x"), local_named(body, "y"));
+    let (call, ret) = body
+        .blocks
+        .iter()
+        .enumerate()
+        .find_map(|(block, bb)| match &bb.terminator.kind {
+            TerminatorKind::Call { destination, target: Some(target), .. } => {
+                assert_eq!(destination.local, y);
+                Some((ProgramPoint { block, statement_index: bb.statements.len() }, *target))
+            }
+            _ => None,
+        })
+        .expect("Expected a call");
+    let ret_start = ProgramPoint { block: ret, statement_index: 0 };
+
+    let mut live = MaybeLiveLocals.iterate_to_fixpoint(body).into_cursor(body);
+    live.seek_before(ret_start);
+    assert!(live.get().contains(x));
+    assert!(live.get().contains(y));
+    live.seek_before(call);
+    assert!(live.get().contains(x));
+    assert!(!live.get().contains(y));
+
+    let mut init = MaybeInitializedLocals.iterate_to_fixpoint(body).into_cursor(body);
+    init.seek_before(call);
+    assert!(!init.get().contains(y));
+    init.seek_after(call);
+    assert!(!init.get().contains(y));
+    init.seek_before(ret_start);
+    assert!(init.get().contains(x));
+    assert!(init.get().contains(y));
+}
+
+/// The local holding the user variable `name`.
+fn local_named(body: &Body, name: &str) -> Local {
+    body.var_debug_info
+        .iter()
+        .find(|info| info.name == name)
+        .and_then(|info| info.local())
+        .unwrap_or_else(|| panic!("Expected a local for `{name}`"))
+}
+
+/// The location of the return terminator of a body.
+fn return_point(body: &Body) -> ProgramPoint {
+    let (block, bb) = body
+        .blocks
+        .iter()
+        .enumerate()
+        .find(|(_, bb)| bb.terminator.kind == TerminatorKind::Return)
+        .unwrap();
+    ProgramPoint { block, statement_index: bb.statements.len() }
+}
+
+fn get_item<'a>(
+    items: &'a CrateItems,
+    item: (ItemKind, &str),
+) -> Option<&'a stable_mir::CrateItem> {
+    items.iter().find(|crate_item| (item.0 == crate_item.kind()) && crate_item.name() == item.1)
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "dataflow_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    run!(args, test_dataflow).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+        pub fn read_through_ref(x: u32) -> u32 {{
+            let y = [x, 1];
+            let r = &y;
+            if x > 10 {{ r[0] }} else {{ r[1] }}
+        }}
+
+        pub fn sum_to(n: u32) -> u32 {{
+            let mut total = 0;
+            let mut i = 0;
+            let mut last;
+            while i < n {{
+                if i % 2 == 0 {{ last = i; }} else {{ last = n; }}
+                total += last;
+                i += 1;
+            }}
+            total
+        }}
+
+        pub fn call_then_use(x: u32) -> u32 {{
+            let y = double(x);
+            y + x
+        }}
+
+        fn double(x: u32) -> u32 {{
+            x * 2
+        }}
+        "#
+    )?;
+    Ok(())
+}
@@ -1,4 +1,5 @@
 //@ compile-flags: --error-format human-annotate-rs -Z unstable-options
+//@ ignore-sgx std::os::fortanix_sgx::usercalls::alloc::Iter changes compiler suggestions
 //@ error-pattern:cannot find type `Iter` in this scope
 
 pub fn main() {
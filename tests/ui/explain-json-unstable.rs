@@ -0,0 +1 @@
+//@ compile-flags: --explain E0384 --error-format=json
@@ -0,0 +1,2 @@
+//@ compile-flags: --explain E0384 --error-format=json -Z unstable-options
+//@ check-pass
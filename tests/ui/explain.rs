@@ -1,2 +1,2 @@
-//@ compile-flags: --explain E0591
+//@ compile-flags: --explain E0591 --error-format=human
 //@ check-pass
@@ -0,0 +1,5 @@
+//@ compile-flags: --test
+//@ edition: 2021
+
+#[test]
+async fn async_test() {} //~ ERROR async functions as tests are experimental
@@ -0,0 +1,3 @@
+//@ compile-flags: --print=crate-items-json
+
+fn main() {}
@@ -0,0 +1,11 @@
+//@ compile-flags: --test
+
+#![feature(test)]
+
+extern crate test;
+
+#[test]
+#[test_generator] //~ ERROR the `#[test_generator]` attribute is an experimental feature
+fn cases() -> Vec<test::TestCase> {
+    Vec::new()
+}
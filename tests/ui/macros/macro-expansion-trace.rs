@@ -0,0 +1,45 @@
+//@ compile-flags: -Z macro-expansion-trace
+
+// Errors inside a `macro_rules!` expansion show the code the expansion produced.
+
+macro_rules! make_fn {
+    ($name:ident, $value:expr) => {
+        fn $name() -> u32 {
+            let x: u32 = $value;
+            let y: bool = x; //~ ERROR mismatched types
+            x
+        }
+    };
+}
+
+make_fn!(answer, 42);
+
+// Only the repetition that the error comes from is highlighted.
+macro_rules! make_bindings {
+    ($($ty:ty),*) => {
+        fn bindings() {
+            $(let _: $ty = 0u32;)* //~ ERROR mismatched types
+        }
+    };
+}
+
+make_bindings!(u32, bool);
+
+// Each of the nested expansions gets a note.
+macro_rules! inner {
+    () => {
+        let _: bool = 0u32; //~ ERROR mismatched types
+    };
+}
+
+macro_rules! outer {
+    () => {
+        fn nested() {
+            inner!();
+        }
+    };
+}
+
+outer!();
+
+fn main() {}
@@ -0,0 +1,59 @@
+//@ no-prefer-dynamic
+//@ compile-flags: --test
+//@ edition: 2021
+//@ run-flags: --test-threads=1
+//@ run-pass
+//@ check-run-results
+//@ normalize-stdout-test: "finished in \d+\.\d+s" -> "finished in $$TIME"
+//@ needs-threads
+//@ needs-unwind
+
+// Checks that `#[test] async fn` runs on libtest's built-in executor.
+
+#![cfg(test)]
+#![feature(async_test)]
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread;
+
+// Returns `Pending` once, and is woken from another thread.
+struct WokenFromThread(bool);
+
+impl Future for WokenFromThread {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        let waker = cx.waker().clone();
+        thread::spawn(move || waker.wake());
+        Poll::Pending
+    }
+}
+
+#[test]
+async fn ready() {
+    assert_eq!(async { 1 + 1 }.await, 2);
+}
+
+#[test]
+async fn pending() {
+    WokenFromThread(false).await;
+}
+
+#[test]
+async fn returns_result() -> Result<(), String> {
+    WokenFromThread(false).await;
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "after await")]
+async fn panics() {
+    WokenFromThread(false).await;
+    panic!("panicked after await");
+}
@@ -0,0 +1,39 @@
+//@ no-prefer-dynamic
+//@ compile-flags: --test
+//@ run-flags: --test-threads=1
+//@ run-pass
+//@ check-run-results
+//@ normalize-stdout-test: "finished in \d+\.\d+s" -> "finished in $$TIME"
+//@ needs-unwind
+
+// Checks that a `#[test_generator]` function expands into one test per case.
+
+#![cfg(test)]
+#![feature(test, test_generator)]
+
+extern crate test;
+
+use test::TestCase;
+
+#[test]
+#[test_generator]
+fn parse() -> Vec<TestCase> {
+    [("zero", "0", 0), ("one", "1", 1), ("negative", "-1", -1)]
+        .into_iter()
+        .map(|(name, input, expected)| {
+            TestCase::new(name, move || assert_eq!(input.parse::<i32>().unwrap(), expected))
+        })
+        .chain([TestCase::new("empty", || {}).ignore_with_message("not yet implemented")])
+        .collect()
+}
+
+#[test]
+#[test_generator]
+#[should_panic(expected = "InvalidDigit")]
+fn parse_invalid() -> impl IntoIterator<Item = TestCase> {
+    ["a", "1.5"].map(|input| {
+        TestCase::new(input, move || {
+            input.parse::<i32>().unwrap();
+        })
+    })
+}
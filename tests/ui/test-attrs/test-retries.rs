@@ -0,0 +1,22 @@
+//@ compile-flags: --test
+//@ run-pass
+//@ run-flags: --test-threads=1 --retries=2 -Zunstable-options
+//@ check-run-results
+//@ normalize-stdout-test: "finished in \d+\.\d+s" -> "finished in $$TIME"
+//@ only-nightly
+
+// Checks that a test passing when it is retried is reported as flaky.
+
+#![cfg(test)]
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static FAILED_ONCE: AtomicBool = AtomicBool::new(false);
+
+#[test]
+fn flaky() -> Result<(), &'static str> {
+    if FAILED_ONCE.swap(true, Ordering::SeqCst) { Ok(()) } else { Err("fails on the first attempt") }
+}
+
+#[test]
+fn passes() {}
@@ -0,0 +1,32 @@
+//@ compile-flags: --test
+//@ run-fail
+//@ run-flags: --test-threads=1 --test-timeout=100ms -Zunstable-options
+//@ check-run-results
+//@ normalize-stdout-test: "finished in \d+\.\d+s" -> "finished in $$TIME"
+//@ only-nightly
+//@ needs-threads
+
+// Checks that a test exceeding `--test-timeout` fails, and that `#[test_timeout]`
+// overrides the option.
+
+#![cfg(test)]
+#![feature(test_timeout)]
+
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn fast() {}
+
+#[test]
+fn hangs() {
+    loop {
+        thread::park();
+    }
+}
+
+#[test]
+#[test_timeout = "10m"]
+fn slow_but_allowed() {
+    thread::sleep(Duration::from_millis(300));
+}
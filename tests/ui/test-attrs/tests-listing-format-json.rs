@@ -19,3 +19,9 @@ fn z_test() {}
 
 #[test]
 fn a_test() {}
+
+mod nested {
+    #[test]
+    #[should_panic(expected = "\"quoted\" message")]
+    fn panics() {}
+}